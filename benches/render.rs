@@ -0,0 +1,78 @@
+#[macro_use]
+extern crate bencher;
+extern crate deuterium;
+
+use bencher::Bencher;
+use deuterium::*;
+
+fn select_in_list(b: &mut Bencher) {
+    let jedi_table = TableDef::new("jedi");
+    let id = NamedField::<i32>::field_of("id", &jedi_table);
+    let query = jedi_table.select_all().where_(id.in_((0..5000).collect::<Vec<i32>>()));
+
+    b.iter(|| query.to_final_sql(&mut SqlContext::new(Box::new(sql::PostgreSqlAdapter))).unwrap());
+}
+
+fn insert_many_rows(b: &mut Bencher) {
+    let jedi_table = TableDef::new("jedi");
+    let name = NamedField::<String>::field_of("name", &jedi_table);
+    let side = NamedField::<bool>::field_of("side", &jedi_table);
+    let force_level = NamedField::<i32>::field_of("force_level", &jedi_table);
+
+    let mut query = jedi_table.insert_fields(&[&name, &side, &force_level]);
+    for i in 0..2000 {
+        query.push_untyped(&[format!("Jedi {}", i).as_expr(), (i % 2 == 0).as_expr(), i.as_expr()]);
+    }
+
+    b.iter(|| query.to_final_sql(&mut SqlContext::new(Box::new(sql::PostgreSqlAdapter))).unwrap());
+}
+
+fn select_joins(b: &mut Bencher) {
+    let jedi_table = TableDef::new("jedi");
+    let padawan_table = TableDef::new("padawan");
+    let name = NamedField::<String>::field_of("name", &jedi_table).qual();
+    let master = NamedField::<String>::field_of("master", &padawan_table).qual();
+    let force_level = NamedField::<i32>::field_of("force_level", &jedi_table).qual();
+
+    let query = jedi_table.select_all()
+        .inner_join(&padawan_table, master.is(name.clone()))
+        .where_(name.is("Luke".to_string()).or(force_level.gt(100)))
+        .order_by(&name)
+        .limit(10);
+
+    b.iter(|| query.to_final_sql(&mut SqlContext::new(Box::new(sql::PostgreSqlAdapter))).unwrap());
+}
+
+fn insert_many_tenant_rows(b: &mut Bencher) {
+    let jedi_table = TableDef::new("jedi").tenant("tenant_id");
+    let name = NamedField::<String>::field_of("name", &jedi_table);
+
+    let mut query = jedi_table.insert_fields(&[&name]);
+    for i in 0..2000 {
+        query.push_untyped(&[format!("Jedi {}", i).as_expr()]);
+    }
+
+    b.iter(|| {
+        let mut ctx = SqlContext::new(Box::new(sql::PostgreSqlAdapter));
+        ctx.set_tenant(42i32);
+        query.to_final_sql(&mut ctx).unwrap()
+    });
+}
+
+fn update_delete_from(b: &mut Bencher) {
+    let jedi_table = TableDef::new("jedi");
+    let padawan_table = TableDef::new_with_alias("padawan", "p");
+    let name = NamedField::<String>::field_of("name", &jedi_table).qual();
+    let master = NamedField::<String>::field_of("master", &padawan_table);
+
+    let update = jedi_table.update().field(name.set(&"Luke".to_string())).from(&padawan_table).where_(master.is(name.clone()));
+    let delete = jedi_table.delete().using(&padawan_table).where_(master.is(name.clone()));
+
+    b.iter(|| {
+        let mut ctx = SqlContext::new(Box::new(sql::PostgreSqlAdapter));
+        (update.to_final_sql(&mut ctx).unwrap(), delete.to_final_sql(&mut ctx).unwrap())
+    });
+}
+
+benchmark_group!(benches, select_in_list, insert_many_rows, select_joins, insert_many_tenant_rows, update_delete_from);
+benchmark_main!(benches);
@@ -0,0 +1,340 @@
+//! `#[derive(Model)]` for Deuterium.
+//!
+//! ```ignore
+//! #[derive(Clone, Debug, Model)]
+//! #[model(table = "jedi")]
+//! pub struct Jedi {
+//!     #[primary_key]
+//!     pub id: i32,
+//!     pub name: String,
+//!     pub force_level: Option<i8>,
+//! }
+//! ```
+//!
+//! Implements `deuterium::Model` for the struct and declares the table definition
+//! `JediTable` with a typed `NamedField` for every field. Set another name for it with
+//! `#[model(table = "jedi", table_struct = "Jedis")]`. Column names are the same as
+//! field names. Mark one or more fields with `#[primary_key]`, several of them make
+//! a composite key.
+
+#![deny(warnings, unused, future_incompatible)]
+
+extern crate proc_macro;
+extern crate proc_macro2;
+#[macro_use]
+extern crate quote;
+#[macro_use]
+extern crate syn;
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+
+#[proc_macro_derive(Model, attributes(model, primary_key))]
+pub fn derive_model(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as syn::DeriveInput);
+    match expand(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into()
+    }
+}
+
+struct Column {
+    ident: syn::Ident,
+    ty: syn::Type,
+    primary_key: bool,
+}
+
+struct Options {
+    table: syn::LitStr,
+    table_struct: syn::Ident,
+}
+
+fn options(input: &syn::DeriveInput) -> syn::Result<Options> {
+    let mut table = None;
+    let mut table_struct = None;
+
+    for attr in input.attrs.iter().filter(|attr| attr.path.is_ident("model")) {
+        let list = match attr.parse_meta()? {
+            syn::Meta::List(list) => list,
+            meta => return Err(syn::Error::new_spanned(meta, "expected #[model(table = \"...\")]"))
+        };
+
+        for nested in list.nested.iter() {
+            let pair = match *nested {
+                syn::NestedMeta::Meta(syn::Meta::NameValue(ref pair)) => pair,
+                ref nested => return Err(syn::Error::new_spanned(nested, "expected `key = \"value\"`"))
+            };
+            let value = match pair.lit {
+                syn::Lit::Str(ref value) => value.clone(),
+                ref lit => return Err(syn::Error::new_spanned(lit, "expected a string"))
+            };
+
+            if pair.path.is_ident("table") {
+                table = Some(value);
+            } else if pair.path.is_ident("table_struct") {
+                table_struct = Some(value.parse()?);
+            } else {
+                return Err(syn::Error::new_spanned(&pair.path, "unknown model option, expected `table` or `table_struct`"))
+            }
+        }
+    }
+
+    let table = match table {
+        Some(table) => table,
+        None => return Err(syn::Error::new(Span::call_site(), "missing #[model(table = \"...\")]"))
+    };
+    let table_struct = match table_struct {
+        Some(table_struct) => table_struct,
+        None => syn::Ident::new(&format!("{}Table", input.ident), input.ident.span())
+    };
+
+    Ok(Options { table, table_struct })
+}
+
+fn columns(input: &syn::DeriveInput) -> syn::Result<Vec<Column>> {
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(&input.generics, "models can't be generic"))
+    }
+
+    let fields = match input.data {
+        syn::Data::Struct(syn::DataStruct { fields: syn::Fields::Named(ref fields), .. }) => fields,
+        _ => return Err(syn::Error::new(Span::call_site(), "Model can only be derived for structs with named fields"))
+    };
+
+    let mut columns = vec![];
+    for field in fields.named.iter() {
+        let ident = field.ident.clone().unwrap();
+        // The table struct holds its `TableDef` in `table`.
+        if ident == "table" {
+            return Err(syn::Error::new_spanned(&ident, "a model can't have a field named `table`"))
+        }
+        columns.push(Column {
+            ident,
+            ty: field.ty.clone(),
+            primary_key: field.attrs.iter().any(|attr| attr.path.is_ident("primary_key"))
+        });
+    }
+
+    if !columns.iter().any(|column| column.primary_key) {
+        return Err(syn::Error::new(Span::call_site(), "a model needs at least one #[primary_key] field"))
+    }
+
+    Ok(columns)
+}
+
+fn expand(input: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let options = options(input)?;
+    let columns = columns(input)?;
+
+    let model = &input.ident;
+    let vis = &input.vis;
+    let table = &options.table_struct;
+    let name = &options.table;
+
+    let idents: Vec<&syn::Ident> = columns.iter().map(|column| &column.ident).collect();
+    let types: Vec<&syn::Type> = columns.iter().map(|column| &column.ty).collect();
+    let names: Vec<String> = idents.iter().map(|ident| ident.to_string()).collect();
+
+    let keys: Vec<&syn::Ident> = columns.iter().filter(|column| column.primary_key).map(|column| &column.ident).collect();
+    let first_key = keys[0];
+    let other_keys = &keys[1..];
+
+    let (updated, updated_names): (Vec<&syn::Ident>, Vec<String>) = columns.iter()
+        .filter(|column| !column.primary_key)
+        .map(|column| (&column.ident, column.ident.to_string()))
+        .unzip();
+
+    Ok(quote! {
+        #[derive(Clone, Debug)]
+        #vis struct #table {
+            pub table: ::deuterium::TableDef,
+            #(pub #idents: ::deuterium::NamedField<#types>,)*
+        }
+
+        impl #table {
+            pub fn new() -> #table {
+                #table::with_table(::deuterium::TableDef::new(#name))
+            }
+
+            pub fn alias(alias: &str) -> #table {
+                #table::with_table(::deuterium::TableDef::new_with_alias(#name, alias))
+            }
+
+            /// Adds a default scope, see `TableDef::scope`.
+            pub fn scope(&self, predicate: ::deuterium::SharedPredicate) -> #table {
+                let mut table = self.clone();
+                table.table = table.table.scope(predicate);
+                table
+            }
+
+            /// Marks the table as soft-deleted, see `TableDef::soft_delete`.
+            pub fn soft_delete(&self, column: &str) -> #table {
+                let mut table = self.clone();
+                table.table = table.table.soft_delete(column);
+                table
+            }
+
+            /// Marks the table as tenant-scoped, see `TableDef::tenant`.
+            pub fn tenant(&self, column: &str) -> #table {
+                let mut table = self.clone();
+                table.table = table.table.tenant(column);
+                table
+            }
+
+            fn with_table(table: ::deuterium::TableDef) -> #table {
+                #table {
+                    #(#idents: ::deuterium::NamedField::<#types>::field_of(#names, &table),)*
+                    table: table,
+                }
+            }
+        }
+
+        impl ::deuterium::From for #table {
+            fn as_sql(&self) -> &dyn deuterium::FromToSql {
+                &self.table
+            }
+
+            fn upcast_from(&self) -> ::deuterium::SharedFrom {
+                ::deuterium::From::upcast_from(&self.table)
+            }
+
+            fn get_scopes(&self) -> Vec<::deuterium::SharedPredicate> {
+                ::deuterium::From::get_scopes(&self.table)
+            }
+
+            fn get_soft_delete(&self) -> Option<::deuterium::DeletedAtField> {
+                ::deuterium::From::get_soft_delete(&self.table)
+            }
+
+            fn get_tenant(&self) -> Option<::deuterium::TenantField> {
+                ::deuterium::From::get_tenant(&self.table)
+            }
+
+            fn as_table(&self) -> Option<&dyn deuterium::Table> {
+                Some(self)
+            }
+        }
+
+        impl ::deuterium::Table for #table {
+            fn upcast_table(&self) -> ::deuterium::SharedTable {
+                ::deuterium::Table::upcast_table(&self.table)
+            }
+
+            fn get_table_name(&self) -> &String {
+                ::deuterium::Table::get_table_name(&self.table)
+            }
+
+            fn get_table_alias(&self) -> &Option<String> {
+                ::deuterium::Table::get_table_alias(&self.table)
+            }
+
+            fn get_scopes(&self) -> Vec<::deuterium::SharedPredicate> {
+                ::deuterium::Table::get_scopes(&self.table)
+            }
+
+            fn get_soft_delete(&self) -> Option<::deuterium::DeletedAtField> {
+                ::deuterium::Table::get_soft_delete(&self.table)
+            }
+
+            fn get_tenant(&self) -> Option<::deuterium::TenantField> {
+                ::deuterium::Table::get_tenant(&self.table)
+            }
+        }
+
+        impl ::deuterium::Selectable<#model> for #table {}
+        impl ::deuterium::Insertable<#model> for #table {}
+        impl ::deuterium::Updatable<#model> for #table {}
+        impl ::deuterium::Deletable<#model> for #table {}
+
+        impl ::deuterium::Model for #model {
+            type Table = #table;
+
+            fn table() -> #table {
+                #table::new()
+            }
+
+            fn columns() -> Vec<::deuterium::SharedField> {
+                use ::deuterium::Field;
+                let table = #table::new();
+                vec![#(table.#idents.upcast_field(),)*]
+            }
+
+            fn primary_key() -> Vec<::deuterium::SharedField> {
+                use ::deuterium::Field;
+                let table = #table::new();
+                vec![#(table.#keys.upcast_field(),)*]
+            }
+
+            fn values(&self) -> Vec<::deuterium::SharedExpression> {
+                use ::deuterium::UntypedExpression;
+                vec![#(self.#idents.upcast_expression(),)*]
+            }
+
+            fn field_updates(&self) -> Vec<::deuterium::SharedFieldUpdate> {
+                use ::deuterium::FieldUpd;
+                let table = #table::new();
+                vec![#(::deuterium::FieldUpdate {
+                    field: table.#updated.clone(),
+                    value: ::deuterium::InsertValue::new(&self.#updated)
+                }.upcast_field_update(),)*]
+            }
+
+            fn primary_key_predicate(&self) -> ::deuterium::SharedPredicate {
+                use ::deuterium::{ToAndPredicate, ToIsPredicate};
+                let table = #table::new();
+                let predicate = table.#first_key.is(self.#first_key.clone());
+                #(let predicate = predicate.and(table.#other_keys.is(self.#other_keys.clone()));)*
+                predicate
+            }
+
+            fn field_update(&self, column: &str) -> Option<::deuterium::SharedFieldUpdate> {
+                use ::deuterium::FieldUpd;
+                let table = #table::new();
+                #(if column == #updated_names {
+                    return Some(::deuterium::FieldUpdate {
+                        field: table.#updated.clone(),
+                        value: ::deuterium::InsertValue::new(&self.#updated)
+                    }.upcast_field_update())
+                })*
+                None
+            }
+
+            fn column_predicate(&self, column: &str) -> Option<::deuterium::SharedPredicate> {
+                use ::deuterium::ToIsPredicate;
+                let table = #table::new();
+                #(if column == #names {
+                    return Some(table.#idents.is(self.#idents.clone()))
+                })*
+                None
+            }
+
+            fn assign(&mut self, column: &str, value: &dyn std::any::Any) -> bool {
+                #(if column == #names {
+                    return match value.downcast_ref::<#types>() {
+                        Some(value) => { self.#idents = value.clone(); true },
+                        None => false
+                    }
+                })*
+                false
+            }
+
+            fn value_of(&self, column: &str) -> Option<&dyn std::any::Any> {
+                #(if column == #names {
+                    return Some(&self.#idents)
+                })*
+                None
+            }
+
+            ::deuterium::__model_from_row!(#model, #(#idents),*);
+        }
+
+        impl ::deuterium::NamedValues for #model {
+            fn named_value(&self, name: &str) -> Option<::deuterium::Value> {
+                #(if name == #names {
+                    return Some(::deuterium::Value::from(self.#idents.clone()))
+                })*
+                None
+            }
+        }
+    })
+}
@@ -0,0 +1,66 @@
+extern crate deuterium;
+
+use std::env;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::process;
+
+use deuterium::codegen;
+
+fn usage() -> ! {
+    let _ = writeln!(io::stderr(), "Usage: deuterium-codegen <schema.sql|schema.json|-> [-o <output.rs>]");
+    process::exit(2)
+}
+
+fn fail(msg: &str) -> ! {
+    let _ = writeln!(io::stderr(), "deuterium-codegen: {}", msg);
+    process::exit(1)
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let mut input = None;
+    let mut output = None;
+    let mut i = 0;
+    while i < args.len() {
+        match &args[i][..] {
+            "-o" | "--output" => {
+                i += 1;
+                output = Some(args.get(i).cloned().unwrap_or_else(|| usage()));
+            },
+            "-h" | "--help" => usage(),
+            path if input.is_none() => input = Some(path.to_string()),
+            _ => usage()
+        }
+        i += 1;
+    }
+
+    let input = input.unwrap_or_else(|| usage());
+
+    let mut schema = String::new();
+    let read = if input == "-" {
+        io::stdin().read_to_string(&mut schema)
+    } else {
+        File::open(&input).and_then(|mut f| f.read_to_string(&mut schema))
+    };
+
+    if let Err(err) = read {
+        fail(&format!("can't read {}: {}", input, err))
+    }
+
+    let tables = codegen::parse_schema(&schema).unwrap_or_else(|err| fail(&err.to_string()));
+    for column in tables.iter().flat_map(|table| table.skipped.iter()) {
+        let _ = writeln!(io::stderr(), "deuterium-codegen: warning: {}", column);
+    }
+    let source = codegen::generate(&tables);
+
+    let written = match output {
+        Some(ref path) => File::create(path).and_then(|mut f| f.write_all(source.as_bytes())),
+        None => io::stdout().write_all(source.as_bytes())
+    };
+
+    if let Err(err) = written {
+        fail(&format!("can't write output: {}", err))
+    }
+}
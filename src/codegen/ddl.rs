@@ -0,0 +1,383 @@
+use super::{CodegenError, CodegenResult, ColumnDef, ColumnType, SkippedColumn, TableSchema};
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    Literal(String),
+    Punct(char),
+}
+
+impl Token {
+    fn is_keyword(&self, keyword: &str) -> bool {
+        match self {
+            Token::Word(word) => word.eq_ignore_ascii_case(keyword),
+            _ => false
+        }
+    }
+
+    fn text(&self) -> String {
+        match *self {
+            Token::Word(ref word) => word.to_string(),
+            Token::Quoted(ref word) => format!("\"{}\"", word),
+            Token::Literal(ref lit) => format!("'{}'", lit),
+            Token::Punct(ch) => ch.to_string(),
+        }
+    }
+}
+
+fn tokenize(input: &str) -> CodegenResult<Vec<Token>> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = vec![];
+    let mut i = 0;
+
+    while i < chars.len() {
+        let ch = chars[i];
+        if ch.is_whitespace() {
+            i += 1;
+        } else if ch == '-' && chars.get(i + 1) == Some(&'-') {
+            while i < chars.len() && chars[i] != '\n' { i += 1; }
+        } else if ch == '/' && chars.get(i + 1) == Some(&'*') {
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) { i += 1; }
+            if i >= chars.len() { return Err(CodegenError::UnexpectedEnd) }
+            i += 2;
+        } else if ch == '"' || ch == '\'' || ch == '`' {
+            let mut value = String::new();
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    None => return Err(CodegenError::UnexpectedEnd),
+                    Some(&c) if c == ch && chars.get(i + 1) == Some(&ch) => { value.push(c); i += 2; },
+                    Some(&c) if c == ch => { i += 1; break },
+                    Some(&c) => { value.push(c); i += 1; }
+                }
+            }
+            tokens.push(if ch == '\'' { Token::Literal(value) } else { Token::Quoted(value) });
+        } else if ch.is_alphanumeric() || ch == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '$') { i += 1; }
+            tokens.push(Token::Word(chars[start..i].iter().cloned().collect()));
+        } else {
+            tokens.push(Token::Punct(ch));
+            i += 1;
+        }
+    }
+
+    Ok(tokens)
+}
+
+enum Column {
+    Supported(ColumnDef),
+    Skipped(SkippedColumn)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> CodegenResult<Token> {
+        let token = self.peek().cloned().ok_or(CodegenError::UnexpectedEnd)?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let matched = self.peek().map(|t| t.is_keyword(keyword)).unwrap_or(false);
+        if matched { self.pos += 1; }
+        matched
+    }
+
+    fn eat_punct(&mut self, ch: char) -> bool {
+        let matched = self.peek() == Some(&Token::Punct(ch));
+        if matched { self.pos += 1; }
+        matched
+    }
+
+    fn expect_punct(&mut self, ch: char) -> CodegenResult<()> {
+        match self.next()? {
+            Token::Punct(c) if c == ch => Ok(()),
+            token => Err(CodegenError::UnexpectedToken(token.text()))
+        }
+    }
+
+    fn ident(&mut self) -> CodegenResult<String> {
+        match self.next()? {
+            Token::Word(word) => Ok(word.to_lowercase()),
+            Token::Quoted(word) => Ok(word),
+            token => Err(CodegenError::UnexpectedToken(token.text()))
+        }
+    }
+
+    /// Skips tokens until `,` or `)` on the current nesting level.
+    fn skip_to_item_end(&mut self) -> CodegenResult<()> {
+        let mut depth = 0;
+        loop {
+            match self.peek() {
+                None => return Err(CodegenError::UnexpectedEnd),
+                Some(&Token::Punct('(')) => depth += 1,
+                Some(&Token::Punct(')')) if depth == 0 => return Ok(()),
+                Some(&Token::Punct(')')) => depth -= 1,
+                Some(&Token::Punct(',')) if depth == 0 => return Ok(()),
+                _ => ()
+            }
+            self.pos += 1;
+        }
+    }
+
+    /// Skips a token together with the parenthesized arguments following it, or a
+    /// parenthesized group on its own.
+    fn skip_token(&mut self) -> CodegenResult<()> {
+        if self.peek() != Some(&Token::Punct('(')) {
+            self.next()?;
+        }
+        self.skip_parens()
+    }
+
+    fn skip_parens(&mut self) -> CodegenResult<()> {
+        if self.eat_punct('(') {
+            self.skip_to_item_end()?;
+            while self.eat_punct(',') { self.skip_to_item_end()?; }
+            self.expect_punct(')')?;
+        }
+        Ok(())
+    }
+
+    fn skip_statement(&mut self) {
+        while let Some(token) = self.peek().cloned() {
+            self.pos += 1;
+            if token == Token::Punct(';') { break }
+        }
+    }
+
+    fn ident_list(&mut self) -> CodegenResult<Vec<String>> {
+        self.expect_punct('(')?;
+        let mut idents = vec![self.ident()?];
+        while self.eat_punct(',') {
+            idents.push(self.ident()?);
+        }
+        self.expect_punct(')')?;
+        Ok(idents)
+    }
+
+    fn create_table(&mut self) -> CodegenResult<Option<TableSchema>> {
+        if !self.eat_keyword("create") { return Ok(None) }
+        let _ = self.eat_keyword("temporary") || self.eat_keyword("temp") || self.eat_keyword("unlogged");
+        if !self.eat_keyword("table") { return Ok(None) }
+
+        if self.eat_keyword("if") && !(self.eat_keyword("not") && self.eat_keyword("exists")) {
+            return Err(CodegenError::UnexpectedToken("IF".to_string()))
+        }
+
+        let mut name = self.ident()?;
+        while self.eat_punct('.') {
+            name = format!("{}.{}", name, self.ident()?);
+        }
+
+        self.expect_punct('(')?;
+
+        let mut columns: Vec<ColumnDef> = vec![];
+        let mut skipped = vec![];
+        let mut primary_key = vec![];
+        loop {
+            if self.eat_punct(')') { break }
+
+            if self.is_table_constraint() {
+                if self.eat_keyword("constraint") {
+                    self.ident()?;
+                }
+                if self.eat_keyword("primary") && self.eat_keyword("key") {
+                    primary_key.extend(self.ident_list()?);
+                }
+                self.skip_to_item_end()?;
+            } else {
+                match self.column(&name)? {
+                    Column::Supported(column) => columns.push(column),
+                    Column::Skipped(column) => skipped.push(column)
+                }
+            }
+
+            if !self.eat_punct(',') {
+                self.expect_punct(')')?;
+                break;
+            }
+        }
+
+        self.skip_statement();
+
+        for column in columns.iter_mut() {
+            if primary_key.contains(&column.name) {
+                column.primary_key = true;
+                column.nullable = false;
+            }
+        }
+
+        Ok(Some(TableSchema { name, columns, skipped }))
+    }
+
+    /// Table constraints start with words that can also name a column, e.g. `check text`,
+    /// so the tokens following them decide.
+    fn is_table_constraint(&self) -> bool {
+        let keyword_at = |offset: usize, keywords: &[&str]| {
+            self.tokens.get(self.pos + offset).map(|t| keywords.iter().any(|kw| t.is_keyword(kw))).unwrap_or(false)
+        };
+        let paren_at = |offset: usize| self.tokens.get(self.pos + offset) == Some(&Token::Punct('('));
+
+        if keyword_at(0, &["constraint"]) {
+            keyword_at(2, &["primary", "unique", "check", "foreign", "exclude"])
+        } else if keyword_at(0, &["primary", "foreign"]) {
+            keyword_at(1, &["key"])
+        } else if keyword_at(0, &["unique"]) {
+            paren_at(1) || keyword_at(1, &["key", "index", "nulls"])
+        } else if keyword_at(0, &["check"]) {
+            paren_at(1)
+        } else if keyword_at(0, &["exclude"]) {
+            paren_at(1) || keyword_at(1, &["using"])
+        } else {
+            false
+        }
+    }
+
+    /// Skips `REFERENCES table [(columns)] [MATCH kind] [ON DELETE | UPDATE action]`,
+    /// whose `SET NULL` action says nothing about the column itself.
+    fn references(&mut self) -> CodegenResult<()> {
+        self.ident()?;
+        while self.eat_punct('.') { self.ident()?; }
+        self.skip_parens()?;
+
+        loop {
+            if self.eat_keyword("match") {
+                self.next()?;
+            } else if self.eat_keyword("on") {
+                self.next()?;
+                if self.eat_keyword("set") || self.eat_keyword("no") {
+                    self.next()?;
+                    self.skip_parens()?;
+                } else {
+                    self.next()?;
+                }
+            } else {
+                return Ok(())
+            }
+        }
+    }
+
+    fn column(&mut self, table: &str) -> CodegenResult<Column> {
+        let name = self.ident()?;
+
+        let stop_words = ["not", "null", "primary", "default", "references", "unique",
+                          "check", "constraint", "collate", "generated"];
+
+        let mut sql_type = String::new();
+        loop {
+            let token = match self.peek() {
+                Some(token) => token.clone(),
+                None => return Err(CodegenError::UnexpectedEnd)
+            };
+
+            match token {
+                Token::Punct(',') | Token::Punct(')') => break,
+                ref t if stop_words.iter().any(|kw| t.is_keyword(kw)) => break,
+                Token::Punct('(') => {
+                    let start = self.pos;
+                    self.expect_punct('(')?;
+                    self.skip_to_item_end()?;
+                    while self.eat_punct(',') { self.skip_to_item_end()?; }
+                    self.expect_punct(')')?;
+                    let modifier: Vec<String> = self.tokens[start..self.pos].iter().map(|t| t.text()).collect();
+                    sql_type.push_str(&modifier.concat());
+                },
+                token => {
+                    self.pos += 1;
+                    if !sql_type.is_empty() && !sql_type.ends_with('(') { sql_type.push(' ') }
+                    sql_type.push_str(&token.text());
+                }
+            }
+        }
+
+        let mut nullable = true;
+        let mut primary_key = false;
+
+        // `NULL` only marks nullability right where a constraint starts, elsewhere
+        // it's a default value or a `SET NULL` action.
+        loop {
+            match self.peek() {
+                Some(&Token::Punct(',')) | Some(&Token::Punct(')')) => break,
+                None => return Err(CodegenError::UnexpectedEnd),
+                Some(_) => ()
+            }
+
+            if self.eat_keyword("not") {
+                if self.eat_keyword("null") {
+                    nullable = false;
+                }
+            } else if self.eat_keyword("null") {
+                nullable = true;
+            } else if self.eat_keyword("primary") {
+                self.eat_keyword("key");
+                primary_key = true;
+            } else if self.eat_keyword("default") {
+                self.skip_token()?;
+                while !self.at_constraint_end() {
+                    self.skip_token()?;
+                }
+            } else if self.eat_keyword("references") {
+                self.references()?;
+            } else {
+                // CHECK, UNIQUE, COLLATE, GENERATED and others with their arguments
+                self.skip_token()?;
+            }
+        }
+
+        Ok(match ColumnType::from_sql(&sql_type) {
+            Some(column_type) => Column::Supported(ColumnDef {
+                name,
+                column_type,
+                nullable: nullable && !primary_key,
+                primary_key,
+            }),
+            None => Column::Skipped(SkippedColumn {
+                table: table.to_string(),
+                name,
+                sql_type
+            })
+        })
+    }
+
+    /// Whether the tokens of a default expression are over: the column ends or another constraint starts.
+    fn at_constraint_end(&self) -> bool {
+        let constraints = ["not", "null", "primary", "unique", "check", "references", "constraint",
+                           "collate", "generated", "default"];
+        match self.peek() {
+            None | Some(&Token::Punct(',')) | Some(&Token::Punct(')')) => true,
+            Some(token) => constraints.iter().any(|kw| token.is_keyword(kw))
+        }
+    }
+}
+
+/// Extracts table definitions from a `CREATE TABLE` script.
+///
+/// Statements other than `CREATE TABLE` are ignored.
+pub fn parse_ddl(input: &str) -> CodegenResult<Vec<TableSchema>> {
+    let mut parser = Parser { tokens: tokenize(input)?, pos: 0 };
+    let mut tables = vec![];
+
+    while parser.peek().is_some() {
+        let start = parser.pos;
+        match parser.create_table()? {
+            Some(table) => tables.push(table),
+            None => {
+                parser.pos = start;
+                parser.skip_statement();
+            }
+        }
+    }
+
+    Ok(tables)
+}
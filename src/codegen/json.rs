@@ -0,0 +1,112 @@
+use serde_json;
+
+use super::{CodegenError, CodegenResult, ColumnDef, ColumnType, SkippedColumn, TableSchema};
+
+fn invalid(msg: &str) -> CodegenError {
+    CodegenError::InvalidSchema(msg.to_string())
+}
+
+fn get_str<'a>(obj: &'a serde_json::Value, key: &str) -> CodegenResult<&'a str> {
+    obj.get(key).and_then(|v| v.as_str()).ok_or(invalid(&format!("`{}` must be a string", key)))
+}
+
+fn skipped(table: &str, column: &str, sql_type: &str) -> SkippedColumn {
+    SkippedColumn {
+        table: table.to_string(),
+        name: column.to_string(),
+        sql_type: sql_type.to_string()
+    }
+}
+
+fn table(value: &serde_json::Value) -> CodegenResult<TableSchema> {
+    let name = get_str(value, "name")?;
+    let columns = value.get("columns").and_then(|v| v.as_array()).ok_or(invalid("`columns` must be an array"))?;
+
+    let mut defs = vec![];
+    let mut skipped_columns = vec![];
+    for column in columns.iter() {
+        let column_name = get_str(column, "name")?;
+        let sql_type = get_str(column, "type")?;
+        let primary_key = column.get("primary_key").and_then(|v| v.as_bool()).unwrap_or(false);
+        match ColumnType::from_sql(sql_type) {
+            Some(column_type) => defs.push(ColumnDef {
+                name: column_name.to_string(),
+                column_type,
+                nullable: !primary_key && column.get("nullable").and_then(|v| v.as_bool()).unwrap_or(true),
+                primary_key,
+            }),
+            None => skipped_columns.push(skipped(name, column_name, sql_type))
+        }
+    }
+
+    Ok(TableSchema { name: name.to_string(), columns: defs, skipped: skipped_columns })
+}
+
+/// Converts rows of `information_schema.columns` into tables, keeping
+/// the order in which tables and columns first appear.
+///
+/// Tables outside of the `public` schema are named `schema.table`. Rows joined with
+/// `information_schema.table_constraints` mark primary key columns with a
+/// `"constraint_type": "PRIMARY KEY"`, such a column may appear once per constraint.
+fn information_schema(rows: &[serde_json::Value]) -> CodegenResult<Vec<TableSchema>> {
+    let mut tables: Vec<TableSchema> = vec![];
+    for row in rows.iter() {
+        let table_name = match row.get("table_schema").and_then(|v| v.as_str()) {
+            Some(schema) if schema != "public" => format!("{}.{}", schema, get_str(row, "table_name")?),
+            _ => get_str(row, "table_name")?.to_string()
+        };
+        let column_name = get_str(row, "column_name")?;
+        let sql_type = get_str(row, "data_type")?;
+        let primary_key = row.get("constraint_type").and_then(|v| v.as_str()) == Some("PRIMARY KEY");
+
+        let idx = match tables.iter().position(|t| t.name == table_name) {
+            Some(idx) => idx,
+            None => {
+                tables.push(TableSchema { name: table_name.clone(), columns: vec![], skipped: vec![] });
+                tables.len() - 1
+            }
+        };
+        let table = &mut tables[idx];
+
+        if let Some(column) = table.columns.iter_mut().find(|c| c.name == column_name) {
+            column.primary_key = column.primary_key || primary_key;
+            column.nullable = column.nullable && !primary_key;
+            continue
+        }
+        if table.skipped.iter().any(|c| c.name == column_name) {
+            continue
+        }
+
+        match ColumnType::from_sql(sql_type) {
+            Some(column_type) => table.columns.push(ColumnDef {
+                name: column_name.to_string(),
+                column_type,
+                nullable: !primary_key && row.get("is_nullable").and_then(|v| v.as_str()).map(|v| v == "YES").unwrap_or(true),
+                primary_key,
+            }),
+            None => table.skipped.push(skipped(&table_name, column_name, sql_type))
+        }
+    }
+
+    Ok(tables)
+}
+
+/// Reads tables from a JSON schema dump.
+///
+/// Two shapes are accepted: `{"tables": [{"name": .., "columns": [{"name": .., "type": ..,
+/// "nullable": .., "primary_key": ..}]}]}` (the top-level object can be replaced with
+/// the bare array of tables) and a flat array of `information_schema.columns` rows.
+pub fn parse_json(input: &str) -> CodegenResult<Vec<TableSchema>> {
+    let value: serde_json::Value = serde_json::from_str(input).map_err(|e| invalid(&e.to_string()))?;
+
+    let items = match value.get("tables") {
+        Some(tables) => tables.as_array().ok_or(invalid("`tables` must be an array"))?,
+        None => value.as_array().ok_or(invalid("expected an object with `tables` or an array"))?
+    };
+
+    if items.iter().any(|item| item.get("column_name").is_some()) {
+        information_schema(items)
+    } else {
+        items.iter().map(table).collect()
+    }
+}
@@ -0,0 +1,164 @@
+//! Offline generator of typed table modules.
+//!
+//! Reads a schema either from a `CREATE TABLE` script or from a JSON dump
+//! and emits Rust source that declares every table together with its typed
+//! `NamedField`s.
+
+use std::fmt;
+
+pub use self::ddl::parse_ddl;
+pub use self::json::parse_json;
+pub use self::rust::generate;
+
+pub mod ddl;
+pub mod json;
+pub mod rust;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum CodegenError {
+    UnexpectedEnd,
+    UnexpectedToken(String),
+    InvalidSchema(String),
+}
+
+impl fmt::Display for CodegenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CodegenError::UnexpectedEnd => write!(f, "unexpected end of input"),
+            CodegenError::UnexpectedToken(ref token) => write!(f, "unexpected token `{}`", token),
+            CodegenError::InvalidSchema(ref msg) => write!(f, "invalid schema: {}", msg),
+        }
+    }
+}
+
+pub type CodegenResult<T> = Result<T, CodegenError>;
+
+/// Rust type of a column as Deuterium sees it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColumnType {
+    Bool,
+    I8,
+    I16,
+    I32,
+    I64,
+    F32,
+    F64,
+    String,
+    ByteList,
+    Json,
+    Timespec,
+    Uuid,
+}
+
+impl ColumnType {
+    /// Maps SQL type name (PostgreSQL flavour) to the column type.
+    pub fn from_sql(sql_type: &str) -> Option<ColumnType> {
+        // Drop type modifiers like `varchar(255)` or `timestamp(3)`
+        let mut base = String::new();
+        let mut depth = 0;
+        for ch in sql_type.to_lowercase().chars() {
+            match ch {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ if depth == 0 => base.push(ch),
+                _ => ()
+            }
+        }
+        let base = base.split_whitespace().collect::<Vec<&str>>().join(" ");
+
+        let ty = match &base[..] {
+            "bool" | "boolean" => ColumnType::Bool,
+            "\"char\"" => ColumnType::I8,
+            "int2" | "smallint" | "smallserial" | "serial2" => ColumnType::I16,
+            "int" | "int4" | "integer" | "serial" | "serial4" => ColumnType::I32,
+            "int8" | "bigint" | "bigserial" | "serial8" => ColumnType::I64,
+            "float4" | "real" => ColumnType::F32,
+            "float8" | "double precision" => ColumnType::F64,
+            "text" | "varchar" | "character varying" | "char" | "character" |
+            "bpchar" | "citext" | "name" => ColumnType::String,
+            "bytea" => ColumnType::ByteList,
+            "json" | "jsonb" => ColumnType::Json,
+            "timestamp" | "timestamp without time zone" => ColumnType::Timespec,
+            "uuid" => ColumnType::Uuid,
+            _ => return None
+        };
+
+        Some(ty)
+    }
+
+    /// Name of the Rust type the field is parametrized with.
+    pub fn rust_type(&self) -> &'static str {
+        match *self {
+            ColumnType::Bool => "bool",
+            ColumnType::I8 => "i8",
+            ColumnType::I16 => "i16",
+            ColumnType::I32 => "i32",
+            ColumnType::I64 => "i64",
+            ColumnType::F32 => "f32",
+            ColumnType::F64 => "f64",
+            ColumnType::String => "String",
+            ColumnType::ByteList => "Vec<u8>",
+            ColumnType::Json => "serde_json::Value",
+            ColumnType::Timespec => "chrono::NaiveDateTime",
+            ColumnType::Uuid => "uuid::Uuid",
+        }
+    }
+
+    /// Name of the field type alias exported by Deuterium.
+    pub fn field_alias(&self, nullable: bool) -> String {
+        let name = match *self {
+            ColumnType::Bool => "BoolField",
+            ColumnType::I8 => "I8Field",
+            ColumnType::I16 => "I16Field",
+            ColumnType::I32 => "I32Field",
+            ColumnType::I64 => "I64Field",
+            ColumnType::F32 => "F32Field",
+            ColumnType::F64 => "F64Field",
+            ColumnType::String => "StringField",
+            ColumnType::ByteList => "ByteListField",
+            ColumnType::Json => "JsonField",
+            ColumnType::Timespec => "TimespecField",
+            ColumnType::Uuid => "UuidField",
+        };
+
+        if nullable { format!("Optional{}", name) } else { name.to_string() }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColumnDef {
+    pub name: String,
+    pub column_type: ColumnType,
+    pub nullable: bool,
+    pub primary_key: bool,
+}
+
+/// Column left out of the generated struct because its type, e.g. `timestamptz`,
+/// `date` or `numeric`, has no Deuterium field type.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SkippedColumn {
+    pub table: String,
+    pub name: String,
+    pub sql_type: String,
+}
+
+impl fmt::Display for SkippedColumn {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "skipped column {}.{} of unsupported type `{}`", self.table, self.name, self.sql_type)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TableSchema {
+    pub name: String,
+    pub columns: Vec<ColumnDef>,
+    pub skipped: Vec<SkippedColumn>,
+}
+
+/// Parses either DDL or JSON schema depending on the input contents.
+pub fn parse_schema(input: &str) -> CodegenResult<Vec<TableSchema>> {
+    match input.trim_start().chars().next() {
+        Some('{') | Some('[') => parse_json(input),
+        _ => parse_ddl(input)
+    }
+}
@@ -0,0 +1,112 @@
+use super::TableSchema;
+
+const KEYWORDS: &[&str] = &[
+    "as", "break", "const", "continue", "crate", "else", "enum", "extern", "false", "fn", "for",
+    "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return",
+    "self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use", "where",
+    "while", "abstract", "box", "do", "final", "macro", "override", "priv", "typeof",
+    "unsized", "virtual", "yield", "async", "await", "dyn", "try",
+    // Reserved by the generated struct itself
+    "table", "new", "alias", "with_table", "primary_key",
+];
+
+/// Turns column name into a valid Rust identifier, appending `_` like `where_` does.
+pub fn field_ident(name: &str) -> String {
+    let mut ident: String = name.chars()
+        .map(|c| if c.is_alphanumeric() || c == '_' { c.to_ascii_lowercase() } else { '_' })
+        .collect();
+
+    if ident.is_empty() || ident.chars().next().unwrap().is_numeric() {
+        ident.insert(0, '_');
+    }
+
+    if KEYWORDS.contains(&&ident[..]) {
+        ident.push('_');
+    }
+
+    ident
+}
+
+/// Turns `some_table` and `public.some_table` into `SomeTableTable`, other schemas are
+/// kept so that same-named tables don't clash: `auth.users` becomes `AuthUsersTable`.
+pub fn struct_ident(table_name: &str) -> String {
+    let short = table_name.strip_prefix("public.").unwrap_or(table_name);
+    let mut ident = String::new();
+    let mut upper = true;
+    for c in short.chars() {
+        if c.is_alphanumeric() {
+            if upper { ident.extend(c.to_uppercase()) } else { ident.push(c) }
+            upper = false;
+        } else {
+            upper = true;
+        }
+    }
+
+    if ident.is_empty() || ident.chars().next().unwrap().is_numeric() {
+        ident.insert(0, 'T');
+    }
+
+    format!("{}Table", ident)
+}
+
+fn generate_table(table: &TableSchema, out: &mut String) {
+    let name = struct_ident(&table.name);
+
+    out.push_str(&format!("/// Table `{}`.\n", table.name));
+    if !table.skipped.is_empty() {
+        let skipped: Vec<String> = table.skipped.iter().map(|c| format!("`{}` ({})", c.name, c.sql_type)).collect();
+        out.push_str("///\n");
+        out.push_str(&format!("/// Columns of unsupported types are left out: {}.\n", skipped.join(", ")));
+    }
+    out.push_str("#[derive(Clone, Debug)]\n");
+    out.push_str(&format!("pub struct {} {{\n", name));
+    out.push_str("    pub table: TableDef,\n");
+    for column in table.columns.iter() {
+        out.push_str(&format!("    pub {}: {},\n", field_ident(&column.name), column.column_type.field_alias(column.nullable)));
+    }
+    out.push_str("}\n\n");
+
+    out.push_str(&format!("impl {} {{\n", name));
+    out.push_str(&format!("    pub fn new() -> {} {{\n", name));
+    out.push_str(&format!("        {}::with_table(TableDef::new({:?}))\n", name, table.name));
+    out.push_str("    }\n\n");
+    out.push_str(&format!("    pub fn alias(alias: &str) -> {} {{\n", name));
+    out.push_str(&format!("        {}::with_table(TableDef::new_with_alias({:?}, alias))\n", name, table.name));
+    out.push_str("    }\n\n");
+    out.push_str(&format!("    fn with_table(table: TableDef) -> {} {{\n", name));
+    out.push_str(&format!("        {} {{\n", name));
+    for column in table.columns.iter() {
+        out.push_str(&format!("            {}: {}::field_of({:?}, &table),\n",
+            field_ident(&column.name), column.column_type.field_alias(column.nullable), column.name));
+    }
+    out.push_str("            table,\n");
+    out.push_str("        }\n");
+    out.push_str("    }\n");
+
+    let primary_key: Vec<String> = table.columns.iter()
+        .filter(|c| c.primary_key)
+        .map(|c| format!("&self.{}", field_ident(&c.name)))
+        .collect();
+
+    if !primary_key.is_empty() {
+        out.push_str("\n    pub fn primary_key(&self) -> Vec<&dyn Field> {\n");
+        out.push_str(&format!("        vec![{}]\n", primary_key.join(", ")));
+        out.push_str("    }\n");
+    }
+
+    out.push_str("}\n");
+}
+
+/// Renders Rust source declaring a struct with typed fields for every table.
+pub fn generate(tables: &[TableSchema]) -> String {
+    let mut out = String::new();
+    out.push_str("// This file is generated by deuterium-codegen, do not edit it manually.\n\n");
+    out.push_str("use deuterium::*;\n");
+
+    for table in tables.iter() {
+        out.push('\n');
+        generate_table(table, &mut out);
+    }
+
+    out
+}
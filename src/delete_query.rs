@@ -1,9 +1,9 @@
-use std::mem;
 use std::marker;
 
 use super::select_query;
 use super::from;
 use super::predicate;
+use super::visit;
 
 pub trait Deletable<M>: from::Table + Sized {
     fn delete(&self) -> DeleteQuery<(), select_query::NoResult, M> {
@@ -14,46 +14,56 @@ pub trait Deletable<M>: from::Table + Sized {
 macro_rules! returning_for {
     ($query:ident) => (
         impl<T, L, M> $query<T, L, M> {
-            pub fn returning_1<R: Clone>(mut self, field: &$crate::expression::Expression<R>) -> $query<(R), $crate::select_query::LimitMany, M> {
+            pub fn returning_1<R: Clone>(mut self, field: &dyn $crate::expression::Expression<R>) -> $query<R, $crate::select_query::LimitMany, M> {
                 self.returning = Some($crate::select_query::Select::Only(vec![field.upcast_expression()]));
-                unsafe{ mem::transmute(self) }
+                self.retype()
             }
 
-            pub fn returning_2<R1: Clone, R2: Clone>(mut self, field1: &$crate::expression::Expression<R1>, field2: &$crate::expression::Expression<R2>) -> $query<(R1, R2), $crate::select_query::LimitMany, M> {
+            pub fn returning_2<R1: Clone, R2: Clone>(mut self, field1: &dyn $crate::expression::Expression<R1>, field2: &dyn $crate::expression::Expression<R2>) -> $query<(R1, R2), $crate::select_query::LimitMany, M> {
                 self.returning = Some($crate::select_query::Select::Only(vec![field1.upcast_expression(), field2.upcast_expression()]));
-                unsafe{ mem::transmute(self) }
+                self.retype()
             }
 
-            pub fn returning(mut self, fields: &[&$crate::expression::UntypedExpression]) -> $query<(), $crate::select_query::LimitMany, M> {
+            pub fn returning(mut self, fields: &[&dyn $crate::expression::UntypedExpression]) -> $query<(), $crate::select_query::LimitMany, M> {
                 self.returning = Some($crate::select_query::Select::Only(fields.iter().map(|f| f.upcast_expression()).collect()));
-                unsafe{ mem::transmute(self) }
+                self.retype()
             }
 
             pub fn returning_all(mut self) -> $query<(), $crate::select_query::LimitMany, M> {
                 self.returning = Some($crate::select_query::Select::All);
-                unsafe{ mem::transmute(self) }
+                self.retype()
             }
 
             pub fn no_returning(mut self) -> $query<(), $crate::select_query::NoResult, M> {
                 self.returning = None;
-                unsafe{ mem::transmute(self) }
+                self.retype()
             }
         }
     )
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct DeleteQuery<T, L, M> {
     only: bool,
     all: bool,
+    #[cfg_attr(feature = "serde", serde(with = "::serialize::shared"))]
     table: from::SharedTable,
+    #[cfg_attr(feature = "serde", serde(with = "::serialize::shared"))]
     using: Option<Vec<from::SharedFrom>>,
+    #[cfg_attr(feature = "serde", serde(with = "::serialize::shared"))]
+    scope: Option<predicate::SharedPredicate>,
+    soft_delete: Option<from::DeletedAtField>,
+    #[cfg_attr(feature = "serde", serde(with = "::serialize::shared"))]
     where_: Option<predicate::SharedPredicate>,
     returning: Option<select_query::Select>,
 
-    _marker_t: marker::PhantomData<T>,
-    _marker_l: marker::PhantomData<L>,
-    _marker_m: marker::PhantomData<M>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    _marker_t: marker::PhantomData<fn() -> T>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    _marker_l: marker::PhantomData<fn() -> L>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    _marker_m: marker::PhantomData<fn() -> M>,
 }
 
 impl<T, L, M> DeleteQuery<T, L, M> {
@@ -62,14 +72,36 @@ impl<T, L, M> DeleteQuery<T, L, M> {
     pub fn is_all(&self) -> bool { self.all }
     pub fn get_table(&self) -> &from::SharedTable { &self.table }
     pub fn get_using(&self) -> &Option<Vec<from::SharedFrom>> { &self.using }
+    pub fn get_scope(&self) -> &Option<predicate::SharedPredicate> { &self.scope }
+    pub fn get_soft_delete(&self) -> &Option<from::DeletedAtField> { &self.soft_delete }
     pub fn get_where(&self) -> &Option<predicate::SharedPredicate> { &self.where_ }
     pub fn get_returning(&self) -> &Option<select_query::Select> { &self.returning }
 
-    pub fn new(table: &from::Table) -> DeleteQuery<T, L, M> {
+    // Rebuilds the query with other type parameters
+    fn retype<T2, L2, M2>(self) -> DeleteQuery<T2, L2, M2> {
+        DeleteQuery {
+            only: self.only,
+            all: self.all,
+            table: self.table,
+            using: self.using,
+            scope: self.scope,
+            soft_delete: self.soft_delete,
+            where_: self.where_,
+            returning: self.returning,
+
+            _marker_t: marker::PhantomData,
+            _marker_l: marker::PhantomData,
+            _marker_m: marker::PhantomData,
+        }
+    }
+
+    pub fn new(table: &dyn from::Table) -> DeleteQuery<T, L, M> {
         DeleteQuery {
             only: false,
             table: table.upcast_table(),
             using: None,
+            scope: select_query::and_all(table.get_scopes()),
+            soft_delete: table.get_soft_delete(),
             where_: None,
             all: false,
             returning: None,
@@ -85,7 +117,7 @@ impl<T, L, M> DeleteQuery<T, L, M> {
         self
     }
 
-    pub fn using(mut self, using: &from::From) -> DeleteQuery<T, L, M> {
+    pub fn using(mut self, using: &dyn from::From) -> DeleteQuery<T, L, M> {
         if self.using.is_none() {
             self.using = Some(vec![])
         }
@@ -94,11 +126,26 @@ impl<T, L, M> DeleteQuery<T, L, M> {
         self
     }
 
+    /// Deletes rows physically even if the table is soft-deleted.
+    pub fn force_delete(mut self) -> DeleteQuery<T, L, M> {
+        self.soft_delete = None;
+        self
+    }
+
     pub fn all(mut self) -> DeleteQuery<T, L, M> {
         self.where_ = None;
         self.all = true;
         self
     }
+
+    /// Rebuilds the sources, predicates and returned expressions of the query with `rewriter`.
+    pub fn rewrite<R: visit::Rewriter + ?Sized>(mut self, rewriter: &mut R) -> DeleteQuery<T, L, M> {
+        self.using = self.using.map(|using| using.into_iter().map(|f| rewriter.rewrite_from(f)).collect());
+        self.scope = self.scope.map(|p| visit::rewrite_predicate(rewriter, &p));
+        self.where_ = self.where_.map(|p| visit::rewrite_predicate(rewriter, &p));
+        self.returning = self.returning.map(|returning| visit::rewrite_select_list(rewriter, &returning));
+        self
+    }
 }
 
 returning_for!(DeleteQuery);
@@ -109,3 +156,8 @@ impl<T:Clone, L:Clone, M:Clone> select_query::Queryable for DeleteQuery<T, L, M>
     fn unset_where(&mut self) { self.where_ = None; }
 }
 
+impl<T:Clone, L:Clone, M:Clone> select_query::Scoped for DeleteQuery<T, L, M> {
+    fn get_scope(&self) -> &Option<predicate::SharedPredicate> { &self.scope }
+    fn unset_scope(&mut self) { self.scope = None; }
+}
+
@@ -1,8 +1,11 @@
 
 use super::expression;
+use super::visit;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Distinct {
+    #[cfg_attr(feature = "serde", serde(with = "::serialize::shared"))]
     on: Option<Vec<expression::SharedExpression>>
 }
 
@@ -13,9 +16,13 @@ impl Distinct {
 
     pub fn get_on(&self) -> &Option<Vec<expression::SharedExpression>> { &self.on }
 
-    pub fn on(fields: &[&expression::UntypedExpression]) -> Distinct {
+    pub fn on(fields: &[&dyn expression::UntypedExpression]) -> Distinct {
         Distinct { on: Some(
             fields.iter().map(|f| f.upcast_expression()).collect()
         )}
     }
+
+    pub fn rewrite<R: visit::Rewriter + ?Sized>(&self, rewriter: &mut R) -> Distinct {
+        Distinct { on: self.on.as_ref().map(|on| on.iter().map(|e| visit::rewrite_expression(rewriter, e)).collect()) }
+    }
 }
@@ -0,0 +1,59 @@
+use std::{error, fmt};
+#[cfg(feature = "postgres")] use postgres;
+
+/// Error of rendering a query, see `QueryToSql::to_final_sql`.
+#[derive(Debug)]
+pub enum Error {
+    /// The feature can't be expressed in the SQL dialect of the adapter.
+    Unsupported { feature: &'static str, adapter: &'static str },
+    /// `IN` predicate with no values renders `IN ()` which is invalid.
+    EmptyInList,
+    /// `UPDATE` without any `SET` entries.
+    EmptyUpdate,
+    /// More bound values than the driver of the adapter accepts, see `SqlAdapter::max_binds`.
+    TooManyPlaceholders { count: usize, max: usize },
+    /// Tenant-scoped table rendered while no tenant is set on the context.
+    MissingTenant { table: String },
+    /// Insert into tenant-scoped table without the list of columns.
+    MissingColumns { table: String },
+    /// Named placeholder without a value, see `CompiledQuery::bind`.
+    UnboundPlaceholder { name: String },
+    /// Raw SQL containing a NUL character, which no database accepts in query text.
+    NulInRaw { content: String },
+    #[cfg(feature = "postgres")]
+    Postgres(postgres::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Unsupported { feature, adapter } => write!(f, "{} is not supported by {}", feature, adapter),
+            Error::EmptyInList => write!(f, "IN predicate with an empty list of values"),
+            Error::EmptyUpdate => write!(f, "UPDATE without fields to set"),
+            Error::TooManyPlaceholders { count, max } => {
+                write!(f, "query binds {} values, but at most {} are supported", count, max)
+            },
+            Error::MissingTenant { ref table } => {
+                write!(f, "table `{}` is tenant-scoped but no tenant is set on SqlContext", table)
+            },
+            Error::MissingColumns { ref table } => {
+                write!(f, "insert into tenant-scoped table `{}` must list its columns", table)
+            },
+            Error::UnboundPlaceholder { ref name } => write!(f, "placeholder `{}` is not bound", name),
+            Error::NulInRaw { ref content } => write!(f, "raw SQL {:?} contains a NUL character", content),
+            #[cfg(feature = "postgres")]
+            Error::Postgres(ref err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+#[cfg(feature = "postgres")]
+impl From<postgres::Error> for Error {
+    fn from(err: postgres::Error) -> Error {
+        Error::Postgres(err)
+    }
+}
+
+pub type Result<T> = ::std::result::Result<T, Error>;
@@ -0,0 +1,278 @@
+//! Evaluation of predicates against a row in memory, without a database.
+//!
+//! Follows SQL three-valued logic: a comparison with NULL is unknown (`None`), `NOT` of unknown
+//! is unknown, `AND` is false as soon as one side is false and `OR` is true as soon as one side
+//! is true. Columns are looked up by name, qualifiers are ignored.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+
+use super::predicate::SharedPredicate;
+use super::predicate::{Inequality, InRangeBounds};
+use super::sql::Value;
+use super::visit::{Node, PredicateNode};
+
+/// Values of a row by column name.
+pub trait FieldValues {
+    fn field_value(&self, name: &str) -> Option<Value>;
+}
+
+impl FieldValues for HashMap<String, Value> {
+    fn field_value(&self, name: &str) -> Option<Value> {
+        self.get(name).cloned()
+    }
+}
+
+impl FieldValues for HashMap<&str, Value> {
+    fn field_value(&self, name: &str) -> Option<Value> {
+        self.get(name).cloned()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum EvalError {
+    /// Part of the predicate that needs a database, e.g. a raw predicate or a subquery.
+    Unsupported(String),
+    /// The row has no value for the column.
+    UnknownField(String),
+    /// Values that can't be compared, e.g. a string with a number.
+    TypeMismatch(Value, Value)
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            EvalError::Unsupported(ref what) => write!(f, "{} can't be evaluated in memory", what),
+            EvalError::UnknownField(ref name) => write!(f, "the row has no field `{}`", name),
+            EvalError::TypeMismatch(ref left, ref right) => write!(f, "can't compare {:?} with {:?}", left, right),
+        }
+    }
+}
+
+pub type EvalResult<T> = Result<T, EvalError>;
+
+/// Truth value of the predicate for the row, `None` when it is unknown because of NULLs.
+pub fn eval(predicate: &SharedPredicate, row: &dyn FieldValues) -> EvalResult<Option<bool>> {
+    Ok(match predicate.predicate_node() {
+        PredicateNode::Is { field, value } => {
+            equals(&operand(field, row)?, &operand(value, row)?)?
+        },
+        PredicateNode::IsNull { field, is_null } => {
+            Some((operand(field, row)? == Value::Null) == is_null)
+        },
+        PredicateNode::In { field, values } => {
+            let value = operand(field, row)?;
+            let values = match values {
+                Node::List(nodes) => nodes,
+                node => return Err(unsupported(&node))
+            };
+
+            let mut result = Some(false);
+            for node in values {
+                match equals(&value, &operand(node, row)?)? {
+                    Some(true) => return Ok(Some(true)),
+                    Some(false) => (),
+                    None => result = None
+                }
+            }
+            result
+        },
+        PredicateNode::Like { field, value, case_sensitive } => {
+            match (operand(field, row)?, operand(value, row)?) {
+                (Value::Null, _) | (_, Value::Null) => None,
+                (Value::String(ref text), Value::String(ref pattern)) => Some(if case_sensitive {
+                    like(text, pattern)
+                } else {
+                    like(&text.to_lowercase(), &pattern.to_lowercase())
+                }),
+                (text, pattern) => return Err(EvalError::TypeMismatch(text, pattern))
+            }
+        },
+        PredicateNode::InRange { field, from, to, bounds } => {
+            let value = operand(field, row)?;
+            let (from, to) = (compare(&value, &operand(from, row)?)?, compare(&value, &operand(to, row)?)?);
+            let (include_from, include_to) = match bounds {
+                InRangeBounds::IncludeBoth => (true, true),
+                InRangeBounds::ExcludeLeft => (false, true),
+                InRangeBounds::ExcludeRight => (true, false),
+                InRangeBounds::ExcludeBoth => (false, false)
+            };
+            and(
+                from.map(|ordering| ordering == Ordering::Greater || include_from && ordering == Ordering::Equal),
+                to.map(|ordering| ordering == Ordering::Less || include_to && ordering == Ordering::Equal)
+            )
+        },
+        PredicateNode::Inequality { field, value, inequality } => {
+            compare(&operand(field, row)?, &operand(value, row)?)?.map(|ordering| match inequality {
+                Inequality::LessThan => ordering == Ordering::Less,
+                Inequality::LessThanEqual => ordering != Ordering::Greater,
+                Inequality::GreaterThan => ordering == Ordering::Greater,
+                Inequality::GreaterThanEqual => ordering != Ordering::Less
+            })
+        },
+        PredicateNode::And(left, right) => and(eval(left, row)?, eval(right, row)?),
+        PredicateNode::Or(left, right) => or(eval(left, row)?, eval(right, row)?),
+        PredicateNode::All(predicates) => {
+            let mut result = Some(true);
+            for predicate in predicates.iter() {
+                result = and(result, eval(predicate, row)?);
+            }
+            result
+        },
+        PredicateNode::Any(predicates) => {
+            let mut result = Some(false);
+            for predicate in predicates.iter() {
+                result = or(result, eval(predicate, row)?);
+            }
+            result
+        },
+        PredicateNode::Exclude(predicate) => eval(predicate, row)?.map(|matches| !matches),
+        PredicateNode::Raw(raw) => return Err(EvalError::Unsupported(format!("raw predicate `{}`", raw.content))),
+        PredicateNode::Tenant(_) => return Err(EvalError::Unsupported("tenant predicate".to_string())),
+        PredicateNode::Other => return Err(EvalError::Unsupported("custom predicate".to_string()))
+    })
+}
+
+/// Whether the row would be returned by a `WHERE` with the predicate, unknown counts as no.
+pub fn matches(predicate: &SharedPredicate, row: &dyn FieldValues) -> EvalResult<bool> {
+    eval(predicate, row).map(|result| result == Some(true))
+}
+
+fn and(left: Option<bool>, right: Option<bool>) -> Option<bool> {
+    match (left, right) {
+        (Some(false), _) | (_, Some(false)) => Some(false),
+        (Some(true), Some(true)) => Some(true),
+        _ => None
+    }
+}
+
+fn or(left: Option<bool>, right: Option<bool>) -> Option<bool> {
+    match (left, right) {
+        (Some(true), _) | (_, Some(true)) => Some(true),
+        (Some(false), Some(false)) => Some(false),
+        _ => None
+    }
+}
+
+fn unsupported(node: &Node) -> EvalError {
+    EvalError::Unsupported(match node {
+        &Node::Raw(raw) => format!("raw expression `{}`", raw.content),
+        &Node::Placeholder(_) | &Node::NamedPlaceholder(_) => "placeholder".to_string(),
+        &Node::Select(_) => "subquery".to_string(),
+        &Node::Function(name, _) => format!("function {}", name),
+        node => format!("{:?}", node)
+    })
+}
+
+fn operand(node: Node, row: &dyn FieldValues) -> EvalResult<Value> {
+    match node {
+        Node::Field(field) => row.field_value(field.name()).ok_or_else(|| EvalError::UnknownField(field.name().to_string())),
+        Node::Value(value) => Ok(value),
+        node => Err(unsupported(&node))
+    }
+}
+
+enum Number {
+    Int(i64),
+    Float(f64)
+}
+
+fn number(value: &Value) -> Option<Number> {
+    Some(match *value {
+        Value::I8(v) => Number::Int(v.into()),
+        Value::I16(v) => Number::Int(v.into()),
+        Value::I32(v) => Number::Int(v.into()),
+        Value::I64(v) => Number::Int(v),
+        Value::F32(v) => Number::Float(v.into()),
+        Value::F64(v) => Number::Float(v),
+        _ => return None
+    })
+}
+
+// Ordering of two values, `None` when one of them is NULL. Numbers of any width compare with each other.
+fn compare(left: &Value, right: &Value) -> EvalResult<Option<Ordering>> {
+    let ordering = match (left, right) {
+        (Value::Null, _) | (_, Value::Null) => return Ok(None),
+        (Value::Bool(l), Value::Bool(r)) => l.cmp(r),
+        (Value::String(l), Value::String(r)) => l.cmp(r),
+        (Value::Bytes(l), Value::Bytes(r)) => l.cmp(r),
+        (Value::DateTime(l), Value::DateTime(r)) => l.cmp(r),
+        (Value::Uuid(l), Value::Uuid(r)) => l.cmp(r),
+        _ => match (number(left), number(right)) {
+            (Some(Number::Int(l)), Some(Number::Int(r))) => l.cmp(&r),
+            (Some(l), Some(r)) => {
+                let (l, r) = (float(l), float(r));
+                match l.partial_cmp(&r) {
+                    Some(ordering) => ordering,
+                    None => return Ok(None)
+                }
+            },
+            _ => return Err(EvalError::TypeMismatch(left.clone(), right.clone()))
+        }
+    };
+    Ok(Some(ordering))
+}
+
+fn float(number: Number) -> f64 {
+    match number {
+        Number::Int(v) => v as f64,
+        Number::Float(v) => v
+    }
+}
+
+fn equals(left: &Value, right: &Value) -> EvalResult<Option<bool>> {
+    match (left, right) {
+        (Value::Json(l), Value::Json(r)) => Ok(Some(l == r)),
+        _ => compare(left, right).map(|ordering| ordering.map(|ordering| ordering == Ordering::Equal))
+    }
+}
+
+enum LikeToken {
+    Many,
+    One,
+    Char(char)
+}
+
+// SQL `LIKE`: `%` matches any run of characters, `_` one character and `\` escapes the next one.
+// Matches greedily, only going back to the last `%` on a mismatch, so it stays linear per `%`.
+fn like(text: &str, pattern: &str) -> bool {
+    let text: Vec<char> = text.chars().collect();
+    let mut tokens = vec![];
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        tokens.push(match c {
+            '%' => LikeToken::Many,
+            '_' => LikeToken::One,
+            '\\' => LikeToken::Char(chars.next().unwrap_or('\\')),
+            c => LikeToken::Char(c)
+        });
+    }
+
+    let (mut t, mut p) = (0, 0);
+    // Position after the last `%` and the text it was last tried at
+    let mut retry = None;
+    while t < text.len() {
+        match tokens.get(p) {
+            Some(&LikeToken::Many) => {
+                p += 1;
+                retry = Some((p, t));
+                continue
+            },
+            Some(&LikeToken::One) => { t += 1; p += 1; continue },
+            Some(&LikeToken::Char(c)) if c == text[t] => { t += 1; p += 1; continue },
+            _ => ()
+        }
+
+        match retry {
+            Some((after, from)) => {
+                p = after;
+                t = from + 1;
+                retry = Some((after, t));
+            },
+            None => return false
+        }
+    }
+
+    tokens[p..].iter().all(|token| matches!(*token, LikeToken::Many))
+}
@@ -1,13 +1,15 @@
-use std::{fmt, rc};
-use std::mem;
+use std::{fmt, sync};
+use std::marker;
 use serde_json;
 use chrono;
 use uuid;
 
 use super::sql;
 use super::field;
+use super::visit;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 /// Non-checking expression with any content you want.
 pub struct RawExpression {
     pub content: String
@@ -22,13 +24,19 @@ impl RawExpression {
 }
 
 /// Intrernal trait for all expressions. Allows some useful casts.
-pub trait UntypedExpression: fmt::Debug {
-    fn expression_as_sql(&self) -> &sql::ToSql;
+pub trait UntypedExpression: fmt::Debug + Send + Sync {
+    fn expression_as_sql(&self) -> &dyn sql::ToSql;
     fn upcast_expression(&self) -> SharedExpression;
+
+    /// Structure of the expression for visitors.
+    fn expression_node(&self) -> visit::Node<'_> { visit::Node::Other }
+
+    /// Copy of the expression with its subqueries rewritten, `None` if it has none.
+    fn rewrite_subqueries(&self, _rewriter: &mut dyn visit::Rewriter) -> Option<SharedExpression> { None }
 }
 
-pub type BoxedExpression = Box<UntypedExpression + 'static>;
-pub type SharedExpression = rc::Rc<BoxedExpression>;
+pub type BoxedExpression = Box<dyn UntypedExpression + 'static>;
+pub type SharedExpression = sync::Arc<BoxedExpression>;
 
 /// Trait to indicate that value is an expression with concrete type.
 pub trait Expression<T>: UntypedExpression {}
@@ -42,12 +50,16 @@ pub trait PrimitiveType: fmt::Debug { }
 macro_rules! to_expression {
     ($t:ty) => (
         impl UntypedExpression for $t {
-            fn expression_as_sql(&self) -> &sql::ToSql {
+            fn expression_as_sql(&self) -> &dyn sql::ToSql {
                 self
             }
 
             fn upcast_expression(&self) -> SharedExpression {
-                rc::Rc::new(Box::new(self.clone()))
+                sync::Arc::new(Box::new(self.clone()))
+            }
+
+            fn expression_node(&self) -> visit::Node<'_> {
+                visit::ToNode::to_node(self)
             }
         }
 
@@ -68,6 +80,7 @@ to_expression!(serde_json::Value);
 to_expression!(chrono::NaiveDateTime);
 to_expression!(uuid::Uuid);
 to_expression!(RawExpression);
+to_expression!(sql::Value);
 
 impl PrimitiveType for bool { }
 impl PrimitiveType for i8 { }
@@ -97,16 +110,59 @@ to_expression!(Option<chrono::NaiveDateTime>);
 to_expression!(Option<uuid::Uuid>);
 to_expression!(Option<RawExpression>);
 
+/// Expression of one type used as expression of other type, see `ToExpression::as_expr`.
+pub struct CastExpression<T> {
+    expression: SharedExpression,
+    _marker: marker::PhantomData<fn() -> T>
+}
+
+impl<T> CastExpression<T> {
+    pub fn new(expression: SharedExpression) -> CastExpression<T> {
+        CastExpression { expression, _marker: marker::PhantomData }
+    }
+
+    pub fn get_expression(&self) -> &SharedExpression { &self.expression }
+}
+
+impl<T> Clone for CastExpression<T> {
+    fn clone(&self) -> CastExpression<T> {
+        CastExpression::new(self.expression.clone())
+    }
+}
+
+impl<T> fmt::Debug for CastExpression<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CastExpression").field("expression", &self.expression).finish()
+    }
+}
+
+impl<T> UntypedExpression for CastExpression<T> {
+    fn expression_as_sql(&self) -> &dyn sql::ToSql {
+        self.expression.expression_as_sql()
+    }
+
+    fn upcast_expression(&self) -> SharedExpression {
+        self.expression.clone()
+    }
+
+    fn expression_node(&self) -> visit::Node<'_> {
+        self.expression.expression_node()
+    }
+}
+
+impl<T> Expression<T> for CastExpression<T> {}
+impl<T> ToExpression<T> for CastExpression<T> {}
+
 // This trait is used to indicate that expression can be casted to
 // expression of other type (e.g. numbers).
 pub trait ToExpression<T>: UntypedExpression + Sized {
-    fn as_expr(&self) -> &Expression<T> { unsafe{ mem::transmute(self as &UntypedExpression) } }
+    fn as_expr(&self) -> CastExpression<T> { CastExpression::new(self.upcast_expression()) }
 }
 
 // This trait is used to indicate that expression can be casted to
 // LIST expression of other type (e.g. list of numbers).
-pub trait ToListExpression<T>: UntypedExpression + Sized {
-    fn as_expr(&self) -> &ListExpression<T> { unsafe{ mem::transmute(self as &UntypedExpression) } }
+pub trait ToListExpression<T>: ListExpression<T> + Sized {
+    fn as_expr(&self) -> &dyn ListExpression<T> { self }
 }
 
 impl<T> Expression<T>         for field::NamedField<T>         where T: PrimitiveType + Clone + 'static + fmt::Debug { }
@@ -168,6 +224,12 @@ macro_rules! cast_numbers_optional {
         impl $comp for field::OptionalI64Field {}
         impl $comp for field::OptionalF32Field {}
         impl $comp for field::OptionalF64Field {}
+        impl $comp for Option<i8> {}
+        impl $comp for Option<i16> {}
+        impl $comp for Option<i32> {}
+        impl $comp for Option<i64> {}
+        impl $comp for Option<f32> {}
+        impl $comp for Option<f64> {}
         impl $comp for RawExpression {}
     )
 }
@@ -306,12 +368,16 @@ impl ToExpression<RawExpression> for field::OptionalTimespecField {}
 impl ToExpression<RawExpression> for field::OptionalUuidField {}
 
 impl<T> UntypedExpression for Vec<T> where T: UntypedExpression + sql::ToPredicateValue + Clone + 'static {
-    fn expression_as_sql(&self) -> &sql::ToSql {
+    fn expression_as_sql(&self) -> &dyn sql::ToSql {
         self
     }
 
     fn upcast_expression(&self) -> SharedExpression {
-        rc::Rc::new(Box::new(self.clone()))
+        sync::Arc::new(Box::new(self.clone()))
+    }
+
+    fn expression_node(&self) -> visit::Node<'_> {
+        visit::Node::List(self.iter().map(|v| v.expression_node()).collect())
     }
 }
 
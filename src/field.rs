@@ -1,4 +1,4 @@
-use std::{fmt, rc};
+use std::{fmt, sync};
 use std::marker;
 use serde_json;
 use chrono;
@@ -7,30 +7,33 @@ use uuid;
 use super::from;
 use super::sql;
 use super::expression;
+use super::visit;
 
-pub trait Field {
+pub trait Field: Send + Sync {
     fn name(&self) -> &str;
     fn table_name(&self) -> &str;
     fn qual(&self) -> Option<&String>;
     fn upcast_field(&self) -> SharedField;
 }
 
-impl fmt::Debug for Field {
+impl fmt::Debug for dyn Field {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "Field: {}", self.name())
     }
 }
 
-pub type BoxedField = Box<Field + 'static>;
-pub type SharedField = rc::Rc<BoxedField>;
+pub type BoxedField = Box<dyn Field + 'static>;
+pub type SharedField = sync::Arc<BoxedField>;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct NamedField<T> {
     pub name: String,
     pub table_name: String,
     pub qual: Option<String>,
 
-    _marker: marker::PhantomData<T>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    _marker: marker::PhantomData<fn() -> T>,
 }
 
 impl<T: Clone> NamedField<T> {
@@ -54,7 +57,7 @@ impl<T: Clone> NamedField<T> {
         }
     }
 
-    pub fn field_of(name: &str, table: &from::Table) -> NamedField<T> {
+    pub fn field_of(name: &str, table: &dyn from::Table) -> NamedField<T> {
         NamedField {
             name: name.to_string(),
             table_name: table.get_table_name().to_string(),
@@ -76,7 +79,7 @@ impl<T: Clone> NamedField<T> {
         field
     }
 
-    pub fn qual_for(&self, table: &from::Table) -> NamedField<T> {
+    pub fn qual_for(&self, table: &dyn from::Table) -> NamedField<T> {
         let mut field = self.clone();
         field.qual = table.get_table_alias().as_ref().map(|v| v.to_string());
         field
@@ -84,12 +87,16 @@ impl<T: Clone> NamedField<T> {
 }
 
 impl<T: Clone + 'static + fmt::Debug> expression::UntypedExpression for NamedField<T> {
-    fn expression_as_sql(&self) -> &sql::ToSql {
+    fn expression_as_sql(&self) -> &dyn sql::ToSql {
         self
     }
 
     fn upcast_expression(&self) -> expression::SharedExpression {
-        rc::Rc::new(Box::new(self.clone()) as expression::BoxedExpression)
+        sync::Arc::new(Box::new(self.clone()) as expression::BoxedExpression)
+    }
+
+    fn expression_node(&self) -> visit::Node<'_> {
+        visit::Node::Field(self)
     }
 }
 
@@ -107,7 +114,7 @@ impl<T: Clone + 'static> Field for NamedField<T> {
     }
 
     fn upcast_field(&self) -> SharedField {
-        rc::Rc::new(Box::new(self.clone()))
+        sync::Arc::new(Box::new(self.clone()))
     }
 }
 
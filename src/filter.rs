@@ -0,0 +1,358 @@
+//! Predicates built from filter descriptions sent as JSON, e.g. by an admin UI.
+//!
+//! A condition is `{"field": "age", "op": "gte", "value": 18}`, conditions are combined with
+//! `{"and": [...]}` and `{"or": [...]}`. Only the columns registered in `Filters` can be
+//! referenced and values are checked against the column type before any SQL is built.
+
+use std::fmt;
+use std::convert::TryFrom;
+use serde_json::{self, Value as Json};
+use chrono;
+use uuid;
+
+use super::field::NamedField;
+use super::predicate::{
+    SharedPredicate,
+    ToIsPredicate,
+    ToOrPredicate,
+    ToAndPredicate,
+    ToInPredicate,
+    ToInRangePredicate,
+    ToInequalityPredicate,
+    ToExcludePredicate,
+    ToLikePredicate,
+    ToIsNullPredicate
+};
+
+/// Operator of a condition, named in lowercase in the filter.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Lte,
+    Gt,
+    Gte,
+    /// Inclusive range, the value is `[from, to]`.
+    Between,
+    /// The value is a non-empty list.
+    In,
+    Like,
+    ILike,
+    /// Takes no value.
+    IsNull,
+    /// Takes no value.
+    NotNull
+}
+
+impl Op {
+    pub fn from_name(name: &str) -> Option<Op> {
+        Some(match name {
+            "eq" => Op::Eq,
+            "ne" => Op::Ne,
+            "lt" => Op::Lt,
+            "lte" => Op::Lte,
+            "gt" => Op::Gt,
+            "gte" => Op::Gte,
+            "between" => Op::Between,
+            "in" => Op::In,
+            "like" => Op::Like,
+            "ilike" => Op::ILike,
+            "is_null" => Op::IsNull,
+            "not_null" => Op::NotNull,
+            _ => return None
+        })
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Op::Eq => "eq",
+            Op::Ne => "ne",
+            Op::Lt => "lt",
+            Op::Lte => "lte",
+            Op::Gt => "gt",
+            Op::Gte => "gte",
+            Op::Between => "between",
+            Op::In => "in",
+            Op::Like => "like",
+            Op::ILike => "ilike",
+            Op::IsNull => "is_null",
+            Op::NotNull => "not_null",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum FilterError {
+    /// Neither a condition nor an `and`/`or` list, with what is wrong about it.
+    Malformed(String),
+    /// The field isn't registered in `Filters`.
+    UnknownField(String),
+    UnknownOperator(String),
+    /// The operator doesn't apply to the column type, e.g. `like` on a number.
+    UnsupportedOperator { field: String, op: Op },
+    InvalidValue { field: String, expected: &'static str, found: Json }
+}
+
+impl fmt::Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FilterError::Malformed(ref reason) => write!(f, "malformed filter: {}", reason),
+            FilterError::UnknownField(ref field) => write!(f, "unknown field `{}`", field),
+            FilterError::UnknownOperator(ref op) => write!(f, "unknown operator `{}`", op),
+            FilterError::UnsupportedOperator { ref field, op } => {
+                write!(f, "operator `{}` is not supported by field `{}`", op.name(), field)
+            },
+            FilterError::InvalidValue { ref field, expected, ref found } => {
+                write!(f, "field `{}` expects {}, got `{}`", field, expected, found)
+            },
+        }
+    }
+}
+
+pub type FilterResult<T> = Result<T, FilterError>;
+
+/// Value of a column type read from a filter, `null` is never accepted.
+pub trait FilterValue: Sized {
+    /// What the JSON value should be, for error messages.
+    fn expected() -> &'static str;
+    fn from_json(value: &Json) -> Option<Self>;
+}
+
+impl FilterValue for bool {
+    fn expected() -> &'static str { "a boolean" }
+    fn from_json(value: &Json) -> Option<bool> { value.as_bool() }
+}
+
+macro_rules! integer_filter_value {
+    ($t:ty) => (
+        impl FilterValue for $t {
+            fn expected() -> &'static str { concat!("an integer fitting ", stringify!($t)) }
+            fn from_json(value: &Json) -> Option<$t> { value.as_i64().and_then(|value| <$t>::try_from(value).ok()) }
+        }
+    )
+}
+
+integer_filter_value!(i8);
+integer_filter_value!(i16);
+integer_filter_value!(i32);
+integer_filter_value!(i64);
+
+impl FilterValue for f32 {
+    fn expected() -> &'static str { "a number" }
+    fn from_json(value: &Json) -> Option<f32> { value.as_f64().map(|value| value as f32) }
+}
+
+impl FilterValue for f64 {
+    fn expected() -> &'static str { "a number" }
+    fn from_json(value: &Json) -> Option<f64> { value.as_f64() }
+}
+
+impl FilterValue for String {
+    fn expected() -> &'static str { "a string" }
+    fn from_json(value: &Json) -> Option<String> { value.as_str().map(|value| value.to_string()) }
+}
+
+impl FilterValue for chrono::NaiveDateTime {
+    fn expected() -> &'static str { "a date and time like \"2015-01-02T03:04:05\"" }
+    fn from_json(value: &Json) -> Option<chrono::NaiveDateTime> { value.as_str().and_then(|value| value.parse().ok()) }
+}
+
+impl FilterValue for uuid::Uuid {
+    fn expected() -> &'static str { "a UUID string" }
+    fn from_json(value: &Json) -> Option<uuid::Uuid> { value.as_str().and_then(|value| uuid::Uuid::parse_str(value).ok()) }
+}
+
+impl FilterValue for serde_json::Value {
+    fn expected() -> &'static str { "a JSON value" }
+    fn from_json(value: &Json) -> Option<serde_json::Value> { Some(value.clone()) }
+}
+
+impl<T: FilterValue> FilterValue for Option<T> {
+    fn expected() -> &'static str { T::expected() }
+    fn from_json(value: &Json) -> Option<Option<T>> { T::from_json(value).map(Some) }
+}
+
+fn invalid(field: &str, expected: &'static str, found: &Json) -> FilterError {
+    FilterError::InvalidValue { field: field.to_string(), expected, found: found.clone() }
+}
+
+fn parse<T: FilterValue>(field: &str, value: &Json) -> FilterResult<T> {
+    if value.is_null() {
+        return Err(invalid(field, T::expected(), value))
+    }
+    T::from_json(value).ok_or_else(|| invalid(field, T::expected(), value))
+}
+
+fn parse_list<T: FilterValue>(field: &str, value: &Json) -> FilterResult<Vec<T>> {
+    match value.as_array() {
+        Some(values) if !values.is_empty() => values.iter().map(|value| parse(field, value)).collect(),
+        _ => Err(invalid(field, "a non-empty list", value))
+    }
+}
+
+fn parse_range<T: FilterValue>(field: &str, value: &Json) -> FilterResult<(T, T)> {
+    match value.as_array() {
+        Some(bounds) if bounds.len() == 2 => Ok((parse(field, &bounds[0])?, parse(field, &bounds[1])?)),
+        _ => Err(invalid(field, "a list of two bounds", value))
+    }
+}
+
+/// Column a filter can reference, see `Filters::field`.
+pub trait FilterField: Send + Sync {
+    /// Condition on the column, `name` is how the filter refers to it.
+    fn predicate(&self, name: &str, op: Op, value: &Json) -> FilterResult<SharedPredicate>;
+}
+
+macro_rules! filter_field {
+    ($t:ty $(, $kind:ident)*) => (
+        impl FilterField for NamedField<$t> {
+            fn predicate(&self, name: &str, op: Op, value: &Json) -> FilterResult<SharedPredicate> {
+                $(
+                    if let Some(predicate) = filter_field!(@$kind self, name, op, value, $t) {
+                        return predicate
+                    }
+                )*
+                match op {
+                    Op::Eq => Ok(self.is(parse::<$t>(name, value)?)),
+                    Op::Ne => Ok(self.is(parse::<$t>(name, value)?).exclude()),
+                    Op::In => Ok(self.in_(parse_list::<$t>(name, value)?)),
+                    op => Err(FilterError::UnsupportedOperator { field: name.to_string(), op })
+                }
+            }
+        }
+    );
+
+    (@ordered $field:ident, $name:ident, $op:ident, $value:ident, $t:ty) => (
+        match $op {
+            Op::Lt => Some(parse::<$t>($name, $value).map(|value| $field.lt(value))),
+            Op::Lte => Some(parse::<$t>($name, $value).map(|value| $field.lte(value))),
+            Op::Gt => Some(parse::<$t>($name, $value).map(|value| $field.gt(value))),
+            Op::Gte => Some(parse::<$t>($name, $value).map(|value| $field.gte(value))),
+            Op::Between => Some(parse_range::<$t>($name, $value).map(|(from, to)| $field.in_range(from, to))),
+            _ => None
+        }
+    );
+
+    (@text $field:ident, $name:ident, $op:ident, $value:ident, $t:ty) => (
+        match $op {
+            Op::Like => Some(parse::<$t>($name, $value).map(|value| $field.like(value))),
+            Op::ILike => Some(parse::<$t>($name, $value).map(|value| $field.ilike(value))),
+            _ => None
+        }
+    );
+
+    // Comparing with `null` means checking for NULL
+    (@nullable $field:ident, $name:ident, $op:ident, $value:ident, $t:ty) => (
+        match $op {
+            Op::IsNull => Some(Ok($field.is_null())),
+            Op::NotNull => Some(Ok($field.not_null())),
+            Op::Eq if $value.is_null() => Some(Ok($field.is_null())),
+            Op::Ne if $value.is_null() => Some(Ok($field.not_null())),
+            _ => None
+        }
+    );
+}
+
+filter_field!(bool);
+filter_field!(i8, ordered);
+filter_field!(i16, ordered);
+filter_field!(i32, ordered);
+filter_field!(i64, ordered);
+filter_field!(f32, ordered);
+filter_field!(f64, ordered);
+filter_field!(String, text);
+filter_field!(serde_json::Value);
+filter_field!(chrono::NaiveDateTime, ordered);
+filter_field!(uuid::Uuid);
+
+filter_field!(Option<bool>, nullable);
+filter_field!(Option<i8>, nullable, ordered);
+filter_field!(Option<i16>, nullable, ordered);
+filter_field!(Option<i32>, nullable, ordered);
+filter_field!(Option<i64>, nullable, ordered);
+filter_field!(Option<f32>, nullable, ordered);
+filter_field!(Option<f64>, nullable, ordered);
+filter_field!(Option<String>, nullable, text);
+filter_field!(Option<serde_json::Value>, nullable);
+filter_field!(Option<chrono::NaiveDateTime>, nullable, ordered);
+filter_field!(Option<uuid::Uuid>, nullable);
+
+/// Whitelist of the columns filters can reference, by the name the filters use.
+#[derive(Default)]
+pub struct Filters {
+    fields: Vec<(String, Box<dyn FilterField>)>
+}
+
+impl fmt::Debug for Filters {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.fields.iter().map(|(name, _)| name)).finish()
+    }
+}
+
+impl Filters {
+    pub fn new() -> Filters {
+        Filters::default()
+    }
+
+    pub fn field<F: FilterField + Clone + 'static>(mut self, name: &str, field: &F) -> Filters {
+        self.fields.retain(|(registered, _)| registered != name);
+        self.fields.push((name.to_string(), Box::new(field.clone())));
+        self
+    }
+
+    /// Predicate of a filter tree.
+    pub fn predicate(&self, filter: &Json) -> FilterResult<SharedPredicate> {
+        let object = match filter.as_object() {
+            Some(object) => object,
+            None => return Err(FilterError::Malformed(format!("expected an object, got `{}`", filter)))
+        };
+
+        for &(key, combine) in [("and", Combine::And), ("or", Combine::Or)].iter() {
+            if let Some(filters) = object.get(key) {
+                if object.len() > 1 {
+                    return Err(FilterError::Malformed(format!("`{}` can't be mixed with other keys", key)))
+                }
+                return self.combine(key, combine, filters)
+            }
+        }
+
+        let name = match object.get("field").and_then(|name| name.as_str()) {
+            Some(name) => name,
+            None => return Err(FilterError::Malformed("condition without a `field` name".to_string()))
+        };
+        let op = match object.get("op").and_then(|op| op.as_str()) {
+            Some(op) => Op::from_name(op).ok_or_else(|| FilterError::UnknownOperator(op.to_string()))?,
+            None => return Err(FilterError::Malformed(format!("condition on `{}` without an `op`", name)))
+        };
+        let field = match self.fields.iter().find(|&(registered, _)| registered == name) {
+            Some((_, field)) => field,
+            None => return Err(FilterError::UnknownField(name.to_string()))
+        };
+
+        field.predicate(name, op, object.get("value").unwrap_or(&Json::Null))
+    }
+
+    fn combine(&self, key: &str, combine: Combine, filters: &Json) -> FilterResult<SharedPredicate> {
+        let filters = match filters.as_array() {
+            Some(filters) if !filters.is_empty() => filters,
+            _ => return Err(FilterError::Malformed(format!("`{}` expects a non-empty list of filters", key)))
+        };
+
+        let mut predicate = self.predicate(&filters[0])?;
+        for filter in filters[1..].iter() {
+            let next = self.predicate(filter)?;
+            predicate = match combine {
+                Combine::And => predicate.and(next),
+                Combine::Or => predicate.or(next)
+            };
+        }
+        Ok(predicate)
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Combine {
+    And,
+    Or
+}
@@ -1,4 +1,4 @@
-use std::{fmt, rc};
+use std::{fmt, sync};
 
 use super::sql;
 use super::select_query;
@@ -6,28 +6,68 @@ use super::insert_query;
 use super::update_query;
 use super::delete_query;
 use super::field::{self, Field};
+use super::predicate;
+use super::expression;
 
-pub trait From: fmt::Debug {
-    fn as_sql(&self) -> &sql::FromToSql;
+pub trait From: fmt::Debug + Send + Sync {
+    fn as_sql(&self) -> &dyn sql::FromToSql;
     fn upcast_from(&self) -> SharedFrom;
+
+    /// Default predicates added to the where clause of queries built from it.
+    fn get_scopes(&self) -> Vec<predicate::SharedPredicate> { vec![] }
+
+    /// Column marking soft-deleted rows, qualified with the table name or alias.
+    fn get_soft_delete(&self) -> Option<DeletedAtField> { None }
+
+    /// Tenant column, qualified with the table name or alias.
+    fn get_tenant(&self) -> Option<TenantField> { None }
+
+    /// The table when selecting from a plain table.
+    fn as_table(&self) -> Option<&dyn Table> { None }
+
+    /// The query when selecting from a subquery.
+    fn as_select(&self) -> Option<&dyn select_query::AbstractSelectQuery> { None }
+
+    /// Alias of the subquery when selecting from one.
+    fn get_select_alias(&self) -> Option<&str> { None }
 }
 
-pub type BoxedFrom = Box<From + 'static>;
-pub type SharedFrom = rc::Rc<BoxedFrom>;
+/// Column of a soft-deleted table, only its name and nullability matter.
+pub type DeletedAtField = field::NamedField<Option<expression::RawExpression>>;
 
-pub trait Table: fmt::Debug {
+/// Column holding the tenant of the row, compared with the tenant of `SqlContext`.
+pub type TenantField = field::NamedField<expression::RawExpression>;
+
+pub type BoxedFrom = Box<dyn From + 'static>;
+pub type SharedFrom = sync::Arc<BoxedFrom>;
+
+pub trait Table: fmt::Debug + Send + Sync {
     fn upcast_table(&self) -> SharedTable;
     fn get_table_name(&self) -> &String;
     fn get_table_alias(&self) -> &Option<String>;
+
+    /// Default predicates added to the where clause of queries built from it.
+    fn get_scopes(&self) -> Vec<predicate::SharedPredicate> { vec![] }
+
+    /// Column marking soft-deleted rows, qualified with the table name or alias.
+    fn get_soft_delete(&self) -> Option<DeletedAtField> { None }
+
+    /// Tenant column, qualified with the table name or alias.
+    fn get_tenant(&self) -> Option<TenantField> { None }
 }
 
-pub type BoxedTable = Box<Table + 'static>;
-pub type SharedTable = rc::Rc<BoxedTable>;
+pub type BoxedTable = Box<dyn Table + 'static>;
+pub type SharedTable = sync::Arc<BoxedTable>;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TableDef {
     name: String,
-    alias: Option<String>
+    alias: Option<String>,
+    #[cfg_attr(feature = "serde", serde(with = "::serialize::shared"))]
+    scopes: Vec<predicate::SharedPredicate>,
+    soft_delete: Option<String>,
+    tenant: Option<String>
 }
 
 // FIXME: Remove after all stuff in insert_query::InsertQuery will be fixed
@@ -44,11 +84,11 @@ macro_rules! insert {
 
 impl TableDef {
     pub fn new(name: &str) -> TableDef {
-        TableDef { name: name.to_string(), alias: None }
+        TableDef { name: name.to_string(), alias: None, scopes: vec![], soft_delete: None, tenant: None }
     }
 
     pub fn new_with_alias(name: &str, alias: &str) -> TableDef {
-        TableDef { name: name.to_string(), alias: Some(alias.to_string()) }
+        TableDef { name: name.to_string(), alias: Some(alias.to_string()), scopes: vec![], soft_delete: None, tenant: None }
     }
 
     pub fn alias(&self, alias: &str) -> TableDef {
@@ -57,6 +97,33 @@ impl TableDef {
         table_def
     }
 
+    /// Adds a predicate ANDed into every select, update and delete built from the table.
+    pub fn scope(&self, predicate: predicate::SharedPredicate) -> TableDef {
+        let mut table_def = self.clone();
+        table_def.scopes.push(predicate);
+        table_def
+    }
+
+    /// Turns deletes into `UPDATE ... SET column = CURRENT_TIMESTAMP` and hides deleted rows from selects.
+    pub fn soft_delete(&self, column: &str) -> TableDef {
+        let mut table_def = self.clone();
+        table_def.soft_delete = Some(column.to_string());
+        table_def
+    }
+
+    /// Makes every query built from or joining the table filter by the tenant of `SqlContext`.
+    pub fn tenant(&self, column: &str) -> TableDef {
+        let mut table_def = self.clone();
+        table_def.tenant = Some(column.to_string());
+        table_def
+    }
+
+    fn qualified<T: Clone>(&self, column: &Option<String>) -> Option<field::NamedField<T>> {
+        column.as_ref().map(|column| {
+            field::NamedField::new_qual(column, &self.name, self.alias.as_ref().unwrap_or(&self.name))
+        })
+    }
+
     // FIXME: Remove after all stuff in insert_query::InsertQuery will be fixed
     insert!(insert_1, (T0, _t0));
 
@@ -68,7 +135,7 @@ impl TableDef {
 
 impl Table for TableDef {
     fn upcast_table(&self) -> SharedTable {
-        rc::Rc::new(Box::new(self.clone()))
+        sync::Arc::new(Box::new(self.clone()))
     }
 
     fn get_table_name(&self) -> &String {
@@ -78,15 +145,43 @@ impl Table for TableDef {
     fn get_table_alias(&self) -> &Option<String> {
         &self.alias
     }
+
+    fn get_scopes(&self) -> Vec<predicate::SharedPredicate> {
+        self.scopes.clone()
+    }
+
+    fn get_soft_delete(&self) -> Option<DeletedAtField> {
+        self.qualified(&self.soft_delete)
+    }
+
+    fn get_tenant(&self) -> Option<TenantField> {
+        self.qualified(&self.tenant)
+    }
 }
 
 impl From for TableDef {
-    fn as_sql(&self) -> &sql::FromToSql {
+    fn as_sql(&self) -> &dyn sql::FromToSql {
         self
     }
 
     fn upcast_from(&self) -> SharedFrom {
-        rc::Rc::new(Box::new(self.clone()))
+        sync::Arc::new(Box::new(self.clone()))
+    }
+
+    fn get_scopes(&self) -> Vec<predicate::SharedPredicate> {
+        self.scopes.clone()
+    }
+
+    fn get_soft_delete(&self) -> Option<DeletedAtField> {
+        self.qualified(&self.soft_delete)
+    }
+
+    fn get_tenant(&self) -> Option<TenantField> {
+        self.qualified(&self.tenant)
+    }
+
+    fn as_table(&self) -> Option<&dyn Table> {
+        Some(self)
     }
 }
 
@@ -96,18 +191,27 @@ impl update_query::Updatable<()> for TableDef {}
 impl delete_query::Deletable<()> for TableDef {}
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(bound = ""))]
 pub struct FromSelect<T, L, M> {
     pub select: select_query::SelectQuery<T, L, M>,
     pub alias: String
 }
 
 impl<T: Clone + 'static + fmt::Debug, L: Clone + 'static + fmt::Debug, M: Clone + 'static + fmt::Debug> From for FromSelect<T, L, M> {
-    fn as_sql(&self) -> &sql::FromToSql {
+    fn as_sql(&self) -> &dyn sql::FromToSql {
         self
     }
 
     fn upcast_from(&self) -> SharedFrom {
-        rc::Rc::new(Box::new(self.clone()))
+        sync::Arc::new(Box::new(self.clone()))
+    }
+
+    fn as_select(&self) -> Option<&dyn select_query::AbstractSelectQuery> {
+        Some(&self.select)
+    }
+
+    fn get_select_alias(&self) -> Option<&str> {
+        Some(&self.alias)
     }
 }
 
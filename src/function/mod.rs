@@ -1,13 +1,14 @@
 use std::marker;
-use std::{fmt, rc};
+use std::{fmt, sync};
 use chrono;
 
 use expression;
 use field;
 use sql;
+use visit;
 
 macro_rules! agg_func {
-    ($foo:ident, $foo_arg:ident, $foo_low:ident) => (
+    ($foo:ident, $foo_arg:ident, $foo_low:ident, $name:expr) => (
         pub trait $foo_arg<R: Clone + 'static, T: Clone + 'static>: Clone + expression::Expression<T>+ 'static {
             fn $foo_low(&self) -> $foo<R, T, Self> {
                 $foo::new(self.clone())
@@ -18,8 +19,8 @@ macro_rules! agg_func {
         pub struct $foo<R: Clone + 'static, T: Clone + 'static, E: $foo_arg<R, T>> {
             pub expression: E,
 
-            _marker_r: marker::PhantomData<R>,
-            _marker_t: marker::PhantomData<T>,
+            _marker_r: marker::PhantomData<fn() -> R>,
+            _marker_t: marker::PhantomData<fn() -> T>,
         }
 
         impl<R: Clone + 'static, T: Clone + 'static, E: $foo_arg<R, T> + 'static> $foo<R, T, E> {
@@ -34,12 +35,16 @@ macro_rules! agg_func {
         }
 
         impl<R: Clone + 'static + fmt::Debug, T: Clone + 'static + fmt::Debug, E: $foo_arg<R, T> + 'static> expression::UntypedExpression for $foo<R, T, E> {
-            fn expression_as_sql(&self) -> &sql::ToSql {
+            fn expression_as_sql(&self) -> &dyn sql::ToSql {
                 self
             }
 
             fn upcast_expression(&self) -> expression::SharedExpression {
-                rc::Rc::new(Box::new(self.clone()) as expression::BoxedExpression)
+                sync::Arc::new(Box::new(self.clone()) as expression::BoxedExpression)
+            }
+
+            fn expression_node(&self) -> visit::Node<'_> {
+                visit::Node::Function($name, vec![self.expression.expression_node()])
             }
         }
 
@@ -47,7 +52,7 @@ macro_rules! agg_func {
     )
 }
 
-agg_func!(Min, MinArg, min);
+agg_func!(Min, MinArg, min, "MIN");
 
 impl MinArg<i8, i8> for field::I8Field {}
 impl MinArg<i16, i16> for field::I16Field {}
@@ -57,8 +62,9 @@ impl MinArg<f32, f32> for field::F32Field {}
 impl MinArg<f64, f64> for field::F64Field {}
 impl MinArg<String, String> for field::StringField {}
 impl MinArg<chrono::NaiveDateTime, chrono::NaiveDateTime> for field::TimespecField {}
+impl MinArg<expression::RawExpression, expression::RawExpression> for expression::CastExpression<expression::RawExpression> {}
 
-agg_func!(Max, MaxArg, max);
+agg_func!(Max, MaxArg, max, "MAX");
 
 impl MaxArg<i8, i8> for field::I8Field {}
 impl MaxArg<i16, i16> for field::I16Field {}
@@ -68,8 +74,9 @@ impl MaxArg<f32, f32> for field::F32Field {}
 impl MaxArg<f64, f64> for field::F64Field {}
 impl MaxArg<String, String> for field::StringField {}
 impl MaxArg<chrono::NaiveDateTime, chrono::NaiveDateTime> for field::TimespecField {}
+impl MaxArg<expression::RawExpression, expression::RawExpression> for expression::CastExpression<expression::RawExpression> {}
 
-agg_func!(Sum, SumArg, sum);
+agg_func!(Sum, SumArg, sum, "SUM");
 
 impl SumArg<i64, i8> for field::I8Field {}
 impl SumArg<i64, i16> for field::I16Field {}
@@ -77,8 +84,9 @@ impl SumArg<i64, i32> for field::I32Field {}
 impl SumArg<i64, i64> for field::I64Field {}
 impl SumArg<f64, f32> for field::F32Field {}
 impl SumArg<f64, f64> for field::F64Field {}
+impl SumArg<expression::RawExpression, expression::RawExpression> for expression::CastExpression<expression::RawExpression> {}
 
-agg_func!(Avg, AvgArg, avg);
+agg_func!(Avg, AvgArg, avg, "AVG");
 
 impl AvgArg<i8, i8> for field::I8Field {}
 impl AvgArg<i16, i16> for field::I16Field {}
@@ -86,21 +94,27 @@ impl AvgArg<i32, i32> for field::I32Field {}
 impl AvgArg<i64, i64> for field::I64Field {}
 impl AvgArg<f32, f32> for field::F32Field {}
 impl AvgArg<f64, f64> for field::F64Field {}
+impl AvgArg<expression::RawExpression, expression::RawExpression> for expression::CastExpression<expression::RawExpression> {}
 
-agg_func!(Count, CountArg, count);
+agg_func!(Count, CountArg, count, "COUNT");
 
 impl<T: 'static + expression::PrimitiveType + Clone> CountArg<i64, T> for field::NamedField<T> {}
+impl CountArg<expression::RawExpression, expression::RawExpression> for expression::CastExpression<expression::RawExpression> {}
 
 #[derive(Clone, Copy, Debug)]
 pub struct CountAll;
 
 impl expression::UntypedExpression for CountAll {
-    fn expression_as_sql(&self) -> &sql::ToSql {
+    fn expression_as_sql(&self) -> &dyn sql::ToSql {
         self
     }
 
     fn upcast_expression(&self) -> expression::SharedExpression {
-        rc::Rc::new(Box::new(self.clone()) as expression::BoxedExpression)
+        sync::Arc::new(Box::new(*self) as expression::BoxedExpression)
+    }
+
+    fn expression_node(&self) -> visit::Node<'_> {
+        visit::Node::Function("COUNT", vec![])
     }
 }
 
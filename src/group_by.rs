@@ -1,13 +1,20 @@
 use super::expression;
+use super::visit;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct GroupBy {
+    #[cfg_attr(feature = "serde", serde(with = "::serialize::shared"))]
     by: Vec<expression::SharedExpression>
 }
 
 impl GroupBy {
     pub fn get_by(&self) -> &Vec<expression::SharedExpression> { &self.by }
-    pub fn by(fields: &[&expression::UntypedExpression]) -> GroupBy {
+    pub fn by(fields: &[&dyn expression::UntypedExpression]) -> GroupBy {
         GroupBy { by: fields.iter().map(|f| f.upcast_expression()).collect() }
     }
+
+    pub fn rewrite<R: visit::Rewriter + ?Sized>(&self, rewriter: &mut R) -> GroupBy {
+        GroupBy { by: self.by.iter().map(|e| visit::rewrite_expression(rewriter, e)).collect() }
+    }
 }
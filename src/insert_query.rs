@@ -1,16 +1,20 @@
-use std::mem;
-use std::marker;
+use std::{cmp, marker, slice};
 
 use super::from;
 use super::field;
 use super::select_query;
-use super::expression;
+use super::expression::{self, UntypedExpression};
+use super::model;
+use super::visit;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum InsertValue<T> {
     Value {
+        #[cfg_attr(feature = "serde", serde(with = "::serialize::shared"))]
         expression: expression::SharedExpression,
-        _marker: marker::PhantomData<T>
+        #[cfg_attr(feature = "serde", serde(skip))]
+        _marker: marker::PhantomData<fn() -> T>
     },
     Default
 }
@@ -20,21 +24,100 @@ pub trait ToInsertValue<T> {
 }
 
 impl<T> InsertValue<T> {
-    pub fn new(exp: &expression::Expression<T>) -> InsertValue<T> {
+    pub fn new<E: ToInsertValue<T>>(exp: E) -> InsertValue<T> {
+        exp.to_insert_val()
+    }
+
+    pub fn value_node(&self) -> visit::Node<'_> {
+        match *self {
+            InsertValue::Value { ref expression, .. } => expression.expression_node(),
+            InsertValue::Default => visit::Node::Default
+        }
+    }
+
+    /// Copy of the value with its expression rewritten, see `visit::rewrite_expression`.
+    pub fn rewrite<R: visit::Rewriter + ?Sized>(&self, rewriter: &mut R) -> InsertValue<T> {
+        match *self {
+            InsertValue::Value { ref expression, .. } => InsertValue::Value {
+                expression: visit::rewrite_expression(rewriter, expression),
+                _marker: marker::PhantomData
+            },
+            InsertValue::Default => InsertValue::Default
+        }
+    }
+}
+
+/// Row of typed insert values, walked by visitors.
+pub trait InsertRow {
+    fn row_nodes(&self) -> Vec<visit::Node<'_>>;
+    fn rewrite_row<R: visit::Rewriter + ?Sized>(&self, rewriter: &mut R) -> Self where Self: Sized;
+}
+
+impl InsertRow for () {
+    fn row_nodes(&self) -> Vec<visit::Node<'_>> { vec![] }
+    fn rewrite_row<R: visit::Rewriter + ?Sized>(&self, _rewriter: &mut R) {}
+}
+
+macro_rules! insert_row_for_tuple {
+    ($($t:ident, $var:ident),+) => (
+        impl<$($t,)+> InsertRow for ($(InsertValue<$t>),+,) {
+            fn row_nodes(&self) -> Vec<visit::Node<'_>> {
+                let &($(ref $var,)+) = self;
+                vec![$($var.value_node()),+]
+            }
+
+            fn rewrite_row<R: visit::Rewriter + ?Sized>(&self, rewriter: &mut R) -> ($(InsertValue<$t>),+,) {
+                let &($(ref $var,)+) = self;
+                ($($var.rewrite(rewriter)),+,)
+            }
+        }
+    )
+}
+
+insert_row_for_tuple!(T1, t1);
+insert_row_for_tuple!(T1, t1, T2, t2);
+insert_row_for_tuple!(T1, t1, T2, t2, T3, t3);
+insert_row_for_tuple!(T1, t1, T2, t2, T3, t3, T4, t4);
+insert_row_for_tuple!(T1, t1, T2, t2, T3, t3, T4, t4, T5, t5);
+insert_row_for_tuple!(T1, t1, T2, t2, T3, t3, T4, t4, T5, t5, T6, t6);
+insert_row_for_tuple!(T1, t1, T2, t2, T3, t3, T4, t4, T5, t5, T6, t6, T7, t7);
+insert_row_for_tuple!(T1, t1, T2, t2, T3, t3, T4, t4, T5, t5, T6, t6, T7, t7, T8, t8);
+insert_row_for_tuple!(T1, t1, T2, t2, T3, t3, T4, t4, T5, t5, T6, t6, T7, t7, T8, t8, T9, t9);
+insert_row_for_tuple!(T1, t1, T2, t2, T3, t3, T4, t4, T5, t5, T6, t6, T7, t7, T8, t8, T9, t9, T10, t10);
+insert_row_for_tuple!(T1, t1, T2, t2, T3, t3, T4, t4, T5, t5, T6, t6, T7, t7, T8, t8, T9, t9, T10, t10, T11, t11);
+insert_row_for_tuple!(T1, t1, T2, t2, T3, t3, T4, t4, T5, t5, T6, t6, T7, t7, T8, t8, T9, t9, T10, t10, T11, t11, T12, t12);
+
+impl<T, E: expression::Expression<T> + ?Sized> ToInsertValue<T> for &E {
+    fn to_insert_val(&self) -> InsertValue<T> {
         InsertValue::Value {
-            expression: exp.upcast_expression(),
+            expression: self.upcast_expression(),
             _marker: marker::PhantomData
         }
     }
 }
 
-impl<'a, 'b, T> ToInsertValue<T> for &'a (expression::Expression<T> + 'b) {
+impl<T> ToInsertValue<T> for expression::CastExpression<T> {
     fn to_insert_val(&self) -> InsertValue<T> {
-        InsertValue::new(*self)
+        InsertValue::Value {
+            expression: self.upcast_expression(),
+            _marker: marker::PhantomData
+        }
+    }
+}
+
+impl<T> ToInsertValue<T> for InsertValue<T> {
+    fn to_insert_val(&self) -> InsertValue<T> {
+        match *self {
+            InsertValue::Value { ref expression, .. } => {
+                InsertValue::Value { expression: expression.clone(), _marker: marker::PhantomData }
+            },
+            InsertValue::Default => InsertValue::Default
+        }
     }
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(bound(serialize = "V: ::serde::Serialize", deserialize = "V: ::serde::Deserialize<'de>")))]
 pub enum Insert<T, V, M> {
     DefaultValues,
     Values(Vec<V>),
@@ -43,14 +126,19 @@ pub enum Insert<T, V, M> {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(bound(serialize = "V: ::serde::Serialize", deserialize = "V: ::serde::Deserialize<'de>")))]
 pub struct InsertQuery<T, V, M, RT, RL> {
+    #[cfg_attr(feature = "serde", serde(with = "::serialize::shared"))]
     into: from::SharedTable,
+    #[cfg_attr(feature = "serde", serde(with = "::serialize::shared"))]
     cols: Option<Vec<field::SharedField>>,
     values: Insert<T, V, M>,
     returning: Option<select_query::Select>,
 
-    _marker_rt: marker::PhantomData<RT>,
-    _marker_rl: marker::PhantomData<RL>
+    #[cfg_attr(feature = "serde", serde(skip))]
+    _marker_rt: marker::PhantomData<fn() -> RT>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    _marker_rl: marker::PhantomData<fn() -> RL>
 }
 
 #[macro_export]
@@ -89,13 +177,27 @@ macro_rules! insertable {
                  InsertQuery::new(self)
             }
 
-            fn insert_fields(&self, fields: &[&field::Field]) -> InsertQuery<(), (), M, (), ()> {
+            fn insert_fields(&self, fields: &[&dyn field::Field]) -> InsertQuery<(), (), M, (), ()> {
                 let mut cols = vec![];
                 for field in fields.iter() {
                     cols.push(field.upcast_field())
                 }
                 InsertQuery::new_with_cols(self, cols)
             }
+
+            fn insert(&self, model: &M) -> InsertQuery<(), (), M, (), ()> where M: model::Model {
+                self.insert_many(slice::from_ref(model))
+            }
+
+            fn insert_many(&self, models: &[M]) -> InsertQuery<(), (), M, (), ()> where M: model::Model {
+                let mut query = InsertQuery::new_with_cols(self, M::columns());
+                for model in models.iter() {
+                    query.push_untyped_values(model.values().into_iter().map(|expression| {
+                        InsertValue::Value { expression, _marker: marker::PhantomData }
+                    }).collect());
+                }
+                query
+            }
         }
     )
 }
@@ -104,7 +206,7 @@ macro_rules! insertable {
 insertable!();
 
 impl<T: Clone, V: Clone, M: Clone, RT: Clone, RL: Clone> InsertQuery<T, V, M, RT, RL> {
-    pub fn new(into: &from::Table) -> InsertQuery<T, V, M, RT, RL> {
+    pub fn new(into: &dyn from::Table) -> InsertQuery<T, V, M, RT, RL> {
         InsertQuery {
             into: into.upcast_table(),
             cols: None,
@@ -116,7 +218,7 @@ impl<T: Clone, V: Clone, M: Clone, RT: Clone, RL: Clone> InsertQuery<T, V, M, RT
         }
     }
 
-    pub fn new_with_cols(into: &from::Table, cols: Vec<field::SharedField>) -> InsertQuery<T, V, M, RT, RL> {
+    pub fn new_with_cols(into: &dyn from::Table, cols: Vec<field::SharedField>) -> InsertQuery<T, V, M, RT, RL> {
         InsertQuery {
             into: into.upcast_table(),
             cols: Some(cols),
@@ -155,7 +257,11 @@ impl<T: Clone, V: Clone, M: Clone, RT: Clone, RL: Clone> InsertQuery<T, V, M, RT
         }
     }
 
-    pub fn push_untyped(&mut self, values: &[&expression::Expression<expression::RawExpression>]) {
+    pub fn push_untyped<E: ToInsertValue<expression::RawExpression>>(&mut self, values: &[E]) {
+        self.push_untyped_values(values.iter().map(|v| v.to_insert_val()).collect())
+    }
+
+    fn push_untyped_values(&mut self, values_vec: Vec<InsertValue<expression::RawExpression>>) {
         let mut reassign = false;
         match &self.values {
             &Insert::DefaultValues | &Insert::FromSelect(_) => {
@@ -164,7 +270,6 @@ impl<T: Clone, V: Clone, M: Clone, RT: Clone, RL: Clone> InsertQuery<T, V, M, RT
             _ => ()
         }
 
-        let values_vec = values.iter().map(|v| v.to_insert_val()).collect();
 
         if reassign {
             self.values = Insert::UntypedValues(vec![values_vec])
@@ -178,35 +283,93 @@ impl<T: Clone, V: Clone, M: Clone, RT: Clone, RL: Clone> InsertQuery<T, V, M, RT
         }
     }
 
+    /// Rebuilds the values, selected rows and returned expressions of the query with `rewriter`.
+    pub fn rewrite<R: visit::Rewriter + ?Sized>(&self, rewriter: &mut R) -> InsertQuery<T, V, M, RT, RL> where V: InsertRow {
+        with_clone!(self, query, {
+            query.values = match self.values {
+                Insert::DefaultValues => Insert::DefaultValues,
+                Insert::Values(ref rows) => Insert::Values(rows.iter().map(|row| row.rewrite_row(rewriter)).collect()),
+                Insert::UntypedValues(ref rows) => Insert::UntypedValues(rows.iter().map(|row| {
+                    row.iter().map(|value| value.rewrite(rewriter)).collect()
+                }).collect()),
+                Insert::FromSelect(ref select) => Insert::FromSelect(select.rewrite(rewriter))
+            };
+            query.returning = self.returning.as_ref().map(|returning| visit::rewrite_select_list(rewriter, returning));
+        })
+    }
+
     pub fn from_select(&self, select: select_query::SelectQuery<T, select_query::LimitMany, M>) -> InsertQuery<T, V, M, RT, RL> {
         with_clone!(self, query, query.values = Insert::FromSelect(select))
     }
 
+    /// Splits a bulk insert into queries binding at most `max_binds` values each,
+    /// counting one value per column, e.g. `query.split(adapter.max_binds())`.
+    pub fn split(&self, max_binds: usize) -> Vec<InsertQuery<T, V, M, RT, RL>> {
+        let mut width = match (&self.cols, &self.values) {
+            (Some(cols), _) => cols.len(),
+            (None, Insert::UntypedValues(rows)) => rows.first().map(|row| row.len()).unwrap_or(0),
+            _ => 0
+        };
+
+        if self.into.get_tenant().is_some() {
+            width += 1;
+        }
+
+        let rows_per_query = max_binds.checked_div(width).map(|rows| cmp::max(1, rows)).unwrap_or(usize::MAX);
+
+        match self.values {
+            Insert::Values(ref rows) if rows.len() > rows_per_query => {
+                rows.chunks(rows_per_query).map(|chunk| {
+                    with_clone!(self, query, query.values = Insert::Values(chunk.to_vec()))
+                }).collect()
+            },
+            Insert::UntypedValues(ref rows) if rows.len() > rows_per_query => {
+                rows.chunks(rows_per_query).map(|chunk| {
+                    with_clone!(self, query, query.values = Insert::UntypedValues(chunk.to_vec()))
+                }).collect()
+            },
+            _ => vec![self.clone()]
+        }
+    }
+
 }
 
 impl<T: Clone, V: Clone, M: Clone, RT, RL> InsertQuery<T, V, M, RT, RL> {
-    pub fn returning_1<T1: Clone>(mut self, field: &expression::Expression<T1>) -> InsertQuery<T, V, M, (T1), select_query::LimitMany> {
+    // Rebuilds the query with other result types
+    fn retype<RT2, RL2>(self) -> InsertQuery<T, V, M, RT2, RL2> {
+        InsertQuery {
+            into: self.into,
+            cols: self.cols,
+            values: self.values,
+            returning: self.returning,
+
+            _marker_rt: marker::PhantomData,
+            _marker_rl: marker::PhantomData,
+        }
+    }
+
+    pub fn returning_1<T1: Clone>(mut self, field: &dyn expression::Expression<T1>) -> InsertQuery<T, V, M, T1, select_query::LimitMany> {
         self.returning = Some(select_query::Select::Only(vec![field.upcast_expression()]));
-        unsafe{ mem::transmute(self) }
+        self.retype()
     }
 
-    pub fn returning_2<T1: Clone, T2: Clone>(mut self, field1: &expression::Expression<T1>, field2: &expression::Expression<T2>) -> InsertQuery<T, V, M, (T1, T2), select_query::LimitMany> {
+    pub fn returning_2<T1: Clone, T2: Clone>(mut self, field1: &dyn expression::Expression<T1>, field2: &dyn expression::Expression<T2>) -> InsertQuery<T, V, M, (T1, T2), select_query::LimitMany> {
         self.returning = Some(select_query::Select::Only(vec![field1.upcast_expression(), field2.upcast_expression()]));
-        unsafe{ mem::transmute(self) }
+        self.retype()
     }
 
-    pub fn returning(mut self, fields: &[&expression::UntypedExpression]) -> InsertQuery<T, V, M, (), select_query::LimitMany> {
+    pub fn returning(mut self, fields: &[&dyn expression::UntypedExpression]) -> InsertQuery<T, V, M, (), select_query::LimitMany> {
         self.returning = Some(select_query::Select::Only(fields.iter().map(|f| f.upcast_expression()).collect()));
-        unsafe{ mem::transmute(self) }
+        self.retype()
     }
 
     pub fn returning_all(mut self) -> InsertQuery<T, V, M, (), select_query::LimitMany> {
         self.returning = Some(select_query::Select::All);
-        unsafe{ mem::transmute(self) }
+        self.retype()
     }
 
     pub fn no_returning(mut self) -> InsertQuery<T, V, M, (), select_query::NoResult> {
         self.returning = None;
-        unsafe{ mem::transmute(self) }
+        self.retype()
     }
 }
@@ -2,6 +2,7 @@ use super::from;
 use super::predicate;
 
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ConditionedJoinType {
     InnerJoin,
     FullOuterJoin,
@@ -13,6 +14,7 @@ pub enum ConditionedJoinType {
 }
 
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum UnconditionedJoinType {
     NaturalJoin,
     NaturalLeftJoin,
@@ -22,15 +24,19 @@ pub enum UnconditionedJoinType {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Join {
     ConditionedJoin {
         join_type: ConditionedJoinType,
+        #[cfg_attr(feature = "serde", serde(with = "::serialize::shared"))]
         from: from::SharedFrom,
+        #[cfg_attr(feature = "serde", serde(with = "::serialize::shared"))]
         on: predicate::SharedPredicate
     },
 
     UnconditionedJoin {
         join_type: UnconditionedJoinType,
+        #[cfg_attr(feature = "serde", serde(with = "::serialize::shared"))]
         from: from::SharedFrom
     }
 }
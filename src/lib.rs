@@ -10,7 +10,14 @@ extern crate chrono;
 #[cfg(feature = "postgres")]
 extern crate postgres;
 extern crate uuid;
+extern crate deuterium_derive;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde_derive;
 
+#[allow(unused_imports)]
 mod prelude {
     pub use {
         ToIsPredicate,
@@ -24,6 +31,7 @@ mod prelude {
         ToIsNullPredicate,
         Selectable,
         Queryable,
+        Scoped,
         Orderable,
         ToSelectQuery,
         Updatable,
@@ -74,23 +82,30 @@ pub use predicate::{
     IsPredicate, ToIsPredicate,
     OrPredicate, ToOrPredicate,
     AndPredicate, ToAndPredicate,
+    AllPredicate,
+    AnyPredicate,
     InPredicate, ToInPredicate,
     InRangePredicate, ToInRangePredicate, InRangeBounds,
     InequalityPredicate, ToInequalityPredicate, Inequality,
     ExcludePredicate, ToExcludePredicate,
     LikePredicate, ToLikePredicate,
     IsNullPredicate, ToIsNullPredicate,
-    RawPredicate
+    RawPredicate,
+    TenantPredicate
 };
 
 pub use select_query::{
     Selectable,
     Queryable,
+    HasHaving,
+    Scoped,
     Orderable,
     SelectQuery,
+    SelectParts,
     SharedSelectQuery,
     ToSelectQuery,
     Select,
+    Deleted,
     NoResult,
     LimitOne,
     LimitTwo,
@@ -101,6 +116,8 @@ pub use update_query::{
     UpdateQuery,
     FieldUpdate,
     FieldUpd,
+    BoxedFieldUpdate,
+    SharedFieldUpdate,
     Updatable,
     ToFieldUpdate,
 };
@@ -110,6 +127,7 @@ pub use insert_query::{
     Insertable,
     ToInsertValue,
     InsertValue,
+    InsertRow,
 };
 
 pub use delete_query::{
@@ -123,14 +141,15 @@ pub use expression::{
     Expression,
     SharedExpression,
     RawExpression,
+    CastExpression,
     ListExpression,
     ToExpression,
     ToListExpression
 };
 
-pub use sql::{SqlContext, ToSql, QueryToSql, FromToSql, ToPredicateValue};
+pub use sql::{SqlContext, ToSql, QueryToSql, FromToSql, ToPredicateValue, CompiledQuery, DebugSql, Template, Fingerprint, Value, NamedValues};
 #[cfg(feature = "postgres")] pub use sql::AsPostgresValue;
-pub use from::{TableDef, Table, BoxedTable, SharedTable, From, BoxedFrom, SharedFrom};
+pub use from::{TableDef, Table, BoxedTable, SharedTable, From, BoxedFrom, SharedFrom, DeletedAtField, TenantField};
 
 pub use function::{
     Sum, SumArg,
@@ -141,7 +160,34 @@ pub use function::{
     CountAll
 };
 
-pub use placeholder::Placeholder;
+pub use placeholder::{Placeholder, NamedPlaceholder};
+
+pub use visit::{
+    Node,
+    PredicateNode,
+    Visitor,
+    Visit,
+    Rewriter,
+    walk_select,
+    walk_select_list,
+    walk_from,
+    walk_join,
+    walk_predicate,
+    walk_update,
+    walk_node,
+    rewrite_predicate,
+    rewrite_expression,
+    rewrite_update,
+    rewrite_select_list,
+    rewrite_join
+};
+
+pub use model::Model;
+pub use deuterium_derive::Model;
+pub use tracked::{Tracked, TrackedError, TrackedResult};
+pub use relation::{Relation, RelationKind};
+pub use error::Error;
+#[cfg(feature = "postgres")] pub use model::Row;
 
 macro_rules! with_clone {
     ($slf: ident, $v:ident, $ex:expr) => ({
@@ -169,3 +215,17 @@ mod distinct;
 mod group_by;
 mod function;
 mod placeholder;
+#[macro_use]
+mod model;
+mod tracked;
+#[macro_use]
+mod relation;
+mod error;
+mod visit;
+pub mod codegen;
+pub mod parse;
+pub mod filter;
+pub mod eval;
+pub mod simplify;
+#[cfg(feature = "serde")]
+mod serialize;
@@ -0,0 +1,111 @@
+use std::{any, fmt};
+#[cfg(feature = "postgres")] use postgres;
+
+use super::from;
+use super::field;
+use super::expression;
+use super::predicate;
+use super::update_query;
+#[cfg(feature = "postgres")] use super::select_query;
+#[cfg(feature = "postgres")] use super::sql::{self, QueryToSql};
+#[cfg(feature = "postgres")] use super::error;
+
+/// Plain struct mapped to the row of a table. Implement it with `#[derive(Model)]`:
+///
+/// ```ignore
+/// #[derive(Clone, Debug, Model)]
+/// #[model(table = "jedi")]
+/// pub struct Jedi {
+///     #[primary_key]
+///     pub id: i32,
+///     pub name: String,
+///     pub force_level: Option<i8>,
+/// }
+/// ```
+///
+/// The derive also declares `JediTable` with typed `NamedField`s for every column,
+/// `#[model(table = "jedi", table_struct = "Jedis")]` names it differently. It implements
+/// `Selectable<Jedi>`, `Insertable<Jedi>`, `Updatable<Jedi>` and `Deletable<Jedi>`.
+/// Column names are the same as field names. Every `#[primary_key]` field is part of
+/// the key, `update_from` sets the other columns and renders `Error::EmptyUpdate`
+/// when there are none. `Jedi` implements `NamedValues`, so it binds named
+/// placeholders called after its fields.
+pub trait Model: Clone + fmt::Debug + 'static {
+    type Table: from::Table + Clone;
+
+    /// Table definition the model is stored in.
+    fn table() -> Self::Table;
+
+    /// All columns of the model in declaration order.
+    fn columns() -> Vec<field::SharedField>;
+
+    /// Values of all columns in the same order as `columns`.
+    fn values(&self) -> Vec<expression::SharedExpression>;
+
+    /// Columns of the primary key, several of them for a composite key.
+    fn primary_key() -> Vec<field::SharedField>;
+
+    /// `SET` entries for every column except the primary key.
+    fn field_updates(&self) -> Vec<update_query::SharedFieldUpdate>;
+
+    /// Predicate matching the row with the primary key of the model.
+    fn primary_key_predicate(&self) -> predicate::SharedPredicate;
+
+    /// `SET` entry for a single column with the current value.
+    fn field_update(&self, column: &str) -> Option<update_query::SharedFieldUpdate>;
+
+    /// Predicate matching the column with the current value.
+    fn column_predicate(&self, column: &str) -> Option<predicate::SharedPredicate>;
+
+    /// Assigns the value to the column, returns `false` if there is no such column
+    /// or the type of the value differs.
+    fn assign(&mut self, column: &str, value: &dyn any::Any) -> bool;
+
+    /// Current value of the column, `None` if there is no such column.
+    fn value_of(&self, column: &str) -> Option<&dyn any::Any>;
+
+    #[cfg(feature = "postgres")]
+    fn from_row(row: &Row) -> Self;
+}
+
+#[cfg(feature = "postgres")]
+pub type Row<'a> = postgres::rows::Row<'a>;
+
+#[cfg(feature = "postgres")]
+impl<T: Clone, L: Clone, M: Model> select_query::SelectQuery<T, L, M> {
+    /// Runs the query and decodes every row into the model.
+    pub fn query(&self, conn: &dyn postgres::GenericConnection) -> Result<Vec<M>, error::Error> {
+        self.query_with_context(conn, &mut sql::SqlContext::new(Box::new(sql::PostgreSqlAdapter)))
+    }
+
+    /// Same as `query`, but renders with the given context, e.g. one with a tenant set.
+    pub fn query_with_context(&self, conn: &dyn postgres::GenericConnection, ctx: &mut sql::SqlContext) -> Result<Vec<M>, error::Error> {
+        let sql = self.to_final_sql(ctx)?;
+        if let Some((name, _)) = ctx.get_named().first() {
+            return Err(error::Error::UnboundPlaceholder { name: name.to_string() })
+        }
+        let params: Vec<&dyn postgres::types::ToSql> = ctx.data().iter().map(|v| &**v).collect();
+        let rows = conn.query(&sql, &params)?;
+        Ok(rows.iter().map(|row| M::from_row(&row)).collect())
+    }
+}
+
+#[cfg(feature = "postgres")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __model_from_row {
+    ($model:ident, $($field:ident),+) => (
+        fn from_row(row: &$crate::Row) -> $model {
+            $model {
+                $($field: row.get(stringify!($field)),)+
+            }
+        }
+    )
+}
+
+#[cfg(not(feature = "postgres"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __model_from_row {
+    ($model:ident, $($field:ident),+) => ()
+}
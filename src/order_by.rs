@@ -1,26 +1,30 @@
 use expression;
+use visit;
 
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Order {
     Asc,
     Desc
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OrderBy {
+    #[cfg_attr(feature = "serde", serde(with = "::serialize::shared"))]
     by: expression::SharedExpression,
     order: Order
 }
 
 impl OrderBy {
-    pub fn by(expression: &expression::UntypedExpression) -> OrderBy {
+    pub fn by(expression: &dyn expression::UntypedExpression) -> OrderBy {
         OrderBy {
             by: expression.upcast_expression(),
             order: Order::Asc
         }
     }
 
-    pub fn reverse_by(expression: &expression::UntypedExpression) -> OrderBy {
+    pub fn reverse_by(expression: &dyn expression::UntypedExpression) -> OrderBy {
         OrderBy {
             by: expression.upcast_expression(),
             order: Order::Desc
@@ -34,4 +38,11 @@ impl OrderBy {
     pub fn get_order(&self) -> &Order {
         &self.order
     }
+
+    pub fn rewrite<R: visit::Rewriter + ?Sized>(&self, rewriter: &mut R) -> OrderBy {
+        OrderBy {
+            by: visit::rewrite_expression(rewriter, &self.by),
+            order: self.order
+        }
+    }
 }
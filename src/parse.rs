@@ -0,0 +1,932 @@
+//! Parser of SQL text into untyped queries.
+//!
+//! Understands the subset of SQL Deuterium can build: `SELECT` with joins, `WHERE`, `GROUP BY`,
+//! `HAVING`, `ORDER BY`, `LIMIT`, `OFFSET` and `FOR UPDATE`, and `INSERT`, `UPDATE` and `DELETE`
+//! with `RETURNING`. Columns and other expressions become `RawExpression`s and are kept as written,
+//! conditions become the matching predicates and placeholders (`$1`, `?` or `:name`) standing for a
+//! whole operand become `Placeholder`s, so the parsed query can be rewritten and rendered for
+//! another adapter.
+
+use std::fmt;
+
+use super::expression::{RawExpression, CastExpression, SharedExpression, UntypedExpression};
+use super::field::{Field, NamedField};
+use super::from::{From, Table, TableDef, SharedFrom};
+use super::insert_query::{InsertQuery, InsertValue};
+use super::update_query::{UpdateQuery, ToFieldUpdate};
+use super::delete_query::DeleteQuery;
+use super::select_query::{SelectQuery, Select, LimitMany, NoResult, Queryable, HasHaving, Orderable};
+use super::predicate::{
+    SharedPredicate,
+    RawPredicate,
+    ToSharedPredicate,
+    ToIsPredicate,
+    ToOrPredicate,
+    ToAndPredicate,
+    ToInPredicate,
+    ToInRangePredicate,
+    ToInequalityPredicate,
+    ToExcludePredicate,
+    ToLikePredicate,
+    ToIsNullPredicate
+};
+use super::placeholder::Placeholder;
+use super::sql;
+use super::visit;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParseError {
+    UnexpectedEnd,
+    UnexpectedToken(String),
+    /// Valid SQL that no Deuterium query can represent, e.g. `UNION`.
+    Unsupported(String),
+    /// A `VALUES` row with another number of values than the columns or the first row.
+    ValueCount { expected: usize, found: usize },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseError::UnexpectedEnd => write!(f, "unexpected end of input"),
+            ParseError::UnexpectedToken(ref token) => write!(f, "unexpected token `{}`", token),
+            ParseError::Unsupported(ref feature) => write!(f, "{} is not supported", feature),
+            ParseError::ValueCount { expected, found } => {
+                write!(f, "VALUES row has {} values, expected {}", found, expected)
+            },
+        }
+    }
+}
+
+pub type ParseResult<T> = Result<T, ParseError>;
+
+/// Parsed query, typed the way the builder types it.
+#[derive(Clone, Debug)]
+pub enum Statement {
+    Select(SelectQuery<(), LimitMany, ()>),
+    Insert(InsertQuery<(), (), (), (), NoResult>),
+    InsertReturning(InsertQuery<(), (), (), (), LimitMany>),
+    Update(UpdateQuery<(), NoResult, ()>),
+    UpdateReturning(UpdateQuery<(), LimitMany, ()>),
+    Delete(DeleteQuery<(), NoResult, ()>),
+    DeleteReturning(DeleteQuery<(), LimitMany, ()>),
+}
+
+impl Statement {
+    /// Rebuilds the query with `rewriter`, see `SelectQuery::rewrite`.
+    pub fn rewrite<R: visit::Rewriter + ?Sized>(self, rewriter: &mut R) -> Statement {
+        match self {
+            Statement::Select(query) => Statement::Select(query.rewrite(rewriter)),
+            Statement::Insert(query) => Statement::Insert(query.rewrite(rewriter)),
+            Statement::InsertReturning(query) => Statement::InsertReturning(query.rewrite(rewriter)),
+            Statement::Update(query) => Statement::Update(query.rewrite(rewriter)),
+            Statement::UpdateReturning(query) => Statement::UpdateReturning(query.rewrite(rewriter)),
+            Statement::Delete(query) => Statement::Delete(query.rewrite(rewriter)),
+            Statement::DeleteReturning(query) => Statement::DeleteReturning(query.rewrite(rewriter)),
+        }
+    }
+}
+
+impl sql::ToSql for Statement {
+    fn to_sql(&self, ctx: &mut sql::SqlContext) -> String {
+        ctx.capture(|ctx| self.write_sql(ctx))
+    }
+
+    fn write_sql(&self, ctx: &mut sql::SqlContext) {
+        match *self {
+            Statement::Select(ref query) => query.write_sql(ctx),
+            Statement::Insert(ref query) => query.write_sql(ctx),
+            Statement::InsertReturning(ref query) => query.write_sql(ctx),
+            Statement::Update(ref query) => query.write_sql(ctx),
+            Statement::UpdateReturning(ref query) => query.write_sql(ctx),
+            Statement::Delete(ref query) => query.write_sql(ctx),
+            Statement::DeleteReturning(ref query) => query.write_sql(ctx),
+        }
+    }
+}
+
+impl sql::QueryToSql for Statement {}
+
+impl visit::Visit for Statement {
+    fn accept<V: visit::Visitor + ?Sized>(&self, visitor: &mut V) {
+        match *self {
+            Statement::Select(ref query) => query.accept(visitor),
+            Statement::Insert(ref query) => query.accept(visitor),
+            Statement::InsertReturning(ref query) => query.accept(visitor),
+            Statement::Update(ref query) => query.accept(visitor),
+            Statement::UpdateReturning(ref query) => query.accept(visitor),
+            Statement::Delete(ref query) => query.accept(visitor),
+            Statement::DeleteReturning(ref query) => query.accept(visitor),
+        }
+    }
+}
+
+/// Parses a single statement, optionally terminated by `;`.
+pub fn parse_sql(input: &str) -> ParseResult<Statement> {
+    let mut parser = Parser { tokens: tokenize(input)?, pos: 0 };
+    let statement = parser.statement()?;
+    parser.eat_op(";");
+    match parser.peek() {
+        Some(token) if token.is_keyword("union") || token.is_keyword("intersect") || token.is_keyword("except") => {
+            Err(ParseError::Unsupported(token.text().to_uppercase()))
+        },
+        Some(token) => Err(ParseError::UnexpectedToken(token.text())),
+        None => Ok(statement)
+    }
+}
+
+// Keywords ending an expression, never taken for an alias.
+const RESERVED: &[&str] = &[
+    "select", "from", "where", "group", "having", "order", "limit", "offset", "for", "as", "on",
+    "using", "join", "inner", "left", "right", "full", "outer", "cross", "natural", "and", "or",
+    "not", "is", "in", "like", "ilike", "between", "asc", "desc", "nulls", "returning", "set",
+    "values", "union", "intersect", "except", "when", "then", "else", "end"
+];
+
+const OPERATORS: &[&str] = &["->>", "<=", ">=", "<>", "!=", "::", "||", "->"];
+
+const COMPARISONS: &[&str] = &["=", "<>", "!=", "<", "<=", ">", ">="];
+
+fn is_reserved(word: &str) -> bool {
+    RESERVED.iter().any(|keyword| word.eq_ignore_ascii_case(keyword))
+}
+
+#[derive(Clone, Debug)]
+enum Token {
+    Word(String),
+    Literal(String),
+    Number(String),
+    /// Placeholder as written and what it stands for.
+    Param(String, SharedExpression),
+    Op(String),
+}
+
+impl Token {
+    fn is_keyword(&self, keyword: &str) -> bool {
+        match *self {
+            Token::Word(ref word) => word.eq_ignore_ascii_case(keyword),
+            _ => false
+        }
+    }
+
+    fn is_op(&self, op: &str) -> bool {
+        match *self {
+            Token::Op(ref o) => o == op,
+            _ => false
+        }
+    }
+
+    fn text(&self) -> String {
+        match *self {
+            Token::Word(ref word) => word.to_string(),
+            Token::Literal(ref lit) => format!("'{}'", lit.replace('\'', "''")),
+            Token::Number(ref number) => number.to_string(),
+            Token::Param(ref text, _) => text.to_string(),
+            Token::Op(ref op) => op.to_string(),
+        }
+    }
+}
+
+fn tokenize(input: &str) -> ParseResult<Vec<Token>> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = vec![];
+    let mut positional = 0;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let ch = chars[i];
+        let rest: String = chars[i..].iter().take(3).cloned().collect();
+        if ch.is_whitespace() {
+            i += 1;
+        } else if rest.starts_with("--") {
+            while i < chars.len() && chars[i] != '\n' { i += 1; }
+        } else if rest.starts_with("/*") {
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) { i += 1; }
+            if i >= chars.len() { return Err(ParseError::UnexpectedEnd) }
+            i += 2;
+        } else if ch == '"' || ch == '\'' || ch == '`' {
+            let mut value = String::new();
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    None => return Err(ParseError::UnexpectedEnd),
+                    Some(&c) if c == ch && chars.get(i + 1) == Some(&ch) => { value.push(c); i += 2; },
+                    Some(&c) if c == ch => { i += 1; break },
+                    Some(&c) => { value.push(c); i += 1; }
+                }
+            }
+            if ch != '\'' {
+                // Quoting differs between dialects and raw expressions keep it as written
+                return Err(ParseError::Unsupported(format!("quoted identifier {}{}{}", ch, value, ch)))
+            }
+            tokens.push(Token::Literal(value));
+        } else if ch.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || (chars[i] == '.' && chars.get(i + 1).map(|c| c.is_ascii_digit()).unwrap_or(false))) {
+                i += 1;
+            }
+            tokens.push(Token::Number(chars[start..i].iter().cloned().collect()));
+        } else if ch.is_alphabetic() || ch == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '$') { i += 1; }
+            tokens.push(Token::Word(chars[start..i].iter().cloned().collect()));
+        } else if ch == '$' && chars.get(i + 1).map(|c| c.is_ascii_digit()).unwrap_or(false) {
+            let start = i;
+            i += 1;
+            while i < chars.len() && chars[i].is_ascii_digit() { i += 1; }
+            let text: String = chars[start..i].iter().cloned().collect();
+            // Placeholders are numbered from 1
+            let idx = match text[1..].parse() {
+                Ok(0) | Err(_) => return Err(ParseError::UnexpectedToken(text)),
+                Ok(idx) => idx
+            };
+            tokens.push(Token::Param(text, Placeholder::new(idx).upcast_expression()));
+        } else if ch == '?' {
+            positional += 1;
+            tokens.push(Token::Param("?".to_string(), Placeholder::new(positional).upcast_expression()));
+            i += 1;
+        } else if ch == ':' && chars.get(i + 1).map(|c| c.is_alphabetic() || *c == '_').unwrap_or(false) {
+            let start = i;
+            i += 1;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') { i += 1; }
+            let text: String = chars[start..i].iter().cloned().collect();
+            let placeholder = Placeholder::named(&text[1..]).upcast_expression();
+            tokens.push(Token::Param(text, placeholder));
+        } else {
+            let op = OPERATORS.iter().find(|op| rest.starts_with(*op)).map(|op| op.to_string()).unwrap_or(ch.to_string());
+            i += op.chars().count();
+            tokens.push(Token::Op(op));
+        }
+    }
+
+    Ok(tokens)
+}
+
+// Writes tokens back as SQL, spaced the way people usually write it. Placeholders only stand
+// alone, inside raw SQL they would keep the syntax of the source dialect.
+fn text(tokens: &[Token]) -> ParseResult<String> {
+    let mut sql = String::new();
+    for (i, token) in tokens.iter().enumerate() {
+        if let Token::Param(ref text, _) = *token {
+            return Err(ParseError::Unsupported(format!("placeholder {} inside an expression", text)))
+        }
+        if i > 0 && spaced(&tokens[..i], token) { sql.push(' ') }
+        sql.push_str(&token.text());
+    }
+    Ok(sql)
+}
+
+fn spaced(before: &[Token], token: &Token) -> bool {
+    let prev = &before[before.len() - 1];
+    if [")", ",", ".", "::"].iter().any(|op| token.is_op(op)) { return false }
+    if ["(", ".", "::"].iter().any(|op| prev.is_op(op)) { return false }
+
+    if token.is_op("(") {
+        // Function calls
+        return match *prev {
+            Token::Word(ref word) => is_reserved(word),
+            _ => true
+        }
+    }
+
+    if prev.is_op("-") || prev.is_op("+") {
+        // Unary signs stick to the operand
+        let unary = match before.len() {
+            1 => true,
+            n => match before[n - 2] {
+                Token::Op(ref op) => op != ")",
+                Token::Word(ref word) => is_reserved(word),
+                _ => false
+            }
+        };
+        return !unary
+    }
+
+    true
+}
+
+fn raw(tokens: &[Token]) -> ParseResult<RawExpression> {
+    text(tokens).map(|sql| RawExpression::new(&sql))
+}
+
+// Whether the expression ends with an alias given without `AS`, as in `count(*) total`.
+fn ends_with_alias(tokens: &[Token]) -> bool {
+    let n = tokens.len();
+    let alias = matches!(tokens[n - 1], Token::Word(_));
+    let operand = match tokens[n - 2] {
+        Token::Op(ref op) => op == ")",
+        _ => true
+    };
+    alias && operand
+}
+
+fn expressions(exprs: &[RawExpression]) -> Vec<&dyn UntypedExpression> {
+    exprs.iter().map(|expr| expr as &dyn UntypedExpression).collect()
+}
+
+fn returning_list(select: &Select) -> Vec<&dyn UntypedExpression> {
+    match *select {
+        Select::Only(ref exprs) => exprs.iter().map(|expr| &***expr).collect(),
+        Select::All => vec![]
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum JoinKind {
+    Inner,
+    Left,
+    LeftOuter,
+    Right,
+    RightOuter,
+    Full,
+    FullOuter,
+    Cross,
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        self.peek().map(|t| t.is_keyword(keyword)).unwrap_or(false)
+    }
+
+    fn peek_op(&self, op: &str) -> bool {
+        self.peek().map(|t| t.is_op(op)).unwrap_or(false)
+    }
+
+    fn next(&mut self) -> ParseResult<Token> {
+        let token = self.peek().cloned().ok_or(ParseError::UnexpectedEnd)?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn unexpected(&self) -> ParseError {
+        match self.peek() {
+            Some(token) => ParseError::UnexpectedToken(token.text()),
+            None => ParseError::UnexpectedEnd
+        }
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let matched = self.peek_keyword(keyword);
+        if matched { self.pos += 1; }
+        matched
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> ParseResult<()> {
+        if self.eat_keyword(keyword) { Ok(()) } else { Err(self.unexpected()) }
+    }
+
+    fn eat_op(&mut self, op: &str) -> bool {
+        let matched = self.peek_op(op);
+        if matched { self.pos += 1; }
+        matched
+    }
+
+    fn expect_op(&mut self, op: &str) -> ParseResult<()> {
+        if self.eat_op(op) { Ok(()) } else { Err(self.unexpected()) }
+    }
+
+    fn ident(&mut self) -> ParseResult<String> {
+        match self.next()? {
+            Token::Word(ref word) if !is_reserved(word) => Ok(word.to_string()),
+            token => Err(ParseError::UnexpectedToken(token.text()))
+        }
+    }
+
+    /// Possibly qualified name, e.g. `public.jedi`.
+    fn name(&mut self) -> ParseResult<String> {
+        let mut name = self.ident()?;
+        while self.eat_op(".") {
+            name = format!("{}.{}", name, self.ident()?);
+        }
+        Ok(name)
+    }
+
+    fn alias(&mut self) -> ParseResult<Option<String>> {
+        if self.eat_keyword("as") {
+            return self.ident().map(Some)
+        }
+        match self.peek() {
+            Some(Token::Word(word)) if !is_reserved(word) => (),
+            _ => return Ok(None)
+        }
+        self.ident().map(Some)
+    }
+
+    fn statement(&mut self) -> ParseResult<Statement> {
+        if self.peek_keyword("select") {
+            Ok(Statement::Select(self.select()?))
+        } else if self.eat_keyword("insert") {
+            self.insert()
+        } else if self.eat_keyword("update") {
+            self.update()
+        } else if self.eat_keyword("delete") {
+            self.delete()
+        } else if self.peek_keyword("with") {
+            Err(ParseError::Unsupported("WITH".to_string()))
+        } else {
+            Err(self.unexpected())
+        }
+    }
+
+    /// Tokens of an expression, up to a comparison, a keyword or the end of a list.
+    fn expr(&mut self) -> ParseResult<Vec<Token>> {
+        let start = self.pos;
+        let mut depth = 0;
+        let mut cases = 0;
+        while let Some(token) = self.peek().cloned() {
+            if depth == 0 && cases == 0 {
+                let ends = match token {
+                    Token::Op(ref op) => [",", ")", ";"].contains(&&op[..]) || COMPARISONS.contains(&&op[..]),
+                    Token::Word(ref word) => is_reserved(word),
+                    _ => false
+                };
+                if ends { break }
+            }
+
+            if token.is_op("(") { depth += 1 }
+            if token.is_op(")") { depth -= 1 }
+            if token.is_keyword("case") { cases += 1 }
+            if token.is_keyword("end") { cases -= 1 }
+            self.pos += 1;
+        }
+
+        if self.pos == start {
+            return Err(self.unexpected())
+        }
+        Ok(self.tokens[start..self.pos].to_vec())
+    }
+
+    fn raw_expr(&mut self) -> ParseResult<RawExpression> {
+        self.expr().and_then(|tokens| raw(&tokens))
+    }
+
+    /// Expression used as a value, a `Placeholder` if it is a single one.
+    fn operand(&mut self) -> ParseResult<SharedExpression> {
+        let tokens = self.expr()?;
+        match &tokens[..] {
+            &[Token::Param(_, ref placeholder)] => Ok(placeholder.clone()),
+            tokens => raw(tokens).map(|expr| expr.upcast_expression())
+        }
+    }
+
+    fn paren_exprs(&mut self) -> ParseResult<Vec<RawExpression>> {
+        self.expect_op("(")?;
+        let exprs = self.expr_list()?;
+        self.expect_op(")")?;
+        Ok(exprs)
+    }
+
+    fn expr_list(&mut self) -> ParseResult<Vec<RawExpression>> {
+        let mut exprs = vec![self.raw_expr()?];
+        while self.eat_op(",") {
+            exprs.push(self.raw_expr()?);
+        }
+        Ok(exprs)
+    }
+
+    fn select_list(&mut self) -> ParseResult<Select> {
+        if self.peek_op("*") && self.tokens.get(self.pos + 1).map(|t| !t.is_op(",")).unwrap_or(true) {
+            self.pos += 1;
+            return Ok(Select::All)
+        }
+
+        let mut exprs = vec![];
+        loop {
+            let mut tokens = self.expr()?;
+            let mut alias = self.alias()?;
+            if alias.is_none() && tokens.len() > 1 && ends_with_alias(&tokens) {
+                alias = tokens.pop().map(|token| token.text());
+            }
+            let expr = match alias {
+                Some(alias) => RawExpression::new(&format!("{} AS {}", text(&tokens)?, alias)),
+                None => raw(&tokens)?
+            };
+            exprs.push(expr.upcast_expression());
+            if !self.eat_op(",") { break }
+        }
+        Ok(Select::Only(exprs))
+    }
+
+    fn returning(&mut self) -> ParseResult<Option<Select>> {
+        if self.eat_keyword("returning") { self.select_list().map(Some) } else { Ok(None) }
+    }
+
+    fn where_(&mut self) -> ParseResult<Option<SharedPredicate>> {
+        if self.eat_keyword("where") { self.or_predicate().map(Some) } else { Ok(None) }
+    }
+
+    fn number(&mut self) -> ParseResult<usize> {
+        match self.next()? {
+            Token::Number(ref number) => number.parse().map_err(|_| ParseError::UnexpectedToken(number.to_string())),
+            Token::Param(ref text, _) => Err(ParseError::Unsupported(format!("placeholder {} as a row count", text))),
+            token => Err(ParseError::UnexpectedToken(token.text()))
+        }
+    }
+
+    fn select<T: Clone>(&mut self) -> ParseResult<SelectQuery<T, LimitMany, ()>> {
+        self.expect_keyword("select")?;
+        let distinct = if self.eat_keyword("distinct") {
+            if self.eat_keyword("on") { Some(self.paren_exprs()?) } else { Some(vec![]) }
+        } else {
+            self.eat_keyword("all");
+            None
+        };
+
+        let select = self.select_list()?;
+        if !self.eat_keyword("from") {
+            return Err(match self.peek() {
+                None => ParseError::Unsupported("SELECT without FROM".to_string()),
+                Some(token) => ParseError::UnexpectedToken(token.text())
+            })
+        }
+
+        let mut query = SelectQuery::new(select, self.table_or_subquery()?);
+        match distinct {
+            Some(ref on) if on.is_empty() => query = query.distinct(),
+            Some(ref on) => query = query.distinct_on(&expressions(on)),
+            None => ()
+        }
+
+        // `FROM a, b` is the same as a cross join
+        while self.eat_op(",") {
+            query = query.cross_join(&**self.table_or_subquery()?);
+        }
+        query = self.joins(query)?;
+
+        if let Some(predicate) = self.where_()? {
+            query = query.where_(predicate);
+        }
+
+        if self.eat_keyword("group") {
+            self.expect_keyword("by")?;
+            query = query.group_by(&expressions(&self.expr_list()?));
+        }
+
+        if self.eat_keyword("having") {
+            query = query.having(self.or_predicate()?);
+        }
+
+        if self.eat_keyword("order") {
+            self.expect_keyword("by")?;
+            loop {
+                let expr = self.raw_expr()?;
+                query = if self.eat_keyword("desc") {
+                    query.reverse_append(&expr)
+                } else {
+                    self.eat_keyword("asc");
+                    query.order_append(&expr)
+                };
+                if self.peek_keyword("nulls") {
+                    return Err(ParseError::Unsupported("NULLS FIRST/LAST".to_string()))
+                }
+                if !self.eat_op(",") { break }
+            }
+        }
+
+        // LIMIT and OFFSET come in either order
+        let (mut limit, mut offset) = (false, false);
+        loop {
+            if !limit && self.eat_keyword("limit") {
+                limit = true;
+                if !self.eat_keyword("all") {
+                    query = query.limit_many(self.number()?);
+                }
+            } else if !offset && self.eat_keyword("offset") {
+                offset = true;
+                query = query.offset(self.number()?);
+                let _ = self.eat_keyword("rows") || self.eat_keyword("row");
+            } else {
+                break
+            }
+        }
+
+        if self.eat_keyword("for") {
+            let update = self.eat_keyword("update");
+            if !update { self.expect_keyword("share")?; }
+            let nowait = self.eat_keyword("nowait");
+            query = match (update, nowait) {
+                (true, false) => query.for_update(),
+                (true, true) => query.for_update_nowait(),
+                (false, false) => query.for_share(),
+                (false, true) => query.for_share_nowait(),
+            };
+        }
+
+        Ok(query)
+    }
+
+    fn table_or_subquery(&mut self) -> ParseResult<SharedFrom> {
+        if self.eat_op("(") {
+            let select = self.select::<()>()?;
+            self.expect_op(")")?;
+            return match self.alias()? {
+                Some(alias) => Ok(select.alias(&alias).upcast_from()),
+                None => Err(ParseError::Unsupported("subquery without an alias".to_string()))
+            }
+        }
+        self.table().map(|table| table.upcast_from())
+    }
+
+    fn table(&mut self) -> ParseResult<TableDef> {
+        let name = self.name()?;
+        Ok(match self.alias()? {
+            Some(alias) => TableDef::new_with_alias(&name, &alias),
+            None => TableDef::new(&name)
+        })
+    }
+
+    fn joins<T: Clone>(&mut self, mut query: SelectQuery<T, LimitMany, ()>) -> ParseResult<SelectQuery<T, LimitMany, ()>> {
+        loop {
+            let natural = self.eat_keyword("natural");
+            let kind = if self.eat_keyword("inner") {
+                JoinKind::Inner
+            } else if self.eat_keyword("left") {
+                if self.eat_keyword("outer") { JoinKind::LeftOuter } else { JoinKind::Left }
+            } else if self.eat_keyword("right") {
+                if self.eat_keyword("outer") { JoinKind::RightOuter } else { JoinKind::Right }
+            } else if self.eat_keyword("full") {
+                if self.eat_keyword("outer") { JoinKind::FullOuter } else { JoinKind::Full }
+            } else if !natural && self.eat_keyword("cross") {
+                JoinKind::Cross
+            } else if natural || self.peek_keyword("join") {
+                JoinKind::Inner
+            } else {
+                return Ok(query)
+            };
+            self.expect_keyword("join")?;
+
+            let from = self.table_or_subquery()?;
+            let from: &dyn From = &**from;
+            query = match (natural, kind) {
+                (true, JoinKind::Inner) => query.natural_join(from),
+                (true, JoinKind::Left) => query.natural_left_join(from),
+                (true, JoinKind::Right) => query.natural_right_join(from),
+                (true, JoinKind::Full) => query.natural_full_join(from),
+                (true, _) => return Err(ParseError::Unsupported("NATURAL OUTER JOIN".to_string())),
+                (false, JoinKind::Cross) => query.cross_join(from),
+                (false, kind) => {
+                    if self.peek_keyword("using") {
+                        return Err(ParseError::Unsupported("JOIN ... USING".to_string()))
+                    }
+                    self.expect_keyword("on")?;
+                    let on = self.or_predicate()?;
+                    match kind {
+                        JoinKind::Left => query.left_join(from, on),
+                        JoinKind::LeftOuter => query.left_outer_join(from, on),
+                        JoinKind::Right => query.right_join(from, on),
+                        JoinKind::RightOuter => query.right_outer_join(from, on),
+                        JoinKind::Full => query.full_join(from, on),
+                        JoinKind::FullOuter => query.full_outer_join(from, on),
+                        _ => query.inner_join(from, on),
+                    }
+                }
+            };
+        }
+    }
+
+    fn or_predicate(&mut self) -> ParseResult<SharedPredicate> {
+        let mut predicate = self.and_predicate()?;
+        while self.eat_keyword("or") {
+            predicate = predicate.or(self.and_predicate()?);
+        }
+        Ok(predicate)
+    }
+
+    fn and_predicate(&mut self) -> ParseResult<SharedPredicate> {
+        let mut predicate = self.not_predicate()?;
+        while self.eat_keyword("and") {
+            predicate = predicate.and(self.not_predicate()?);
+        }
+        Ok(predicate)
+    }
+
+    fn not_predicate(&mut self) -> ParseResult<SharedPredicate> {
+        if self.eat_keyword("not") {
+            return self.not_predicate().map(|predicate| predicate.exclude())
+        }
+
+        if self.peek_op("(") {
+            // Either a nested condition or an expression like `(a + b) > c`
+            let start = self.pos;
+            self.pos += 1;
+            if let Ok(predicate) = self.or_predicate() {
+                if self.eat_op(")") && !self.continues_comparison() {
+                    return Ok(predicate)
+                }
+            }
+            self.pos = start;
+        }
+
+        if self.eat_keyword("exists") {
+            let tokens = self.expr()?;
+            return Ok(RawPredicate::new(&format!("EXISTS {}", text(&tokens)?)).upcast())
+        }
+
+        self.comparison()
+    }
+
+    fn continues_comparison(&self) -> bool {
+        match self.peek() {
+            Some(Token::Op(op)) => ![")", ",", ";"].contains(&&op[..]),
+            Some(token) => ["is", "in", "like", "ilike", "between", "not"].iter().any(|kw| token.is_keyword(kw)),
+            None => false
+        }
+    }
+
+    fn comparison(&mut self) -> ParseResult<SharedPredicate> {
+        let left = self.expr()?;
+        let field = raw(&left)?;
+        let negated = self.eat_keyword("not");
+
+        let comparison = match self.peek() {
+            Some(Token::Op(op)) if COMPARISONS.contains(&&op[..]) => Some(op.to_string()),
+            _ => None
+        };
+
+        let predicate = if let Some(op) = comparison {
+            if negated { return Err(ParseError::UnexpectedToken(op)) }
+            self.pos += 1;
+            let value = CastExpression::<RawExpression>::new(self.operand()?);
+            match &op[..] {
+                "=" => field.is(value),
+                "<>" | "!=" => field.is(value).exclude(),
+                "<" => field.lt(value),
+                "<=" => field.lte(value),
+                ">" => field.gt(value),
+                _ => field.gte(value),
+            }
+        } else if self.eat_keyword("in") {
+            self.expect_op("(")?;
+            let predicate = if self.peek_keyword("select") {
+                field.in_(self.select::<(RawExpression,)>()?)
+            } else {
+                let mut values = vec![CastExpression::<RawExpression>::new(self.operand()?)];
+                while self.eat_op(",") {
+                    values.push(CastExpression::new(self.operand()?));
+                }
+                field.in_(values)
+            };
+            self.expect_op(")")?;
+            predicate
+        } else if self.eat_keyword("like") {
+            field.like(CastExpression::<String>::new(self.operand()?))
+        } else if self.eat_keyword("ilike") {
+            field.ilike(CastExpression::<String>::new(self.operand()?))
+        } else if self.eat_keyword("between") {
+            let from = CastExpression::<RawExpression>::new(self.operand()?);
+            self.expect_keyword("and")?;
+            let to = CastExpression::<RawExpression>::new(self.operand()?);
+            field.in_range(from, to)
+        } else if !negated && self.eat_keyword("is") {
+            let not_null = self.eat_keyword("not");
+            if !self.eat_keyword("null") {
+                return Err(ParseError::Unsupported(format!("IS {}", self.unexpected_text())))
+            }
+            if not_null { field.not_null() } else { field.is_null() }
+        } else if negated {
+            return Err(self.unexpected())
+        } else {
+            // A boolean column or function call
+            RawPredicate::new(&field.content).upcast()
+        };
+
+        Ok(if negated { predicate.exclude() } else { predicate })
+    }
+
+    fn unexpected_text(&self) -> String {
+        self.peek().map(|token| token.text().to_uppercase()).unwrap_or_default()
+    }
+
+    fn insert(&mut self) -> ParseResult<Statement> {
+        self.expect_keyword("into")?;
+        let table = TableDef::new(&self.name()?);
+
+        let mut width = None;
+        let mut query: InsertQuery<(), (), (), (), NoResult> = if self.peek_op("(") && !self.tokens.get(self.pos + 1).map(|t| t.is_keyword("select")).unwrap_or(false) {
+            self.pos += 1;
+            let mut cols = vec![NamedField::<RawExpression>::new(&self.ident()?, table.get_table_name()).upcast_field()];
+            while self.eat_op(",") {
+                cols.push(NamedField::<RawExpression>::new(&self.ident()?, table.get_table_name()).upcast_field());
+            }
+            self.expect_op(")")?;
+            width = Some(cols.len());
+            InsertQuery::new_with_cols(&table, cols)
+        } else {
+            InsertQuery::new(&table)
+        };
+
+        if self.eat_keyword("values") {
+            loop {
+                self.expect_op("(")?;
+                let mut row = vec![];
+                loop {
+                    row.push(if self.eat_keyword("default") {
+                        InsertValue::Default
+                    } else {
+                        InsertValue::new(CastExpression::<RawExpression>::new(self.operand()?))
+                    });
+                    if !self.eat_op(",") { break }
+                }
+                self.expect_op(")")?;
+                let expected = *width.get_or_insert(row.len());
+                if row.len() != expected {
+                    return Err(ParseError::ValueCount { expected, found: row.len() })
+                }
+                query.push_untyped(&row);
+                if !self.eat_op(",") { break }
+            }
+        } else if self.eat_keyword("default") {
+            self.expect_keyword("values")?;
+        } else if self.peek_keyword("select") {
+            query = query.from_select(self.select()?);
+        } else if self.eat_op("(") {
+            query = query.from_select(self.select()?);
+            self.expect_op(")")?;
+        } else {
+            return Err(self.unexpected())
+        }
+
+        if self.peek_keyword("on") {
+            return Err(ParseError::Unsupported("ON CONFLICT".to_string()))
+        }
+
+        Ok(match self.returning()? {
+            Some(Select::All) => Statement::InsertReturning(query.returning_all()),
+            Some(select) => Statement::InsertReturning(query.returning(&returning_list(&select))),
+            None => Statement::Insert(query)
+        })
+    }
+
+    fn update(&mut self) -> ParseResult<Statement> {
+        let only = self.eat_keyword("only");
+        let table = self.table()?;
+        self.expect_keyword("set")?;
+
+        let mut query: UpdateQuery<(), NoResult, ()> = UpdateQuery::new(&table);
+        if only { query = query.only() }
+        loop {
+            let column = RawExpression::new(&self.name()?);
+            self.expect_op("=")?;
+            query = if self.eat_keyword("default") {
+                query.field(column.set_default())
+            } else {
+                query.field(column.set(&CastExpression::<RawExpression>::new(self.operand()?)))
+            };
+            if !self.eat_op(",") { break }
+        }
+
+        if self.eat_keyword("from") {
+            loop {
+                query = query.from(&**self.table_or_subquery()?);
+                if !self.eat_op(",") { break }
+            }
+        }
+
+        query = match self.where_()? {
+            Some(predicate) => query.where_(predicate),
+            None => query.all()
+        };
+
+        Ok(match self.returning()? {
+            Some(Select::All) => Statement::UpdateReturning(query.returning_all()),
+            Some(select) => Statement::UpdateReturning(query.returning(&returning_list(&select))),
+            None => Statement::Update(query)
+        })
+    }
+
+    fn delete(&mut self) -> ParseResult<Statement> {
+        self.expect_keyword("from")?;
+        let only = self.eat_keyword("only");
+        let table = self.table()?;
+
+        let mut query: DeleteQuery<(), NoResult, ()> = DeleteQuery::new(&table);
+        if only { query = query.only() }
+
+        if self.eat_keyword("using") {
+            loop {
+                query = query.using(&**self.table_or_subquery()?);
+                if !self.eat_op(",") { break }
+            }
+        }
+
+        query = match self.where_()? {
+            Some(predicate) => query.where_(predicate),
+            None => query.all()
+        };
+
+        Ok(match self.returning()? {
+            Some(Select::All) => Statement::DeleteReturning(query.returning_all()),
+            Some(select) => Statement::DeleteReturning(query.returning(&returning_list(&select))),
+            None => Statement::Delete(query)
+        })
+    }
+}
@@ -1,32 +1,62 @@
-use std::rc;
+use std::sync;
 use chrono;
 use serde_json;
 
 use super::expression;
 use super::sql;
+use super::visit;
 
+/// `$n`, numbered by hand.
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Placeholder {
-    idx: u8
+    idx: usize
 }
 
 impl Placeholder {
-    pub fn new(idx: u8) -> Placeholder {
-        Placeholder { idx: idx }
+    pub fn new(idx: usize) -> Placeholder {
+        Placeholder { idx }
     }
 
-    pub fn get_idx(&self) -> u8 {
+    /// Placeholder bound by name, see `NamedPlaceholder`.
+    pub fn named(name: &str) -> NamedPlaceholder {
+        NamedPlaceholder::new(name)
+    }
+
+    pub fn get_idx(&self) -> usize {
         self.idx
     }
 }
 
+/// Gets its position at render time, bound later by name with `CompiledQuery::bind`.
+/// Every occurrence of the same name shares the position.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NamedPlaceholder {
+    name: String
+}
+
+impl NamedPlaceholder {
+    pub fn new(name: &str) -> NamedPlaceholder {
+        NamedPlaceholder { name: name.to_string() }
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+}
+
 impl expression::UntypedExpression for Placeholder {
-    fn expression_as_sql(&self) -> &sql::ToSql {
+    fn expression_as_sql(&self) -> &dyn sql::ToSql {
         self
     }
 
     fn upcast_expression(&self) -> expression::SharedExpression {
-        rc::Rc::new(Box::new(self.clone()) as expression::BoxedExpression)
+        sync::Arc::new(Box::new(*self) as expression::BoxedExpression)
+    }
+
+    fn expression_node(&self) -> visit::Node<'_> {
+        visit::Node::Placeholder(self)
     }
 }
 
@@ -37,3 +67,25 @@ impl expression::ToExpression<Vec<u8>> for Placeholder {}
 impl expression::ToExpression<chrono::NaiveDateTime> for Placeholder {}
 impl expression::ToExpression<serde_json::Value> for Placeholder {}
 impl expression::ToExpression<expression::RawExpression> for Placeholder {}
+
+impl expression::UntypedExpression for NamedPlaceholder {
+    fn expression_as_sql(&self) -> &dyn sql::ToSql {
+        self
+    }
+
+    fn upcast_expression(&self) -> expression::SharedExpression {
+        sync::Arc::new(Box::new(self.clone()) as expression::BoxedExpression)
+    }
+
+    fn expression_node(&self) -> visit::Node<'_> {
+        visit::Node::NamedPlaceholder(self)
+    }
+}
+
+impl expression::ToExpression<bool> for NamedPlaceholder {}
+impl expression::ToExpression<f64> for NamedPlaceholder {}
+impl expression::ToExpression<String> for NamedPlaceholder {}
+impl expression::ToExpression<Vec<u8>> for NamedPlaceholder {}
+impl expression::ToExpression<chrono::NaiveDateTime> for NamedPlaceholder {}
+impl expression::ToExpression<serde_json::Value> for NamedPlaceholder {}
+impl expression::ToExpression<expression::RawExpression> for NamedPlaceholder {}
@@ -0,0 +1,15 @@
+use super::super::visit;
+
+/// `AND` of any number of predicates, true when the list is empty.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AllPredicate {
+    #[cfg_attr(feature = "serde", serde(with = "::serialize::shared"))]
+    pub predicates: Vec<super::SharedPredicate>
+}
+
+impl super::Predicate for AllPredicate {
+    fn predicate_node(&self) -> visit::PredicateNode<'_> {
+        visit::PredicateNode::All(&self.predicates)
+    }
+}
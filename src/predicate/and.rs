@@ -1,8 +1,12 @@
 use super::ToSharedPredicate;
+use super::super::visit;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct AndPredicate {
+    #[cfg_attr(feature = "serde", serde(with = "::serialize::shared"))]
     pub left: super::SharedPredicate,
+    #[cfg_attr(feature = "serde", serde(with = "::serialize::shared"))]
     pub right: super::SharedPredicate
 }
 
@@ -16,4 +20,8 @@ impl ToAndPredicate for super::SharedPredicate {
     }
 }
 
-impl super::Predicate for AndPredicate { }
+impl super::Predicate for AndPredicate {
+    fn predicate_node(&self) -> visit::PredicateNode<'_> {
+        visit::PredicateNode::And(&self.left, &self.right)
+    }
+}
@@ -0,0 +1,15 @@
+use super::super::visit;
+
+/// `OR` of any number of predicates, false when the list is empty.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AnyPredicate {
+    #[cfg_attr(feature = "serde", serde(with = "::serialize::shared"))]
+    pub predicates: Vec<super::SharedPredicate>
+}
+
+impl super::Predicate for AnyPredicate {
+    fn predicate_node(&self) -> visit::PredicateNode<'_> {
+        visit::PredicateNode::Any(&self.predicates)
+    }
+}
@@ -1,7 +1,10 @@
 use super::ToSharedPredicate;
+use super::super::visit;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ExcludePredicate {
+    #[cfg_attr(feature = "serde", serde(with = "::serialize::shared"))]
     pub predicate: super::SharedPredicate
 }
 
@@ -9,7 +12,11 @@ pub trait ToExcludePredicate {
     fn exclude(&self) -> super::SharedPredicate;
 }
 
-impl super::Predicate for ExcludePredicate {}
+impl super::Predicate for ExcludePredicate {
+    fn predicate_node(&self) -> visit::PredicateNode<'_> {
+        visit::PredicateNode::Exclude(&self.predicate)
+    }
+}
 
 impl ToExcludePredicate for super::SharedPredicate {
     fn exclude(&self) -> super::SharedPredicate {
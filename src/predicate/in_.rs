@@ -1,16 +1,22 @@
 use super::super::expression;
 use super::super::sql;
 use super::super::field;
+use super::super::visit;
 
 use super::ToSharedPredicate;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct InPredicate<F, T> {
     field: F,
     values: T
 }
 
 impl<F, T> InPredicate<F, T> {
+    pub fn new(field: F, values: T) -> InPredicate<F, T> {
+        InPredicate { field, values }
+    }
+
     pub fn get_field(&self) -> &F { &self.field }
     pub fn get_values(&self) -> &T { &self.values }
 }
@@ -21,8 +27,22 @@ pub trait ToInPredicate<T> {
 }
 
 impl<F, T> super::Predicate for InPredicate<F, T>
-    where F: sql::ToPredicateValue,
-          T: sql::ToPredicateValue { }
+    where F: sql::ToPredicateValue + Clone + 'static,
+          T: sql::ToPredicateValue + Clone + 'static {
+    fn predicate_node(&self) -> visit::PredicateNode<'_> {
+        visit::PredicateNode::In { field: self.field.value_node(), values: self.values.value_node() }
+    }
+
+    fn rewrite_operands(&self, rewriter: &mut dyn visit::Rewriter) -> Option<super::SharedPredicate> {
+        match (self.field.rewrite_value(rewriter), self.values.rewrite_value(rewriter)) {
+            (None, None) => None,
+            (field, values) => Some(InPredicate {
+                field: field.unwrap_or_else(|| self.field.clone()),
+                values: values.unwrap_or_else(|| self.values.clone())
+            }.upcast())
+        }
+    }
+}
 
 impl<T> ToInPredicate<T> for field::NamedField<T> where T: sql::ToPredicateValue + Clone + 'static {
     fn in_<B: expression::ToListExpression<T> + sql::ToPredicateValue + Clone + 'static>(&self, val: B) -> super::SharedPredicate {
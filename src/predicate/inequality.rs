@@ -4,8 +4,10 @@ use super::ToSharedPredicate;
 use super::super::expression;
 use super::super::field;
 use super::super::sql;
+use super::super::visit;
 
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Inequality {
     LessThan,
     LessThanEqual,
@@ -14,6 +16,7 @@ pub enum Inequality {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct InequalityPredicate<F, T> {
     field: F,
     value: T,
@@ -21,6 +24,10 @@ pub struct InequalityPredicate<F, T> {
 }
 
 impl<F, T> InequalityPredicate<F, T> {
+    pub fn new(field: F, value: T, inequality: Inequality) -> InequalityPredicate<F, T> {
+        InequalityPredicate { field, value, inequality }
+    }
+
     pub fn get_field(&self) -> &F { &self.field }
     pub fn get_value(&self) -> &T { &self.value }
     pub fn get_inequality(&self) -> &Inequality { &self.inequality }
@@ -41,8 +48,27 @@ pub trait ToInequalityPredicate<T> {
 }
 
 impl<F, T> super::Predicate for InequalityPredicate<F, T>
-    where F: sql::ToPredicateValue,
-          T: sql::ToPredicateValue { }
+    where F: sql::ToPredicateValue + Clone + 'static,
+          T: sql::ToPredicateValue + Clone + 'static {
+    fn predicate_node(&self) -> visit::PredicateNode<'_> {
+        visit::PredicateNode::Inequality {
+            field: self.field.value_node(),
+            value: self.value.value_node(),
+            inequality: self.inequality
+        }
+    }
+
+    fn rewrite_operands(&self, rewriter: &mut dyn visit::Rewriter) -> Option<super::SharedPredicate> {
+        match (self.field.rewrite_value(rewriter), self.value.rewrite_value(rewriter)) {
+            (None, None) => None,
+            (field, value) => Some(InequalityPredicate {
+                field: field.unwrap_or_else(|| self.field.clone()),
+                value: value.unwrap_or_else(|| self.value.clone()),
+                inequality: self.inequality
+            }.upcast())
+        }
+    }
+}
 
 macro_rules! impl_for {
     ($field:ty, $expr:ty) => (
@@ -1,16 +1,22 @@
 use super::super::sql;
 use super::super::expression;
 use super::super::field;
+use super::super::visit;
 
 use super::ToSharedPredicate;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct IsPredicate<F, T> {
     field: F,
     value: T,
 }
 
 impl<F, T> IsPredicate<F, T> {
+    pub fn new(field: F, value: T) -> IsPredicate<F, T> {
+        IsPredicate { field, value }
+    }
+
     pub fn get_field(&self) -> &F { &self.field }
     pub fn get_value(&self) -> &T { &self.value }
 }
@@ -20,8 +26,22 @@ pub trait ToIsPredicate<T> {
 }
 
 impl<F, T> super::Predicate for IsPredicate<F, T>
-    where F: sql::ToPredicateValue,
-          T: sql::ToPredicateValue { }
+    where F: sql::ToPredicateValue + Clone + 'static,
+          T: sql::ToPredicateValue + Clone + 'static {
+    fn predicate_node(&self) -> visit::PredicateNode<'_> {
+        visit::PredicateNode::Is { field: self.field.value_node(), value: self.value.value_node() }
+    }
+
+    fn rewrite_operands(&self, rewriter: &mut dyn visit::Rewriter) -> Option<super::SharedPredicate> {
+        match (self.field.rewrite_value(rewriter), self.value.rewrite_value(rewriter)) {
+            (None, None) => None,
+            (field, value) => Some(IsPredicate {
+                field: field.unwrap_or_else(|| self.field.clone()),
+                value: value.unwrap_or_else(|| self.value.clone())
+            }.upcast())
+        }
+    }
+}
 
 impl<T> ToIsPredicate<T> for field::NamedField<T> where T: sql::ToPredicateValue + Clone + 'static {
     fn is<B: expression::ToExpression<T> + sql::ToPredicateValue + Clone + 'static>(&self, val: B) -> super::SharedPredicate {
@@ -1,16 +1,22 @@
 use super::super::sql;
 use super::super::expression;
 use super::super::field;
+use super::super::visit;
 
 use super::ToSharedPredicate;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct IsNullPredicate<F> {
     field: F,
     is_null: bool
 }
 
 impl<F> IsNullPredicate<F> {
+    pub fn new(field: F, is_null: bool) -> IsNullPredicate<F> {
+        IsNullPredicate { field, is_null }
+    }
+
     pub fn get_field(&self) -> &F { &self.field }
     pub fn is_null(&self) -> bool { self.is_null }
 }
@@ -20,7 +26,15 @@ pub trait ToIsNullPredicate {
     fn not_null(&self) -> super::SharedPredicate;
 }
 
-impl<F> super::Predicate for IsNullPredicate<F> where F: sql::ToPredicateValue {}
+impl<F> super::Predicate for IsNullPredicate<F> where F: sql::ToPredicateValue + Clone + 'static {
+    fn predicate_node(&self) -> visit::PredicateNode<'_> {
+        visit::PredicateNode::IsNull { field: self.field.value_node(), is_null: self.is_null }
+    }
+
+    fn rewrite_operands(&self, rewriter: &mut dyn visit::Rewriter) -> Option<super::SharedPredicate> {
+        self.field.rewrite_value(rewriter).map(|field| IsNullPredicate { field, is_null: self.is_null }.upcast())
+    }
+}
 
 impl<T> ToIsNullPredicate for field::NamedField<Option<T>> where T: sql::ToPredicateValue + Clone + 'static {
     fn is_null(&self) -> super::SharedPredicate {
@@ -1,10 +1,12 @@
 use super::super::expression;
 use super::super::field;
 use super::super::sql;
+use super::super::visit;
 
 use super::ToSharedPredicate;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct LikePredicate<F, T> {
     field: F,
     value: T,
@@ -12,6 +14,10 @@ pub struct LikePredicate<F, T> {
 }
 
 impl<F, T> LikePredicate<F, T> {
+    pub fn new(field: F, value: T, is_case_sensitive: bool) -> LikePredicate<F, T> {
+        LikePredicate { field, value, is_case_sensitive }
+    }
+
     pub fn get_field(&self) -> &F { &self.field }
     pub fn get_value(&self) -> &T { &self.value }
     pub fn is_case_sensitive(&self) -> bool { self.is_case_sensitive }
@@ -26,8 +32,27 @@ pub trait ToLikePredicate<T> {
 }
 
 impl<F, T> super::Predicate for LikePredicate<F, T>
-    where F: sql::ToPredicateValue,
-          T: sql::ToPredicateValue { }
+    where F: sql::ToPredicateValue + Clone + 'static,
+          T: sql::ToPredicateValue + Clone + 'static {
+    fn predicate_node(&self) -> visit::PredicateNode<'_> {
+        visit::PredicateNode::Like {
+            field: self.field.value_node(),
+            value: self.value.value_node(),
+            case_sensitive: self.is_case_sensitive
+        }
+    }
+
+    fn rewrite_operands(&self, rewriter: &mut dyn visit::Rewriter) -> Option<super::SharedPredicate> {
+        match (self.field.rewrite_value(rewriter), self.value.rewrite_value(rewriter)) {
+            (None, None) => None,
+            (field, value) => Some(LikePredicate {
+                field: field.unwrap_or_else(|| self.field.clone()),
+                value: value.unwrap_or_else(|| self.value.clone()),
+                is_case_sensitive: self.is_case_sensitive
+            }.upcast())
+        }
+    }
+}
 
 macro_rules! impl_for {
     ($field:ty, $expr:ty) => (
@@ -1,12 +1,16 @@
-use std::{fmt, rc};
+use std::{fmt, sync};
 
 use super::sql;
+use super::visit;
 
 pub use self::raw::RawPredicate;
+pub use self::tenant::TenantPredicate;
 pub use self::is::{IsPredicate, ToIsPredicate};
 pub use self::is_null::{IsNullPredicate, ToIsNullPredicate};
 pub use self::or::{OrPredicate, ToOrPredicate};
 pub use self::and::{AndPredicate, ToAndPredicate};
+pub use self::all::AllPredicate;
+pub use self::any::AnyPredicate;
 pub use self::exclude::{ExcludePredicate, ToExcludePredicate};
 pub use self::like::{LikePredicate, ToLikePredicate};
 pub use self::in_::{
@@ -29,12 +33,22 @@ pub mod is_null;
 pub mod in_;
 pub mod range;
 pub mod and;
+pub mod all;
+pub mod any;
 pub mod inequality;
 pub mod exclude;
 pub mod like;
 pub mod raw;
+pub mod tenant;
 
-pub trait Predicate: sql::PredicateToSql + fmt::Debug {}
+pub trait Predicate: sql::PredicateToSql + fmt::Debug + Send + Sync {
+    /// Structure of the predicate for visitors and rewriters.
+    fn predicate_node(&self) -> visit::PredicateNode<'_> { visit::PredicateNode::Other }
+
+    /// Copy of a leaf predicate with the subqueries of its operands rewritten,
+    /// `None` if it has none.
+    fn rewrite_operands(&self, _rewriter: &mut dyn visit::Rewriter) -> Option<SharedPredicate> { None }
+}
 
 pub trait ToSharedPredicate {
     fn upcast(self) -> SharedPredicate;
@@ -42,9 +56,9 @@ pub trait ToSharedPredicate {
 
 impl<T> ToSharedPredicate for T where T: Predicate + Clone + 'static {
     fn upcast(self) -> SharedPredicate {
-        rc::Rc::new(Box::new(self))
+        sync::Arc::new(Box::new(self))
     }
 }
 
-pub type BoxedPredicate = Box<Predicate + 'static>;
-pub type SharedPredicate = rc::Rc<BoxedPredicate>;
+pub type BoxedPredicate = Box<dyn Predicate + 'static>;
+pub type SharedPredicate = sync::Arc<BoxedPredicate>;
@@ -1,8 +1,12 @@
 use super::ToSharedPredicate;
+use super::super::visit;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OrPredicate {
+    #[cfg_attr(feature = "serde", serde(with = "::serialize::shared"))]
     pub left: super::SharedPredicate,
+    #[cfg_attr(feature = "serde", serde(with = "::serialize::shared"))]
     pub right: super::SharedPredicate
 }
 
@@ -10,7 +14,11 @@ pub trait ToOrPredicate {
     fn or(&self, val: super::SharedPredicate) -> super::SharedPredicate;
 }
 
-impl super::Predicate for OrPredicate { }
+impl super::Predicate for OrPredicate {
+    fn predicate_node(&self) -> visit::PredicateNode<'_> {
+        visit::PredicateNode::Or(&self.left, &self.right)
+    }
+}
 
 impl ToOrPredicate for super::SharedPredicate {
     fn or(&self, predicate: super::SharedPredicate) -> super::SharedPredicate {
@@ -2,10 +2,12 @@ use chrono;
 use super::super::sql;
 use super::super::expression;
 use super::super::field;
+use super::super::visit;
 
 use super::ToSharedPredicate;
 
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum InRangeBounds {
     ExcludeBoth,
     IncludeBoth,
@@ -14,6 +16,7 @@ pub enum InRangeBounds {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct InRangePredicate<F, T1, T2> {
     field: F,
     from: T1,
@@ -22,6 +25,10 @@ pub struct InRangePredicate<F, T1, T2> {
 }
 
 impl<F, T1, T2> InRangePredicate<F, T1, T2> {
+    pub fn new(field: F, from: T1, to: T2, bounds: InRangeBounds) -> InRangePredicate<F, T1, T2> {
+        InRangePredicate { field, from, to, bounds }
+    }
+
     pub fn get_field(&self) -> &F { &self.field }
     pub fn get_from(&self) -> &T1 { &self.from }
     pub fn get_to(&self) -> &T2 { &self.to }
@@ -47,10 +54,31 @@ pub trait ToInRangePredicate<T> {
 }
 
 impl<F, T1, T2> super::Predicate for InRangePredicate<F, T1, T2>
-    where F: sql::ToPredicateValue,
-          T1: sql::ToPredicateValue,
-          T2: sql::ToPredicateValue
-    { }
+    where F: sql::ToPredicateValue + Clone + 'static,
+          T1: sql::ToPredicateValue + Clone + 'static,
+          T2: sql::ToPredicateValue + Clone + 'static
+{
+    fn predicate_node(&self) -> visit::PredicateNode<'_> {
+        visit::PredicateNode::InRange {
+            field: self.field.value_node(),
+            from: self.from.value_node(),
+            to: self.to.value_node(),
+            bounds: self.bounds
+        }
+    }
+
+    fn rewrite_operands(&self, rewriter: &mut dyn visit::Rewriter) -> Option<super::SharedPredicate> {
+        match (self.field.rewrite_value(rewriter), self.from.rewrite_value(rewriter), self.to.rewrite_value(rewriter)) {
+            (None, None, None) => None,
+            (field, from, to) => Some(InRangePredicate {
+                field: field.unwrap_or_else(|| self.field.clone()),
+                from: from.unwrap_or_else(|| self.from.clone()),
+                to: to.unwrap_or_else(|| self.to.clone()),
+                bounds: self.bounds
+            }.upcast())
+        }
+    }
+}
 
 macro_rules! impl_for {
     ($field:ty, $expr:ty) => (
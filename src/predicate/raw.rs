@@ -1,4 +1,7 @@
+use super::super::visit;
+
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct RawPredicate {
     pub content: String
 }
@@ -9,4 +12,8 @@ impl RawPredicate {
     }
 }
 
-impl super::Predicate for RawPredicate { }
+impl super::Predicate for RawPredicate {
+    fn predicate_node(&self) -> visit::PredicateNode<'_> {
+        visit::PredicateNode::Raw(self)
+    }
+}
@@ -0,0 +1,26 @@
+use super::super::from;
+use super::super::visit;
+
+use super::ToSharedPredicate;
+
+/// Matches the tenant column with the tenant set on `SqlContext` at render time.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TenantPredicate {
+    pub field: from::TenantField
+}
+
+impl TenantPredicate {
+    pub fn get_field(&self) -> &from::TenantField { &self.field }
+
+    /// Tenant filter for the tenant column of a table, if it has one.
+    pub fn filter(field: Option<from::TenantField>) -> Option<super::SharedPredicate> {
+        field.map(|field| TenantPredicate { field }.upcast())
+    }
+}
+
+impl super::Predicate for TenantPredicate {
+    fn predicate_node(&self) -> visit::PredicateNode<'_> {
+        visit::PredicateNode::Tenant(self)
+    }
+}
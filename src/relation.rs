@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+#[cfg(feature = "postgres")] use postgres;
+
+use super::from;
+use super::field;
+use super::expression;
+use super::join;
+#[cfg(feature = "postgres")] use super::model;
+#[cfg(feature = "postgres")] use super::error;
+use super::predicate::{self, ToIsPredicate, ToInPredicate};
+use super::select_query::{self, Queryable};
+use super::sql;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RelationKind {
+    BelongsTo,
+    HasMany
+}
+
+/// Relation between two tables declared once through a foreign key.
+#[derive(Clone, Debug)]
+pub struct Relation<K> {
+    kind: RelationKind,
+    target: from::SharedFrom,
+    foreign_key: field::NamedField<K>,
+    primary_key: field::NamedField<K>,
+    join_type: join::ConditionedJoinType,
+}
+
+// Fields of a join condition must be qualified to stay unambiguous.
+fn qualified<K: Clone>(field: &field::NamedField<K>) -> field::NamedField<K> {
+    if field.qual.is_some() { field.clone() } else { field.qual() }
+}
+
+impl<K> Relation<K>
+    where K: sql::ToPredicateValue + expression::UntypedExpression + Clone + 'static,
+          field::NamedField<K>: expression::ToExpression<K> {
+    /// Child side of the relation: `foreign_key` references `primary_key` of `parent`.
+    pub fn belongs_to(parent: &dyn from::From, foreign_key: &field::NamedField<K>, primary_key: &field::NamedField<K>) -> Relation<K> {
+        Relation {
+            kind: RelationKind::BelongsTo,
+            target: parent.upcast_from(),
+            foreign_key: foreign_key.clone(),
+            primary_key: primary_key.clone(),
+            join_type: join::ConditionedJoinType::InnerJoin,
+        }
+    }
+
+    /// Parent side of the relation: `foreign_key` of `child` references `primary_key`.
+    pub fn has_many(child: &dyn from::From, foreign_key: &field::NamedField<K>, primary_key: &field::NamedField<K>) -> Relation<K> {
+        Relation {
+            kind: RelationKind::HasMany,
+            target: child.upcast_from(),
+            foreign_key: foreign_key.clone(),
+            primary_key: primary_key.clone(),
+            join_type: join::ConditionedJoinType::InnerJoin,
+        }
+    }
+
+    /// Joins the relation with `LEFT JOIN` instead of `INNER JOIN`.
+    pub fn left(&self) -> Relation<K> {
+        with_clone!(self, relation, relation.join_type = join::ConditionedJoinType::LeftJoin)
+    }
+
+    pub fn get_kind(&self) -> RelationKind { self.kind }
+    pub fn get_target(&self) -> &from::SharedFrom { &self.target }
+    pub fn get_foreign_key(&self) -> &field::NamedField<K> { &self.foreign_key }
+    pub fn get_primary_key(&self) -> &field::NamedField<K> { &self.primary_key }
+    pub fn get_join_type(&self) -> join::ConditionedJoinType { self.join_type }
+
+    pub fn on(&self) -> predicate::SharedPredicate {
+        qualified(&self.foreign_key).is(qualified(&self.primary_key))
+    }
+
+    pub fn to_join(&self) -> join::Join {
+        join::Join::ConditionedJoin {
+            join_type: self.join_type,
+            from: self.target.clone(),
+            on: self.on()
+        }
+    }
+
+    /// Selects rows of the target table related to the keys: children referencing any of
+    /// the parent keys for has-many, parents with any of the foreign keys for belongs-to.
+    pub fn children_query<C: Clone>(&self, keys: Vec<K>) -> select_query::SelectQuery<(), select_query::LimitMany, C> {
+        let field = match self.kind {
+            RelationKind::HasMany => &self.foreign_key,
+            RelationKind::BelongsTo => &self.primary_key
+        };
+        select_query::SelectQuery::new(select_query::Select::All, self.target.clone())
+            .where_(field.in_(keys))
+    }
+}
+
+impl<K> Relation<K>
+    where K: sql::ToPredicateValue + expression::UntypedExpression + Clone + Eq + Hash + 'static,
+          field::NamedField<K>: expression::ToExpression<K> {
+    /// Groups loaded rows of the target table by the source row they relate to, keeping the
+    /// order of source rows. `parent_key` and `child_key` return the key joining the two:
+    /// the primary key of the parent and the foreign key of the child for has-many, the
+    /// foreign key of the child and the primary key of the parent for belongs-to.
+    pub fn group<P, C, PK, CK>(&self, parents: Vec<P>, children: Vec<C>, parent_key: PK, child_key: CK) -> Vec<(P, Vec<C>)>
+        where PK: Fn(&P) -> K, CK: Fn(&C) -> K {
+        let mut by_key: HashMap<K, Vec<C>> = HashMap::new();
+        for child in children.into_iter() {
+            by_key.entry(child_key(&child)).or_default().push(child);
+        }
+
+        parents.into_iter().map(|parent| {
+            let children = by_key.remove(&parent_key(&parent)).unwrap_or_default();
+            (parent, children)
+        }).collect()
+    }
+
+    /// Loads related rows of all source rows with a single `IN (...)` query, see `group`.
+    #[cfg(feature = "postgres")]
+    pub fn eager_load<P, C, PK, CK>(&self, conn: &dyn postgres::GenericConnection, parents: Vec<P>, parent_key: PK, child_key: CK) -> Result<Vec<(P, Vec<C>)>, error::Error>
+        where C: model::Model, PK: Fn(&P) -> K, CK: Fn(&C) -> K {
+        if parents.is_empty() {
+            return Ok(vec![])
+        }
+
+        let mut keys: Vec<K> = vec![];
+        for parent in parents.iter() {
+            let key = parent_key(parent);
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+        let children: Vec<C> = self.children_query(keys).query(conn)?;
+        Ok(self.group(parents, children, parent_key, child_key))
+    }
+}
+
+/// Declares `join_<relation>` methods on `SelectQuery`.
+///
+/// ```ignore
+/// relations! {
+///     pub trait JediRelations {
+///         join_padawans => Relation::has_many(&padawan, &padawan.master_id, &jedi.id);
+///     }
+/// }
+///
+/// jedi.select_all().join_padawans();
+/// ```
+#[macro_export]
+macro_rules! relations {
+    (
+        pub trait $name:ident {
+            $($method:ident => $relation:expr;)+
+        }
+    ) => (
+        pub trait $name: Sized {
+            $(fn $method(&self) -> Self;)+
+        }
+
+        impl<T: Clone, L: Clone, M: Clone> $name for $crate::SelectQuery<T, L, M> {
+            $(fn $method(&self) -> Self {
+                self.join_relation(&$relation)
+            })+
+        }
+    )
+}
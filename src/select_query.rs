@@ -1,24 +1,45 @@
-use std::{fmt, rc};
-use std::mem;
+use std::{fmt, sync};
 use std::marker;
 
 use super::from;
 use super::expression::{self, UntypedExpression};
-use super::predicate::{self, ToOrPredicate, ToAndPredicate, ToExcludePredicate};
+use super::predicate::{self, ToOrPredicate, ToAndPredicate, ToExcludePredicate, ToIsNullPredicate};
 use super::sql;
 use super::order_by;
+use super::field;
 use super::join;
+use super::relation;
 use super::distinct;
 use super::group_by;
+use super::visit;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Select {
-    Only(Vec<expression::SharedExpression>),
+    Only(#[cfg_attr(feature = "serde", serde(with = "::serialize::shared"))] Vec<expression::SharedExpression>),
     All
 }
 
-pub trait AbstractSelectQuery: sql::ToSql {
+pub trait AbstractSelectQuery: sql::ToSql + Send + Sync {
+    /// Clauses of the query for visitors.
+    fn get_parts(&self) -> SelectParts<'_>;
 
+    /// Copy of the query without its row, limit and model types.
+    fn untyped(&self) -> SelectQuery<(), LimitMany, ()>;
+}
+
+/// Borrowed clauses of a select query, see `visit::walk_select`.
+#[derive(Clone, Copy, Debug)]
+pub struct SelectParts<'a> {
+    pub distinct: &'a Option<distinct::Distinct>,
+    pub select: &'a Select,
+    pub from: &'a from::SharedFrom,
+    pub joins: &'a [join::Join],
+    pub scope: &'a Option<predicate::SharedPredicate>,
+    pub where_: &'a Option<predicate::SharedPredicate>,
+    pub group_by: &'a Option<group_by::GroupBy>,
+    pub having: &'a Option<predicate::SharedPredicate>,
+    pub order_by: &'a [order_by::OrderBy]
 }
 
 pub trait ToSelectQuery: sql::ToSql {
@@ -27,11 +48,12 @@ pub trait ToSelectQuery: sql::ToSql {
 
 impl<T> ToSelectQuery for T where T: AbstractSelectQuery + Clone + 'static {
     fn upcast(&self) -> SharedSelectQuery {
-        rc::Rc::new(Box::new(self.clone()))
+        sync::Arc::new(Box::new(self.clone()))
     }
 }
 
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum SelectFor {
     Update,
     UpdateNoWait,
@@ -139,47 +161,80 @@ predicate_trait!(
     or_exclude_having
 );
 
+/// Default scope taken from the table the query is built from.
+pub trait Scoped: Queryable {
+    fn get_scope(&self) -> &Option<predicate::SharedPredicate>;
+    fn unset_scope(&mut self);
+
+    /// Drops default scopes of the table, keeping the explicit where clause.
+    fn unscoped(&self) -> Self {
+        with_clone!(self, query, query.unset_scope())
+    }
+
+    /// Applies a named scope, i.e. any `Fn(&Q) -> Q` like `fn active<Q: Queryable>(q: &Q) -> Q`.
+    fn apply_scope<F>(&self, scope: F) -> Self where F: Fn(&Self) -> Self {
+        scope(self)
+    }
+}
+
+/// ANDs all the predicates together, `None` if there are none.
+pub fn and_all(predicates: Vec<predicate::SharedPredicate>) -> Option<predicate::SharedPredicate> {
+    predicates.into_iter().fold(None, |acc, predicate| match acc {
+        Some(acc) => Some(acc.and(predicate)),
+        None => Some(predicate)
+    })
+}
+
+/// Where clause combined with the default scope.
+pub fn scoped_where(scope: &Option<predicate::SharedPredicate>, where_: &Option<predicate::SharedPredicate>) -> Option<predicate::SharedPredicate> {
+    match (scope, where_) {
+        (Some(scope), Some(where_)) => Some(scope.and(where_.clone())),
+        (Some(scope), None) => Some(scope.clone()),
+        (None, where_) => where_.clone()
+    }
+}
+
 pub trait Orderable: Clone {
     fn get_order_by_mut(&mut self) -> &mut Vec<order_by::OrderBy>;
     fn set_order_by(&mut self, order_by: Vec<order_by::OrderBy>);
 
-    fn order_by(&self, field: &UntypedExpression) -> Self {
+    fn order_by(&self, field: &dyn UntypedExpression) -> Self {
         with_clone!(self, query, query.set_order_by(
             vec![order_by::OrderBy::by(field)]
         ))
     }
 
-    fn order_by_fields(&self, fields: &[&UntypedExpression]) -> Self {
+    fn order_by_fields(&self, fields: &[&dyn UntypedExpression]) -> Self {
         with_clone!(self, query, query.set_order_by(
             fields.iter().map(|f| order_by::OrderBy::by(*f)).collect()
         ))
     }
 
-    fn reverse_by(&self, field: &UntypedExpression) -> Self {
+    fn reverse_by(&self, field: &dyn UntypedExpression) -> Self {
         with_clone!(self, query, query.set_order_by(
             vec![order_by::OrderBy::reverse_by(field)]
         ))
     }
 
-    fn reverse_by_fields(&self, fields: &[&UntypedExpression]) -> Self {
+    fn reverse_by_fields(&self, fields: &[&dyn UntypedExpression]) -> Self {
         with_clone!(self, query, query.set_order_by(
             fields.iter().map(|f| order_by::OrderBy::reverse_by(*f)).collect()
         ))
     }
 
-    fn order_append(&self, field: &UntypedExpression) -> Self {
+    fn order_append(&self, field: &dyn UntypedExpression) -> Self {
         with_clone!(self, query, query.get_order_by_mut().push(order_by::OrderBy::by(field)))
     }
 
-    fn order_prepend(&self, field: &UntypedExpression) -> Self {
+    fn order_prepend(&self, field: &dyn UntypedExpression) -> Self {
         with_clone!(self, query, query.get_order_by_mut().insert(0, order_by::OrderBy::by(field)))
     }
 
-    fn reverse_append(&self, field: &UntypedExpression) -> Self {
+    fn reverse_append(&self, field: &dyn UntypedExpression) -> Self {
         with_clone!(self, query, query.get_order_by_mut().push(order_by::OrderBy::reverse_by(field)))
     }
 
-    fn reverse_prepend(&self, field: &UntypedExpression) -> Self {
+    fn reverse_prepend(&self, field: &dyn UntypedExpression) -> Self {
         with_clone!(self, query, query.get_order_by_mut().insert(0, order_by::OrderBy::reverse_by(field)))
     }
 
@@ -189,23 +244,43 @@ pub trait Orderable: Clone {
 
 }
 
+/// Which rows of a soft-deleted table the select returns.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Deleted {
+    Exclude,
+    Include,
+    Only
+}
+
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct SelectQuery<T, L, M> {
     distinct: Option<distinct::Distinct>,
     select: Select,
+    #[cfg_attr(feature = "serde", serde(with = "::serialize::shared"))]
     from: from::SharedFrom,
     joins: Vec<join::Join>,
+    #[cfg_attr(feature = "serde", serde(with = "::serialize::shared"))]
+    scope: Option<predicate::SharedPredicate>,
+    soft_delete: Option<from::DeletedAtField>,
+    deleted: Deleted,
+    #[cfg_attr(feature = "serde", serde(with = "::serialize::shared"))]
     where_: Option<predicate::SharedPredicate>,
     group_by: Option<group_by::GroupBy>,
+    #[cfg_attr(feature = "serde", serde(with = "::serialize::shared"))]
     having: Option<predicate::SharedPredicate>,
     limit: Option<usize>,
     offset: Option<usize>,
     order_by: Vec<order_by::OrderBy>,
     for_: Option<SelectFor>,
 
-    _marker_t: marker::PhantomData<T>,
-    _marker_l: marker::PhantomData<L>,
-    _marker_m: marker::PhantomData<M>
+    #[cfg_attr(feature = "serde", serde(skip))]
+    _marker_t: marker::PhantomData<fn() -> T>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    _marker_l: marker::PhantomData<fn() -> L>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    _marker_m: marker::PhantomData<fn() -> M>
 }
 
 impl<T, L, M> SelectQuery<T, L, M> {
@@ -214,6 +289,9 @@ impl<T, L, M> SelectQuery<T, L, M> {
     pub fn get_select(&self) -> &Select { &self.select }
     pub fn get_from(&self) -> &from::SharedFrom { &self.from }
     pub fn get_joins(&self) -> &Vec<join::Join> { &self.joins }
+    pub fn get_scope(&self) -> &Option<predicate::SharedPredicate> { &self.scope }
+    pub fn get_soft_delete(&self) -> &Option<from::DeletedAtField> { &self.soft_delete }
+    pub fn get_deleted(&self) -> Deleted { self.deleted }
     pub fn get_where(&self) -> &Option<predicate::SharedPredicate> { &self.where_ }
     pub fn get_group_by(&self) -> &Option<group_by::GroupBy> { &self.group_by }
     pub fn get_having(&self) -> &Option<predicate::SharedPredicate> { &self.having }
@@ -221,6 +299,15 @@ impl<T, L, M> SelectQuery<T, L, M> {
     pub fn get_offset(&self) -> &Option<usize> { &self.offset }
     pub fn get_order_by(&self) -> &Vec<order_by::OrderBy> { &self.order_by }
     pub fn get_for(&self) -> &Option<SelectFor> { &self.for_ }
+
+    /// Predicate filtering soft-deleted rows according to the mode.
+    pub fn get_deleted_predicate(&self) -> Option<predicate::SharedPredicate> {
+        self.soft_delete.as_ref().and_then(|field| match self.deleted {
+            Deleted::Exclude => Some(field.is_null()),
+            Deleted::Include => None,
+            Deleted::Only => Some(field.not_null())
+        })
+    }
 }
 
 
@@ -229,10 +316,52 @@ impl<T: Clone, L: Clone, M: Clone> SelectQuery<T, L, M> {
 
     // METHODS
 
+    /// Rebuilds the expressions, sources and predicates of every clause of the query with `rewriter`.
+    pub fn rewrite<R: visit::Rewriter + ?Sized>(&self, rewriter: &mut R) -> SelectQuery<T, L, M> {
+        with_clone!(self, query, {
+            query.distinct = self.distinct.as_ref().map(|distinct| distinct.rewrite(rewriter));
+            query.select = visit::rewrite_select_list(rewriter, &self.select);
+            query.from = rewriter.rewrite_from(self.from.clone());
+            query.joins = self.joins.iter().map(|join| visit::rewrite_join(rewriter, join)).collect();
+            query.scope = self.scope.as_ref().map(|p| visit::rewrite_predicate(rewriter, p));
+            query.where_ = self.where_.as_ref().map(|p| visit::rewrite_predicate(rewriter, p));
+            query.group_by = self.group_by.as_ref().map(|group_by| group_by.rewrite(rewriter));
+            query.having = self.having.as_ref().map(|p| visit::rewrite_predicate(rewriter, p));
+            query.order_by = self.order_by.iter().map(|order_by| order_by.rewrite(rewriter)).collect();
+        })
+    }
+
+    // Rebuilds the query with other type parameters
+    fn retype<T2, L2, M2>(self) -> SelectQuery<T2, L2, M2> {
+        SelectQuery {
+            distinct: self.distinct,
+            select: self.select,
+            from: self.from,
+            joins: self.joins,
+            scope: self.scope,
+            soft_delete: self.soft_delete,
+            deleted: self.deleted,
+            where_: self.where_,
+            group_by: self.group_by,
+            having: self.having,
+            limit: self.limit,
+            offset: self.offset,
+            order_by: self.order_by,
+            for_: self.for_,
+
+            _marker_t: marker::PhantomData,
+            _marker_l: marker::PhantomData,
+            _marker_m: marker::PhantomData,
+        }
+    }
+
     pub fn new(select: Select, from: from::SharedFrom) -> SelectQuery<T, L, M> {
         SelectQuery {
             distinct: None,
             select: select,
+            scope: and_all(from.get_scopes()),
+            soft_delete: from.get_soft_delete(),
+            deleted: Deleted::Exclude,
             from: from,
             joins: vec![],
             where_: None,
@@ -253,24 +382,29 @@ impl<T: Clone, L: Clone, M: Clone> SelectQuery<T, L, M> {
         with_clone!(self, query, query.distinct = Some(distinct::Distinct::new()))
     }
 
-    pub fn distinct_on(&self, fields: &[&UntypedExpression]) -> SelectQuery<T, L, M> {
+    pub fn distinct_on(&self, fields: &[&dyn UntypedExpression]) -> SelectQuery<T, L, M> {
         with_clone!(self, query, query.distinct = Some(distinct::Distinct::on(fields)))
     }
 
-    pub fn group_by(&self, fields: &[&UntypedExpression]) -> SelectQuery<T, L, M> {
+    pub fn group_by(&self, fields: &[&dyn UntypedExpression]) -> SelectQuery<T, L, M> {
         with_clone!(self, query, query.group_by = Some(group_by::GroupBy::by(fields)))
     }
 
     pub fn limit(&self, limit: usize) -> SelectQuery<T, LimitOne, M> {
         let mut query = self.clone();
         query.limit = Some(limit);
-        unsafe{ mem::transmute(query) }
+        query.retype()
+    }
+
+    /// Same as `limit`, but the query keeps returning many rows, e.g. a page of them.
+    pub fn limit_many(&self, limit: usize) -> SelectQuery<T, L, M> {
+        with_clone!(self, query, query.limit = Some(limit))
     }
 
     pub fn first(&self) -> SelectQuery<T, LimitOne, M> {
         let mut query = self.clone();
         query.limit = Some(1);
-        unsafe{ mem::transmute(query) }
+        query.retype()
     }
 
     pub fn offset(&self, offset: usize) -> SelectQuery<T, L, M> {
@@ -301,54 +435,70 @@ impl<T: Clone, L: Clone, M: Clone> SelectQuery<T, L, M> {
         with_clone!(self, query, query.for_ = Some(SelectFor::ShareNoWait))
     }
 
-    pub fn inner_join(&self, from: &from::From, on: predicate::SharedPredicate) -> SelectQuery<T, L, M> {
+    pub fn inner_join(&self, from: &dyn from::From, on: predicate::SharedPredicate) -> SelectQuery<T, L, M> {
         with_clone!(self, query, query.joins.push(join::Join::inner_join(from.upcast_from(), on)))
     }
 
-    pub fn full_outer_join(&self, from: &from::From, on: predicate::SharedPredicate) -> SelectQuery<T, L, M> {
+    pub fn full_outer_join(&self, from: &dyn from::From, on: predicate::SharedPredicate) -> SelectQuery<T, L, M> {
         with_clone!(self, query, query.joins.push(join::Join::full_outer_join(from.upcast_from(), on)))
     }
 
-    pub fn right_outer_join(&self, from: &from::From, on: predicate::SharedPredicate) -> SelectQuery<T, L, M> {
+    pub fn right_outer_join(&self, from: &dyn from::From, on: predicate::SharedPredicate) -> SelectQuery<T, L, M> {
         with_clone!(self, query, query.joins.push(join::Join::right_outer_join(from.upcast_from(), on)))
     }
 
-    pub fn left_outer_join(&self, from: &from::From, on: predicate::SharedPredicate) -> SelectQuery<T, L, M> {
+    pub fn left_outer_join(&self, from: &dyn from::From, on: predicate::SharedPredicate) -> SelectQuery<T, L, M> {
         with_clone!(self, query, query.joins.push(join::Join::left_outer_join(from.upcast_from(), on)))
     }
 
-    pub fn full_join(&self, from: &from::From, on: predicate::SharedPredicate) -> SelectQuery<T, L, M> {
+    pub fn full_join(&self, from: &dyn from::From, on: predicate::SharedPredicate) -> SelectQuery<T, L, M> {
         with_clone!(self, query, query.joins.push(join::Join::full_join(from.upcast_from(), on)))
     }
 
-    pub fn left_join(&self, from: &from::From, on: predicate::SharedPredicate) -> SelectQuery<T, L, M> {
+    pub fn left_join(&self, from: &dyn from::From, on: predicate::SharedPredicate) -> SelectQuery<T, L, M> {
         with_clone!(self, query, query.joins.push(join::Join::left_join(from.upcast_from(), on)))
     }
 
-    pub fn right_join(&self, from: &from::From, on: predicate::SharedPredicate) -> SelectQuery<T, L, M> {
+    pub fn right_join(&self, from: &dyn from::From, on: predicate::SharedPredicate) -> SelectQuery<T, L, M> {
         with_clone!(self, query, query.joins.push(join::Join::right_join(from.upcast_from(), on)))
     }
 
-    pub fn natural_join(&self, from: &from::From) -> SelectQuery<T, L, M> {
+    pub fn natural_join(&self, from: &dyn from::From) -> SelectQuery<T, L, M> {
         with_clone!(self, query, query.joins.push(join::Join::natural_join(from.upcast_from())))
     }
 
-    pub fn natural_left_join(&self, from: &from::From) -> SelectQuery<T, L, M> {
+    pub fn natural_left_join(&self, from: &dyn from::From) -> SelectQuery<T, L, M> {
         with_clone!(self, query, query.joins.push(join::Join::natural_left_join(from.upcast_from())))
     }
 
-    pub fn natural_right_join(&self, from: &from::From) -> SelectQuery<T, L, M> {
+    pub fn natural_right_join(&self, from: &dyn from::From) -> SelectQuery<T, L, M> {
         with_clone!(self, query, query.joins.push(join::Join::natural_right_join(from.upcast_from())))
     }
 
-    pub fn natural_full_join(&self, from: &from::From) -> SelectQuery<T, L, M> {
+    pub fn natural_full_join(&self, from: &dyn from::From) -> SelectQuery<T, L, M> {
         with_clone!(self, query, query.joins.push(join::Join::natural_full_join(from.upcast_from())))
     }
 
-    pub fn cross_join(&self, from: &from::From) -> SelectQuery<T, L, M> {
+    pub fn cross_join(&self, from: &dyn from::From) -> SelectQuery<T, L, M> {
         with_clone!(self, query, query.joins.push(join::Join::cross_join(from.upcast_from())))
     }
 
+    pub fn join_relation<K>(&self, relation: &relation::Relation<K>) -> SelectQuery<T, L, M>
+        where K: sql::ToPredicateValue + expression::UntypedExpression + Clone + 'static,
+              field::NamedField<K>: expression::ToExpression<K> {
+        with_clone!(self, query, query.joins.push(relation.to_join()))
+    }
+
+    /// Returns soft-deleted rows together with the rest, also of joined tables.
+    pub fn with_deleted(&self) -> SelectQuery<T, L, M> {
+        with_clone!(self, query, query.deleted = Deleted::Include)
+    }
+
+    /// Returns soft-deleted rows only.
+    pub fn only_deleted(&self) -> SelectQuery<T, L, M> {
+        with_clone!(self, query, query.deleted = Deleted::Only)
+    }
+
     pub fn unjoin(&self) -> SelectQuery<T, L, M> {
         with_clone!(self, query, query.joins = vec![])
     }
@@ -357,15 +507,15 @@ impl<T: Clone, L: Clone, M: Clone> SelectQuery<T, L, M> {
 pub trait Selectable<M: Clone>: from::From {
     // FIXME: Unify select_N after [generics](https://github.com/rust-lang/rfcs/issues/376)
 
-    fn select_1<T: Clone>(&self, field: &expression::Expression<T>) -> SelectQuery<(T,), LimitMany, M> {
+    fn select_1<T: Clone>(&self, field: &dyn expression::Expression<T>) -> SelectQuery<(T,), LimitMany, M> {
         SelectQuery::new(Select::Only(vec![field.upcast_expression()]), self.upcast_from())
     }
 
-    fn select_2<T1: Clone, T2: Clone>(&self, field1: &expression::Expression<T1>, field2: &expression::Expression<T2>) -> SelectQuery<(T1, T2), LimitMany, M> {
+    fn select_2<T1: Clone, T2: Clone>(&self, field1: &dyn expression::Expression<T1>, field2: &dyn expression::Expression<T2>) -> SelectQuery<(T1, T2), LimitMany, M> {
         SelectQuery::new(Select::Only(vec![field1.upcast_expression(), field2.upcast_expression()]), self.upcast_from())
     }
 
-    fn select(&self, fields: &[&UntypedExpression]) -> SelectQuery<(), LimitMany, M> {
+    fn select(&self, fields: &[&dyn UntypedExpression]) -> SelectQuery<(), LimitMany, M> {
         SelectQuery::new(Select::Only(fields.iter().map(|f| f.upcast_expression()).collect()), self.upcast_from())
     }
 
@@ -384,6 +534,11 @@ impl<T: Clone, L: Clone, M: Clone> Queryable for SelectQuery<T, L, M> {
     fn unset_where(&mut self) { self.where_ = None; }
 }
 
+impl<T: Clone, L: Clone, M: Clone> Scoped for SelectQuery<T, L, M> {
+    fn get_scope(&self) -> &Option<predicate::SharedPredicate> { &self.scope }
+    fn unset_scope(&mut self) { self.scope = None; }
+}
+
 impl<T: Clone, L: Clone, M: Clone> HasHaving for SelectQuery<T, L, M> {
     fn get_having(&self) -> &Option<predicate::SharedPredicate> { &self.having }
     fn set_having(&mut self, predicate: predicate::SharedPredicate) { self.having = Some(predicate); }
@@ -395,18 +550,44 @@ impl<T: Clone, L: Clone, M: Clone> Orderable for SelectQuery<T, L, M> {
     fn set_order_by(&mut self, order_by: Vec<order_by::OrderBy>) { self.order_by = order_by }
 }
 
-impl<T: Clone, L: Clone, M: Clone> AbstractSelectQuery for SelectQuery<T, L, M> { }
+impl<T: Clone, L: Clone, M: Clone> AbstractSelectQuery for SelectQuery<T, L, M> {
+    fn get_parts(&self) -> SelectParts<'_> {
+        SelectParts {
+            distinct: &self.distinct,
+            select: &self.select,
+            from: &self.from,
+            joins: &self.joins,
+            scope: &self.scope,
+            where_: &self.where_,
+            group_by: &self.group_by,
+            having: &self.having,
+            order_by: &self.order_by
+        }
+    }
 
-pub type BoxedSelectQuery = Box<AbstractSelectQuery + 'static>;
-pub type SharedSelectQuery = rc::Rc<BoxedSelectQuery>;
+    fn untyped(&self) -> SelectQuery<(), LimitMany, ()> {
+        self.clone().retype()
+    }
+}
+
+pub type BoxedSelectQuery = Box<dyn AbstractSelectQuery + 'static>;
+pub type SharedSelectQuery = sync::Arc<BoxedSelectQuery>;
 
 impl<T: Clone + 'static + fmt::Debug, L: Clone + 'static + fmt::Debug, M: Clone + 'static + fmt::Debug> UntypedExpression for SelectQuery<T, L, M> {
-    fn expression_as_sql(&self) -> &sql::ToSql {
+    fn expression_as_sql(&self) -> &dyn sql::ToSql {
                 self
     }
 
     fn upcast_expression(&self) -> expression::SharedExpression {
-        rc::Rc::new(Box::new(self.clone()) as expression::BoxedExpression)
+        sync::Arc::new(Box::new(self.clone()) as expression::BoxedExpression)
+    }
+
+    fn expression_node(&self) -> visit::Node<'_> {
+        visit::Node::Select(self)
+    }
+
+    fn rewrite_subqueries(&self, rewriter: &mut dyn visit::Rewriter) -> Option<expression::SharedExpression> {
+        Some(self.rewrite(rewriter).upcast_expression())
     }
 }
 
@@ -0,0 +1,358 @@
+//! Serde support, enabled with the `serde` feature.
+//!
+//! Queries derive `Serialize` and `Deserialize`. Shared predicates, expressions,
+//! sources and updates are trait objects, so they are written as documents built
+//! from their visitor nodes and read back untyped: columns become
+//! `NamedField<RawExpression>`, operands `CastExpression<RawExpression>` and
+//! held values keep their `Value` variant, so a loaded query renders for any adapter.
+
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde::ser::Error as SerError;
+use serde::de::Error as DeError;
+
+use super::expression::{self, CastExpression, RawExpression, UntypedExpression};
+use super::field::{self, Field, NamedField};
+use super::from::{self, Table, TableDef};
+use super::function;
+use super::insert_query::{InsertValue, ToInsertValue};
+use super::placeholder::{Placeholder, NamedPlaceholder};
+use super::predicate::{self, ToSharedPredicate};
+use super::select_query::{SelectQuery, LimitMany};
+use super::sql::Value;
+use super::update_query::{self, FieldUpd};
+use super::visit::{Node, PredicateNode};
+
+type Operand = CastExpression<RawExpression>;
+
+/// Trait objects written through a document, used with `#[serde(with = "::serialize::shared")]`.
+pub trait Shared: Sized {
+    fn serialize_shared<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>;
+    fn deserialize_shared<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>;
+}
+
+pub mod shared {
+    use serde::{Serializer, Deserializer};
+
+    pub fn serialize<T: super::Shared, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+        value.serialize_shared(serializer)
+    }
+
+    pub fn deserialize<'de, T: super::Shared, D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+        T::deserialize_shared(deserializer)
+    }
+}
+
+struct Borrowed<'a, T: 'a>(&'a T);
+
+impl<'a, T: Shared> Serialize for Borrowed<'a, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize_shared(serializer)
+    }
+}
+
+struct Owned<T>(T);
+
+impl<'de, T: Shared> Deserialize<'de> for Owned<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Owned<T>, D::Error> {
+        T::deserialize_shared(deserializer).map(Owned)
+    }
+}
+
+impl<T: Shared> Shared for Option<T> {
+    fn serialize_shared<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match *self {
+            Some(ref value) => serializer.serialize_some(&Borrowed(value)),
+            None => serializer.serialize_none()
+        }
+    }
+
+    fn deserialize_shared<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<T>, D::Error> {
+        Option::<Owned<T>>::deserialize(deserializer).map(|value| value.map(|value| value.0))
+    }
+}
+
+impl<T: Shared> Shared for Vec<T> {
+    fn serialize_shared<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter().map(Borrowed))
+    }
+
+    fn deserialize_shared<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<T>, D::Error> {
+        Vec::<Owned<T>>::deserialize(deserializer).map(|values| values.into_iter().map(|value| value.0).collect())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+enum ExpressionDoc {
+    Field(NamedField<RawExpression>),
+    Value(Value),
+    Raw(RawExpression),
+    Placeholder(Placeholder),
+    NamedPlaceholder(NamedPlaceholder),
+    List(Vec<ExpressionDoc>),
+    Select(Box<SelectQuery<(), LimitMany, ()>>),
+    Function(String, Vec<ExpressionDoc>),
+    Default
+}
+
+fn field_doc(field: &dyn Field) -> NamedField<RawExpression> {
+    match field.qual() {
+        Some(qual) => NamedField::new_qual(field.name(), field.table_name(), qual),
+        None => NamedField::new(field.name(), field.table_name())
+    }
+}
+
+fn expression_doc(node: Node) -> Result<ExpressionDoc, String> {
+    Ok(match node {
+        Node::Field(field) => ExpressionDoc::Field(field_doc(field)),
+        Node::Value(value) => ExpressionDoc::Value(value),
+        Node::Raw(raw) => ExpressionDoc::Raw(raw.clone()),
+        Node::Placeholder(placeholder) => ExpressionDoc::Placeholder(*placeholder),
+        Node::NamedPlaceholder(placeholder) => ExpressionDoc::NamedPlaceholder(placeholder.clone()),
+        Node::List(nodes) => ExpressionDoc::List(expression_docs(nodes)?),
+        Node::Select(select) => ExpressionDoc::Select(Box::new(select.untyped())),
+        Node::Function(name, args) => ExpressionDoc::Function(name.to_string(), expression_docs(args)?),
+        Node::Default => ExpressionDoc::Default,
+        Node::Other => return Err("expression has no serializable structure".to_string())
+    })
+}
+
+fn expression_docs(nodes: Vec<Node>) -> Result<Vec<ExpressionDoc>, String> {
+    nodes.into_iter().map(expression_doc).collect()
+}
+
+fn expression(doc: ExpressionDoc) -> Result<expression::SharedExpression, String> {
+    Ok(match doc {
+        ExpressionDoc::Field(field) => field.upcast_expression(),
+        ExpressionDoc::Value(value) => value.upcast_expression(),
+        ExpressionDoc::Raw(raw) => raw.upcast_expression(),
+        ExpressionDoc::Placeholder(placeholder) => placeholder.upcast_expression(),
+        ExpressionDoc::NamedPlaceholder(placeholder) => placeholder.upcast_expression(),
+        ExpressionDoc::List(docs) => operands(docs)?.upcast_expression(),
+        ExpressionDoc::Select(select) => select.upcast_expression(),
+        ExpressionDoc::Function(name, args) => aggregate(&name, operands(args)?)?,
+        ExpressionDoc::Default => return Err("DEFAULT is only allowed as an inserted or updated value".to_string())
+    })
+}
+
+fn aggregate(name: &str, mut args: Vec<Operand>) -> Result<expression::SharedExpression, String> {
+    if name == "COUNT" && args.is_empty() {
+        return Ok(function::CountAll.upcast_expression())
+    }
+
+    if args.len() != 1 {
+        return Err(format!("{} takes one argument, got {}", name, args.len()))
+    }
+
+    let arg = args.remove(0);
+    Ok(match name {
+        "MIN" => function::Min::<RawExpression, RawExpression, Operand>::new(arg).upcast_expression(),
+        "MAX" => function::Max::<RawExpression, RawExpression, Operand>::new(arg).upcast_expression(),
+        "SUM" => function::Sum::<RawExpression, RawExpression, Operand>::new(arg).upcast_expression(),
+        "AVG" => function::Avg::<RawExpression, RawExpression, Operand>::new(arg).upcast_expression(),
+        "COUNT" => function::Count::<RawExpression, RawExpression, Operand>::new(arg).upcast_expression(),
+        _ => return Err(format!("unknown function {}", name))
+    })
+}
+
+fn operand(doc: ExpressionDoc) -> Result<Operand, String> {
+    expression(doc).map(CastExpression::new)
+}
+
+fn operands(docs: Vec<ExpressionDoc>) -> Result<Vec<Operand>, String> {
+    docs.into_iter().map(operand).collect()
+}
+
+impl Shared for expression::SharedExpression {
+    fn serialize_shared<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        expression_doc(self.expression_node()).map_err(S::Error::custom)?.serialize(serializer)
+    }
+
+    fn deserialize_shared<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        expression(ExpressionDoc::deserialize(deserializer)?).map_err(D::Error::custom)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+enum PredicateDoc {
+    Is { field: ExpressionDoc, value: ExpressionDoc },
+    IsNull { field: ExpressionDoc, is_null: bool },
+    In { field: ExpressionDoc, values: ExpressionDoc },
+    Like { field: ExpressionDoc, value: ExpressionDoc, case_sensitive: bool },
+    InRange { field: ExpressionDoc, from: ExpressionDoc, to: ExpressionDoc, bounds: predicate::InRangeBounds },
+    Inequality { field: ExpressionDoc, value: ExpressionDoc, inequality: predicate::Inequality },
+    And(Box<PredicateDoc>, Box<PredicateDoc>),
+    Or(Box<PredicateDoc>, Box<PredicateDoc>),
+    All(Vec<PredicateDoc>),
+    Any(Vec<PredicateDoc>),
+    Exclude(Box<PredicateDoc>),
+    Raw(predicate::RawPredicate),
+    Tenant(predicate::TenantPredicate)
+}
+
+fn predicate_doc(predicate: &predicate::SharedPredicate) -> Result<PredicateDoc, String> {
+    Ok(match predicate.predicate_node() {
+        PredicateNode::Is { field, value } => PredicateDoc::Is {
+            field: expression_doc(field)?,
+            value: expression_doc(value)?
+        },
+        PredicateNode::IsNull { field, is_null } => PredicateDoc::IsNull {
+            field: expression_doc(field)?,
+            is_null
+        },
+        PredicateNode::In { field, values } => PredicateDoc::In {
+            field: expression_doc(field)?,
+            values: expression_doc(values)?
+        },
+        PredicateNode::Like { field, value, case_sensitive } => PredicateDoc::Like {
+            field: expression_doc(field)?,
+            value: expression_doc(value)?,
+            case_sensitive
+        },
+        PredicateNode::InRange { field, from, to, bounds } => PredicateDoc::InRange {
+            field: expression_doc(field)?,
+            from: expression_doc(from)?,
+            to: expression_doc(to)?,
+            bounds
+        },
+        PredicateNode::Inequality { field, value, inequality } => PredicateDoc::Inequality {
+            field: expression_doc(field)?,
+            value: expression_doc(value)?,
+            inequality
+        },
+        PredicateNode::And(left, right) => PredicateDoc::And(Box::new(predicate_doc(left)?), Box::new(predicate_doc(right)?)),
+        PredicateNode::Or(left, right) => PredicateDoc::Or(Box::new(predicate_doc(left)?), Box::new(predicate_doc(right)?)),
+        PredicateNode::All(predicates) => PredicateDoc::All(predicates.iter().map(predicate_doc).collect::<Result<_, _>>()?),
+        PredicateNode::Any(predicates) => PredicateDoc::Any(predicates.iter().map(predicate_doc).collect::<Result<_, _>>()?),
+        PredicateNode::Exclude(predicate) => PredicateDoc::Exclude(Box::new(predicate_doc(predicate)?)),
+        PredicateNode::Raw(raw) => PredicateDoc::Raw(raw.clone()),
+        PredicateNode::Tenant(tenant) => PredicateDoc::Tenant(tenant.clone()),
+        PredicateNode::Other => return Err("predicate has no serializable structure".to_string())
+    })
+}
+
+fn predicate(doc: PredicateDoc) -> Result<predicate::SharedPredicate, String> {
+    Ok(match doc {
+        PredicateDoc::Is { field, value } => predicate::IsPredicate::new(operand(field)?, operand(value)?).upcast(),
+        PredicateDoc::IsNull { field, is_null } => predicate::IsNullPredicate::new(operand(field)?, is_null).upcast(),
+        PredicateDoc::In { field, values } => predicate::InPredicate::new(operand(field)?, operand(values)?).upcast(),
+        PredicateDoc::Like { field, value, case_sensitive } => {
+            predicate::LikePredicate::new(operand(field)?, operand(value)?, case_sensitive).upcast()
+        },
+        PredicateDoc::InRange { field, from, to, bounds } => {
+            predicate::InRangePredicate::new(operand(field)?, operand(from)?, operand(to)?, bounds).upcast()
+        },
+        PredicateDoc::Inequality { field, value, inequality } => {
+            predicate::InequalityPredicate::new(operand(field)?, operand(value)?, inequality).upcast()
+        },
+        PredicateDoc::And(left, right) => predicate::AndPredicate { left: predicate(*left)?, right: predicate(*right)? }.upcast(),
+        PredicateDoc::Or(left, right) => predicate::OrPredicate { left: predicate(*left)?, right: predicate(*right)? }.upcast(),
+        PredicateDoc::All(docs) => predicate::AllPredicate { predicates: predicates(docs)? }.upcast(),
+        PredicateDoc::Any(docs) => predicate::AnyPredicate { predicates: predicates(docs)? }.upcast(),
+        PredicateDoc::Exclude(inner) => predicate::ExcludePredicate { predicate: predicate(*inner)? }.upcast(),
+        PredicateDoc::Raw(raw) => raw.upcast(),
+        PredicateDoc::Tenant(tenant) => tenant.upcast()
+    })
+}
+
+fn predicates(docs: Vec<PredicateDoc>) -> Result<Vec<predicate::SharedPredicate>, String> {
+    docs.into_iter().map(predicate).collect()
+}
+
+impl Shared for predicate::SharedPredicate {
+    fn serialize_shared<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        predicate_doc(self).map_err(S::Error::custom)?.serialize(serializer)
+    }
+
+    fn deserialize_shared<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        predicate(PredicateDoc::deserialize(deserializer)?).map_err(D::Error::custom)
+    }
+}
+
+fn table_doc(table: &dyn Table) -> TableDef {
+    let mut table_def = match *table.get_table_alias() {
+        Some(ref alias) => TableDef::new_with_alias(table.get_table_name(), alias),
+        None => TableDef::new(table.get_table_name())
+    };
+
+    for scope in table.get_scopes() {
+        table_def = table_def.scope(scope);
+    }
+    if let Some(field) = table.get_soft_delete() {
+        table_def = table_def.soft_delete(&field.name);
+    }
+    if let Some(field) = table.get_tenant() {
+        table_def = table_def.tenant(&field.name);
+    }
+
+    table_def
+}
+
+#[derive(Serialize, Deserialize)]
+enum FromDoc {
+    Table(TableDef),
+    Select { select: SelectQuery<(), LimitMany, ()>, alias: String }
+}
+
+impl Shared for from::SharedFrom {
+    fn serialize_shared<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let doc = match (self.as_table(), self.as_select(), self.get_select_alias()) {
+            (Some(table), _, _) => FromDoc::Table(table_doc(table)),
+            (None, Some(select), Some(alias)) => FromDoc::Select { select: select.untyped(), alias: alias.to_string() },
+            _ => return Err(S::Error::custom("source has no serializable structure"))
+        };
+        doc.serialize(serializer)
+    }
+
+    fn deserialize_shared<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match FromDoc::deserialize(deserializer)? {
+            FromDoc::Table(table) => from::From::upcast_from(&table),
+            FromDoc::Select { select, alias } => from::From::upcast_from(&from::FromSelect { select, alias })
+        })
+    }
+}
+
+impl Shared for from::SharedTable {
+    fn serialize_shared<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        table_doc(&***self).serialize(serializer)
+    }
+
+    fn deserialize_shared<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        TableDef::deserialize(deserializer).map(|table| table.upcast_table())
+    }
+}
+
+impl Shared for field::SharedField {
+    fn serialize_shared<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        field_doc(&***self).serialize(serializer)
+    }
+
+    fn deserialize_shared<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        NamedField::<RawExpression>::deserialize(deserializer).map(|field| field.upcast_field())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct FieldUpdateDoc {
+    field: ExpressionDoc,
+    value: ExpressionDoc
+}
+
+impl Shared for update_query::SharedFieldUpdate {
+    fn serialize_shared<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let doc = FieldUpdateDoc {
+            field: expression_doc(self.field_node()).map_err(S::Error::custom)?,
+            value: expression_doc(self.value_node()).map_err(S::Error::custom)?
+        };
+        doc.serialize(serializer)
+    }
+
+    fn deserialize_shared<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let doc = FieldUpdateDoc::deserialize(deserializer)?;
+        let value: InsertValue<RawExpression> = match doc.value {
+            ExpressionDoc::Default => InsertValue::Default,
+            value => operand(value).map_err(D::Error::custom)?.to_insert_val()
+        };
+        let field = operand(doc.field).map_err(D::Error::custom)?;
+        Ok(update_query::FieldUpdate { field, value }.upcast_field_update())
+    }
+}
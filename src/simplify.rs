@@ -0,0 +1,204 @@
+//! Simplification of predicates built up incrementally, e.g. from filters or scopes.
+//!
+//! `simplify` pushes `NOT` down to the leaves with De Morgan's laws, flattens nested `AND`
+//! and `OR` into `AllPredicate` and `AnyPredicate` lists, which render with minimal
+//! parentheses, and then folds what it can:
+//!
+//! * `true` and `false` raw predicates and leaves comparing constants only,
+//! * duplicate operands of the same list,
+//! * `IN` with a single element into `=`,
+//! * contradictions such as `x = 1 AND x = 2`, `x = 1 AND x IS NULL` or `p AND NOT p`.
+//!
+//! The result matches the same rows as the original predicate. A leaf that is unknown
+//! because of NULLs may be folded to false though, so wrapping the result in `NOT` again
+//! can differ from wrapping the original.
+
+use std::collections::HashMap;
+
+use super::eval;
+use super::expression::{CastExpression, RawExpression, SharedExpression, UntypedExpression};
+use super::field::{Field, NamedField};
+use super::predicate::{self, SharedPredicate, ToSharedPredicate};
+use super::sql::{PostgreSqlAdapter, PredicateToSql, SqlContext, Value};
+use super::visit::{Node, PredicateNode};
+
+type Operand = CastExpression<RawExpression>;
+
+/// Equivalent predicate in normal form, see the module documentation.
+pub fn simplify(predicate: &SharedPredicate) -> SharedPredicate {
+    build(normalize(predicate, false))
+}
+
+enum Term {
+    Const(bool),
+    /// Predicate that isn't a junction, `negated` when it stands for `NOT predicate`.
+    Leaf { predicate: SharedPredicate, negated: bool, key: String },
+    All(Vec<Term>),
+    Any(Vec<Term>)
+}
+
+fn normalize(predicate: &SharedPredicate, negated: bool) -> Term {
+    match predicate.predicate_node() {
+        PredicateNode::And(left, right) => junction(!negated, vec![normalize(left, negated), normalize(right, negated)]),
+        PredicateNode::Or(left, right) => junction(negated, vec![normalize(left, negated), normalize(right, negated)]),
+        PredicateNode::All(predicates) => junction(!negated, predicates.iter().map(|p| normalize(p, negated)).collect()),
+        PredicateNode::Any(predicates) => junction(negated, predicates.iter().map(|p| normalize(p, negated)).collect()),
+        PredicateNode::Exclude(inner) => normalize(inner, !negated),
+        PredicateNode::Raw(raw) => match raw.content.trim().to_lowercase().as_str() {
+            "true" => Term::Const(!negated),
+            "false" => Term::Const(negated),
+            _ => leaf(predicate.clone(), negated)
+        },
+        PredicateNode::In { field, values: Node::List(values) } if values.len() == 1 => {
+            match (operand(&field), operand(&values[0])) {
+                (Some(field), Some(value)) => leaf(predicate::IsPredicate::new(field, value).upcast(), negated),
+                _ => leaf(predicate.clone(), negated)
+            }
+        },
+        _ => leaf(predicate.clone(), negated)
+    }
+}
+
+fn leaf(predicate: SharedPredicate, negated: bool) -> Term {
+    // Leaves without columns, e.g. `1 = 1`, are constants. A NULL makes them unknown,
+    // which doesn't match any row.
+    let no_columns: HashMap<&str, Value> = HashMap::new();
+    match eval::eval(&predicate, &no_columns) {
+        Ok(Some(matches)) => return Term::Const(matches != negated),
+        Ok(None) => return Term::Const(false),
+        Err(_) => ()
+    }
+
+    let key = key(&predicate);
+    Term::Leaf { predicate, negated, key }
+}
+
+// Leaves with the same key are the same predicate: same SQL and same held values.
+fn key(predicate: &SharedPredicate) -> String {
+    let mut ctx = SqlContext::new(Box::new(PostgreSqlAdapter));
+    let sql = predicate.to_sql(false, &mut ctx);
+    format!("{} {:?} {:?}", sql, ctx.values(), ctx.get_named())
+}
+
+fn operand(node: &Node) -> Option<Operand> {
+    let expression: SharedExpression = match *node {
+        Node::Field(field) => match field.qual() {
+            Some(qual) => NamedField::<RawExpression>::new_qual(field.name(), field.table_name(), qual),
+            None => NamedField::<RawExpression>::new(field.name(), field.table_name())
+        }.upcast_expression(),
+        Node::Value(ref value) => value.clone().upcast_expression(),
+        Node::Raw(raw) => raw.clone().upcast_expression(),
+        Node::Placeholder(placeholder) => placeholder.upcast_expression(),
+        Node::NamedPlaceholder(placeholder) => placeholder.upcast_expression(),
+        _ => return None
+    };
+    Some(CastExpression::new(expression))
+}
+
+// `AND` of the terms when `all`, `OR` otherwise.
+fn junction(all: bool, terms: Vec<Term>) -> Term {
+    let mut flat: Vec<Term> = vec![];
+    for term in terms {
+        // Junctions of the same kind are already flat, their terms are deduplicated one by one
+        let nested = match term {
+            Term::Const(value) if value == all => continue,
+            Term::Const(value) => return Term::Const(value),
+            Term::All(terms) if all => terms,
+            Term::Any(terms) if !all => terms,
+            term => vec![term]
+        };
+
+        for term in nested {
+            match term {
+                Term::Leaf { ref key, negated, .. } if flat.iter().any(|t| same_leaf(t, key, negated)) => (),
+                term => flat.push(term)
+            }
+        }
+    }
+
+    if all && contradiction(&flat) {
+        return Term::Const(false)
+    }
+
+    match flat.len() {
+        0 => Term::Const(all),
+        1 => flat.remove(0),
+        _ if all => Term::All(flat),
+        _ => Term::Any(flat)
+    }
+}
+
+fn same_leaf(term: &Term, key: &str, negated: bool) -> bool {
+    match term {
+        &Term::Leaf { key: ref other, negated: other_negated, .. } => other == key && other_negated == negated,
+        _ => false
+    }
+}
+
+// What a conjunct says about a single column: equal to a value, NULL or not NULL.
+enum Fact {
+    Equals(Value),
+    IsNull(bool)
+}
+
+fn fact(predicate: &SharedPredicate, negated: bool) -> Option<(String, Fact)> {
+    match predicate.predicate_node() {
+        PredicateNode::Is { field: Node::Field(field), value: Node::Value(value) } |
+        PredicateNode::Is { field: Node::Value(value), value: Node::Field(field) } if !negated && value != Value::Null => {
+            Some((column(field), Fact::Equals(value)))
+        },
+        PredicateNode::IsNull { field: Node::Field(field), is_null } => Some((column(field), Fact::IsNull(is_null != negated))),
+        _ => None
+    }
+}
+
+fn column(field: &dyn Field) -> String {
+    format!("{}.{}.{}", field.table_name(), field.qual().map(|qual| qual.as_str()).unwrap_or(""), field.name())
+}
+
+fn contradiction(conjuncts: &[Term]) -> bool {
+    let mut facts: Vec<(String, Fact)> = vec![];
+    for (i, term) in conjuncts.iter().enumerate() {
+        if let &Term::Leaf { ref predicate, negated, ref key } = term {
+            if conjuncts[..i].iter().any(|t| same_leaf(t, key, !negated)) {
+                return true
+            }
+
+            if let Some((column, fact)) = fact(predicate, negated) {
+                if facts.iter().any(|(other_column, other)| *other_column == column && contradicts(other, &fact)) {
+                    return true
+                }
+                facts.push((column, fact));
+            }
+        }
+    }
+    false
+}
+
+fn contradicts(left: &Fact, right: &Fact) -> bool {
+    match (left, right) {
+        (Fact::Equals(left), Fact::Equals(right)) => differ(left, right),
+        (Fact::Equals(_), Fact::IsNull(is_null)) | (Fact::IsNull(is_null), Fact::Equals(_)) => *is_null,
+        (Fact::IsNull(left), Fact::IsNull(right)) => left != right
+    }
+}
+
+// Values known to be different, comparing numbers of any width like the database.
+fn differ(left: &Value, right: &Value) -> bool {
+    let equals = predicate::IsPredicate::new(
+        Operand::new(left.clone().upcast_expression()),
+        Operand::new(right.clone().upcast_expression())
+    ).upcast();
+    let no_columns: HashMap<&str, Value> = HashMap::new();
+    eval::eval(&equals, &no_columns) == Ok(Some(false))
+}
+
+fn build(term: Term) -> SharedPredicate {
+    match term {
+        Term::Const(value) => predicate::RawPredicate::new(if value { "true" } else { "false" }).upcast(),
+        Term::Leaf { predicate, negated: false, .. } => predicate,
+        Term::Leaf { predicate, negated: true, .. } => predicate::ExcludePredicate { predicate }.upcast(),
+        Term::All(terms) => predicate::AllPredicate { predicates: terms.into_iter().map(build).collect() }.upcast(),
+        Term::Any(terms) => predicate::AnyPredicate { predicates: terms.into_iter().map(build).collect() }.upcast()
+    }
+}
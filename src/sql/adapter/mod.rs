@@ -1,23 +1,154 @@
 use std::fmt;
+use std::fmt::Write;
+
+use super::Value;
 
 pub trait SqlAdapter: fmt::Debug {
-    fn placeholder(&self, idx: u8) -> String;
+    fn placeholder(&self, idx: usize) -> String;
+
+    /// Same as `placeholder`, but appends to `sql`.
+    fn write_placeholder(&self, idx: usize, sql: &mut String) {
+        sql.push_str(&self.placeholder(idx))
+    }
+
+    /// Name of the dialect used in errors.
+    fn name(&self) -> &'static str { "SQL" }
+
+    fn supports_distinct_on(&self) -> bool { true }
+
+    /// Maximum number of values the driver binds to a single statement.
+    fn max_binds(&self) -> usize { usize::MAX }
+
+    /// Whether a placeholder can be referenced several times by its number.
+    fn numbered_placeholders(&self) -> bool { true }
+
+    /// Expression of the current time, soft deletes set the column to it.
+    fn current_timestamp(&self) -> &'static str { "CURRENT_TIMESTAMP" }
+
+    /// Appends `value` as a literal of the dialect, used by `QueryToSql::to_debug_sql`.
+    fn write_literal(&self, value: &Value, sql: &mut String) {
+        write_literal(value, sql)
+    }
+}
+
+/// Standard SQL literal of `value`, quoting strings by doubling single quotes.
+pub fn write_literal(value: &Value, sql: &mut String) {
+    match *value {
+        Value::Null => sql.push_str("NULL"),
+        Value::Bool(value) => sql.push_str(if value { "TRUE" } else { "FALSE" }),
+        Value::I8(value) => { let _ = write!(sql, "{}", value); },
+        Value::I16(value) => { let _ = write!(sql, "{}", value); },
+        Value::I32(value) => { let _ = write!(sql, "{}", value); },
+        Value::I64(value) => { let _ = write!(sql, "{}", value); },
+        Value::F32(value) => write_float(value, sql),
+        Value::F64(value) => write_float(value, sql),
+        Value::String(ref value) => write_quoted(value, sql),
+        Value::Bytes(ref value) => {
+            sql.push_str("X'");
+            write_hex(value, sql);
+            sql.push('\'');
+        },
+        Value::Json(ref value) => write_quoted(&value.to_string(), sql),
+        Value::DateTime(ref value) => write_quoted(&value.format("%Y-%m-%d %H:%M:%S%.f").to_string(), sql),
+        Value::Uuid(ref value) => write_quoted(&value.hyphenated().to_string(), sql),
+    }
+}
+
+// Formats the value at its own precision, widening an f32 would print e.g. 0.1 as 0.10000000149011612.
+fn write_float<F: Into<f64> + Copy + fmt::Debug>(value: F, sql: &mut String) {
+    let wide: f64 = value.into();
+    if wide.is_nan() {
+        sql.push_str("'NaN'")
+    } else if wide.is_infinite() {
+        sql.push_str(if wide > 0.0 { "'Infinity'" } else { "'-Infinity'" })
+    } else {
+        let _ = write!(sql, "{:?}", value);
+    }
+}
+
+fn write_quoted(value: &str, sql: &mut String) {
+    sql.push('\'');
+    for c in value.chars() {
+        if c == '\'' { sql.push('\'') }
+        sql.push(c);
+    }
+    sql.push('\'');
+}
+
+fn write_hex(bytes: &[u8], sql: &mut String) {
+    for byte in bytes.iter() {
+        let _ = write!(sql, "{:02x}", byte);
+    }
 }
 
 #[derive(Copy, Clone, Debug)]
 pub struct PostgreSqlAdapter;
 
 impl SqlAdapter for PostgreSqlAdapter {
-    fn placeholder(&self, idx: u8) -> String {
+    fn placeholder(&self, idx: usize) -> String {
         format!("${}", idx)
     }
+
+    fn write_placeholder(&self, idx: usize, sql: &mut String) {
+        let _ = write!(sql, "${}", idx);
+    }
+
+    fn name(&self) -> &'static str { "PostgreSQL" }
+
+    fn max_binds(&self) -> usize { 65535 }
+
+    fn write_literal(&self, value: &Value, sql: &mut String) {
+        match *value {
+            // Hex format of bytea, standard_conforming_strings keeps the backslash as is
+            Value::Bytes(ref bytes) => {
+                sql.push_str("'\\x");
+                write_hex(bytes, sql);
+                sql.push_str("'::bytea");
+            },
+            _ => write_literal(value, sql)
+        }
+    }
 }
 
 #[derive(Copy, Clone, Debug)]
 pub struct MysqlAdapter;
 
 impl SqlAdapter for MysqlAdapter {
-    fn placeholder(&self, _: u8) -> String {
+    fn placeholder(&self, _: usize) -> String {
         "?".to_owned()
     }
+
+    fn write_placeholder(&self, _: usize, sql: &mut String) {
+        sql.push('?')
+    }
+
+    fn name(&self) -> &'static str { "MySQL" }
+
+    fn supports_distinct_on(&self) -> bool { false }
+
+    fn max_binds(&self) -> usize { 65535 }
+
+    fn numbered_placeholders(&self) -> bool { false }
+
+    fn write_literal(&self, value: &Value, sql: &mut String) {
+        match *value {
+            Value::String(ref value) => write_mysql_quoted(value, sql),
+            Value::Json(ref value) => write_mysql_quoted(&value.to_string(), sql),
+            _ => write_literal(value, sql)
+        }
+    }
+}
+
+// MySQL treats backslashes in strings as escapes unless NO_BACKSLASH_ESCAPES is set.
+fn write_mysql_quoted(value: &str, sql: &mut String) {
+    sql.push('\'');
+    for c in value.chars() {
+        match c {
+            '\'' => sql.push_str("''"),
+            '\\' => sql.push_str("\\\\"),
+            '\0' => sql.push_str("\\0"),
+            c => sql.push(c)
+        }
+    }
+    sql.push('\'');
 }
\ No newline at end of file
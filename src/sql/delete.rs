@@ -1,41 +1,76 @@
 use super::super::delete_query;
+use super::super::select_query;
+use super::super::predicate::{TenantPredicate, ToIsNullPredicate};
 use super::from::FromToSql;
+use super::predicate::PredicateToSql;
+use std::fmt::Write;
 
 impl<T, L, M> super::ToSql for delete_query::DeleteQuery<T, L, M> {
     fn to_sql(&self, ctx: &mut super::SqlContext) -> String {
-        let mut sql = "DELETE FROM".to_string();
+        ctx.capture(|ctx| self.write_sql(ctx))
+    }
+
+    fn write_sql(&self, ctx: &mut super::SqlContext) {
+        ctx.write(match *self.get_soft_delete() {
+            Some(_) => "UPDATE",
+            None => "DELETE FROM"
+        });
 
         if self.is_only() {
-            sql = format!("{} ONLY", sql)
+            ctx.write(" ONLY")
         }
 
-        sql = format!("{} {}", sql, self.get_table().to_from_sql(ctx));
+        ctx.write(" ");
+        self.get_table().write_from_sql(ctx);
+
+        if let Some(ref field) = *self.get_soft_delete() {
+            ctx.newline();
+            let now = ctx.adapter().current_timestamp();
+            let _ = write!(ctx, "SET {} = {}", field.name, now);
+        }
 
         if self.get_using().is_some() {
             let using = self.get_using().as_ref().unwrap();
-            if !using.is_empty() {
-                let tables_str: Vec<String> = using.iter().map(|v| v.as_sql().to_from_sql(ctx)).collect();
-                sql = format!("{} USING {}", sql, tables_str.join(", "))
+            let keyword = if self.get_soft_delete().is_some() { "FROM " } else { "USING " };
+            for (i, table) in using.iter().enumerate() {
+                if i == 0 {
+                    ctx.newline();
+                    ctx.write(keyword);
+                } else {
+                    ctx.write(", ");
+                }
+                table.as_sql().write_from_sql(ctx);
             }
         }
 
-        match self.get_where().as_ref() {
-            Some(predicate) => {
-                sql = format!("{} WHERE {}", sql, predicate.to_sql(false, ctx))
-            },
-            None if !self.is_all() => {
-                // http://devopsreactions.tumblr.com/post/47352638154/almost-ran-update-without-where
-                sql = format!("{} WHERE true = false", sql)
-            },
-            _ => ()
-        }
+        if self.get_where().is_none() && !self.is_all() {
+            // http://devopsreactions.tumblr.com/post/47352638154/almost-ran-update-without-where
+            ctx.newline();
+            ctx.write("WHERE true = false")
+        } else {
+            let mut predicates = vec![];
+            predicates.extend(TenantPredicate::filter(self.get_table().get_tenant()));
+            // Soft deletion doesn't touch rows that are already deleted
+            predicates.extend(self.get_soft_delete().as_ref().map(|field| field.is_null()));
+            for from in self.get_using().iter().flat_map(|tables| tables.iter()) {
+                predicates.extend(super::join::filters(from, select_query::Deleted::Exclude));
+            }
+            predicates.extend(select_query::scoped_where(self.get_scope(), self.get_where()));
 
-        match self.get_returning() {
-            &Some(ref select) => sql = format!("{} RETURNING {}", sql, select.to_sql(ctx)),
-            &None => ()
+            if let Some(predicate) = select_query::and_all(predicates) {
+                ctx.newline();
+                ctx.write("WHERE ");
+                ctx.indent();
+                predicate.write_sql(false, ctx);
+                ctx.dedent();
+            }
         }
 
-        sql
+        if let Some(ref select) = *self.get_returning() {
+            ctx.newline();
+            ctx.write("RETURNING ");
+            select.write_sql(ctx);
+        }
     }
 }
 
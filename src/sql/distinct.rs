@@ -1,4 +1,5 @@
 use super::super::distinct;
+use super::super::error;
 
 impl super::ToSql for distinct::Distinct {
     fn to_sql(&self, ctx: &mut super::SqlContext) -> String {
@@ -6,6 +7,10 @@ impl super::ToSql for distinct::Distinct {
             &None => "DISTINCT".to_string(),
             &Some(ref on) if on.is_empty() => "DISTINCT".to_string(),
             &Some(ref on) => {
+                if !ctx.adapter().supports_distinct_on() {
+                    let adapter = ctx.adapter().name();
+                    ctx.error(error::Error::Unsupported { feature: "DISTINCT ON", adapter });
+                }
                 let defs: Vec<String> = on.iter().map(|f| f.expression_as_sql().to_sql(ctx)).collect();
                 format!("DISTINCT ON ({})", defs.join(", "))
             }
@@ -9,6 +9,15 @@ impl<T> super::ToSql for insert_query::InsertValue<T> {
             &insert_query::InsertValue::Default => "DEFAULT".to_string()
         }
     }
+
+    fn write_sql(&self, ctx: &mut super::SqlContext) {
+        match *self {
+            insert_query::InsertValue::Value{ref expression, ..} => {
+                expression.expression_as_sql().write_sql(ctx)
+            },
+            insert_query::InsertValue::Default => ctx.write("DEFAULT")
+        }
+    }
 }
 
 impl super::ToSql for ()  {
@@ -24,6 +33,13 @@ macro_rules! to_sql_for_insert_tuple {
                 let &($(ref $var,)+) = self;
                 format!($fmt, $($var.to_sql(ctx),)+)
             }
+
+            #[allow(unused_assignments)]
+            fn write_sql(&self, ctx: &mut super::SqlContext) {
+                let &($(ref $var,)+) = self;
+                let mut sep = "";
+                $(ctx.write(sep); $var.write_sql(ctx); sep = ", ";)+
+            }
         }
 
     )
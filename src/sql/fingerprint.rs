@@ -0,0 +1,45 @@
+use std::fmt;
+
+// 64-bit FNV-1a, fixed so hashes stay comparable across builds and Rust versions.
+const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// Normalized SQL of a query and its hash, the same for every set of values.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Fingerprint {
+    sql: String,
+    hash: u64,
+}
+
+impl Fingerprint {
+    /// Collapses whitespace outside of quotes in the normalized SQL and hashes it.
+    pub fn new(normalized: &str) -> Fingerprint {
+        let mut sql = String::with_capacity(normalized.len());
+        let mut quote = None;
+        for c in normalized.chars() {
+            match quote {
+                Some(q) if c == q => quote = None,
+                Some(_) => (),
+                None if c == '\'' || c == '"' => quote = Some(c),
+                None if c.is_whitespace() => {
+                    if !sql.ends_with(' ') { sql.push(' ') }
+                    continue
+                },
+                None => ()
+            }
+            sql.push(c);
+        }
+
+        let hash = sql.bytes().fold(FNV_OFFSET, |hash, byte| (hash ^ byte as u64).wrapping_mul(FNV_PRIME));
+        Fingerprint { sql, hash }
+    }
+
+    pub fn get_sql(&self) -> &str { &self.sql }
+    pub fn get_hash(&self) -> u64 { self.hash }
+}
+
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:016x}", self.hash)
+    }
+}
@@ -2,24 +2,38 @@ use super::super::from::{self, Table};
 
 pub trait FromToSql {
     fn to_from_sql(&self, ctx: &mut super::SqlContext) -> String;
+
+    /// Writes the source into the buffer of the context, see `ToSql::write_sql`.
+    fn write_from_sql(&self, ctx: &mut super::SqlContext) {
+        let sql = self.to_from_sql(ctx);
+        ctx.write(&sql)
+    }
+}
+
+fn write_table(table: &dyn Table, ctx: &mut super::SqlContext) {
+    ctx.write(table.get_table_name());
+    if let Some(ref alias) = *table.get_table_alias() {
+        ctx.write(" AS ");
+        ctx.write(alias);
+    }
 }
 
 impl FromToSql for from::TableDef {
-    fn to_from_sql(&self, _ctx: &mut super::SqlContext) -> String {
-        let name = self.get_table_name();
-        match self.get_table_alias() {
-            &Some(ref alias) => format!("{} AS {}", name, alias),
-            &None => format!("{}", name),
-        }
+    fn to_from_sql(&self, ctx: &mut super::SqlContext) -> String {
+        ctx.capture(|ctx| self.write_from_sql(ctx))
+    }
+
+    fn write_from_sql(&self, ctx: &mut super::SqlContext) {
+        write_table(self, ctx)
     }
 }
 
 impl FromToSql for from::SharedTable {
-    fn to_from_sql(&self, _ctx: &mut super::SqlContext) -> String {
-        let name = self.get_table_name();
-        match self.get_table_alias() {
-            &Some(ref alias) => format!("{} AS {}", name, alias),
-            &None => format!("{}", name),
-        }
+    fn to_from_sql(&self, ctx: &mut super::SqlContext) -> String {
+        ctx.capture(|ctx| self.write_from_sql(ctx))
+    }
+
+    fn write_from_sql(&self, ctx: &mut super::SqlContext) {
+        write_table(&***self, ctx)
     }
 }
@@ -2,11 +2,18 @@ use super::super::group_by;
 
 impl super::ToSql for group_by::GroupBy {
     fn to_sql(&self, ctx: &mut super::SqlContext) -> String {
-        if !self.get_by().is_empty() {
-            let defs: Vec<String> = self.get_by().iter().map(|f| f.expression_as_sql().to_sql(ctx)).collect();
-            format!(" GROUP BY {}", defs.join(", "))
-        } else {
-            String::new()
+        ctx.capture(|ctx| self.write_sql(ctx))
+    }
+
+    fn write_sql(&self, ctx: &mut super::SqlContext) {
+        for (i, f) in self.get_by().iter().enumerate() {
+            if i == 0 {
+                ctx.newline();
+                ctx.write("GROUP BY ");
+            } else {
+                ctx.write(", ");
+            }
+            f.expression_as_sql().write_sql(ctx);
         }
     }
 }
@@ -1,51 +1,138 @@
+use std::cmp;
+
 use super::super::insert_query;
+use super::super::from;
+use super::super::error;
+use super::ToSql;
 
 impl<T: Clone, V: super::ToSql, M: Clone> super::ToSql for insert_query::Insert<T, V, M> {
     fn to_sql(&self, ctx: &mut super::SqlContext) -> String {
-        match self {
-            &insert_query::Insert::DefaultValues => {
-                format!("DEFAULT VALUES")
-            },
-            &insert_query::Insert::Values(ref rows) => {
-                let rows_str: Vec<String> = rows.iter().map(|row| { format!("({})", row.to_sql(ctx)) }).collect();
-                format!("VALUES\n    {}", rows_str.join(",\n    "))
-            },
-            &insert_query::Insert::UntypedValues(ref rows) => {
-                let rows_str: Vec<String> = rows.iter().map(|row| {
-                    let values_str: Vec<String> = row.iter().map(|v| v.to_sql(ctx)).collect();
-                    format!("({})", values_str.join(", "))
-                }).collect();
-                format!("VALUES\n    {}", rows_str.join(",\n    "))
-            },
-            &insert_query::Insert::FromSelect(ref select) => {
-                select.to_sql(ctx)
+        ctx.capture(|ctx| self.write_sql(ctx))
+    }
+
+    fn write_sql(&self, ctx: &mut super::SqlContext) {
+        write_values(self, None, ctx)
+    }
+}
+
+// Writes the values of an insert, appending the tenant of the context to every row if any.
+fn write_values<T: Clone, V: super::ToSql, M: Clone>(values: &insert_query::Insert<T, V, M>, tenant: Option<&from::TenantField>, ctx: &mut super::SqlContext) {
+    match *values {
+        insert_query::Insert::DefaultValues => {
+            match tenant {
+                Some(tenant) => {
+                    ctx.write("VALUES (");
+                    write_tenant(tenant, ctx);
+                    ctx.write(")");
+                },
+                None => ctx.write("DEFAULT VALUES")
+            }
+        },
+        insert_query::Insert::Values(ref rows) => {
+            ctx.write("VALUES");
+            for (i, row) in rows.iter().take(row_count(rows.len(), ctx)).enumerate() {
+                write_row_break(i, ctx);
+                row.write_sql(ctx);
+                if let Some(tenant) = tenant {
+                    ctx.write(", ");
+                    write_tenant(tenant, ctx);
+                }
+                ctx.write(")");
+            }
+        },
+        insert_query::Insert::UntypedValues(ref rows) => {
+            ctx.write("VALUES");
+            for (i, row) in rows.iter().take(row_count(rows.len(), ctx)).enumerate() {
+                write_row_break(i, ctx);
+                for (j, value) in row.iter().enumerate() {
+                    if j > 0 { ctx.write(", ") }
+                    value.write_sql(ctx);
+                }
+                if let Some(tenant) = tenant {
+                    if !row.is_empty() { ctx.write(", ") }
+                    write_tenant(tenant, ctx);
+                }
+                ctx.write(")");
+            }
+        },
+        insert_query::Insert::FromSelect(ref select) => {
+            match tenant {
+                Some(tenant) => {
+                    // The tenant is held before the values of the subquery to keep them in text order
+                    ctx.write("SELECT source.*, ");
+                    write_tenant(tenant, ctx);
+                    ctx.write(" FROM (");
+                    select.write_sql(ctx);
+                    ctx.write(") AS source");
+                },
+                None => select.write_sql(ctx)
             }
         }
     }
 }
 
+// Bulk inserts of any size share the fingerprint of the query.
+fn row_count(rows: usize, ctx: &super::SqlContext) -> usize {
+    if ctx.is_normalized() { cmp::min(rows, 1) } else { rows }
+}
+
+// Rows always start on lines of their own, indented under VALUES.
+fn write_row_break(i: usize, ctx: &mut super::SqlContext) {
+    if i > 0 { ctx.write(",") }
+    if ctx.is_pretty() {
+        ctx.indent();
+        ctx.newline();
+        ctx.dedent();
+    } else {
+        ctx.write("\n    ");
+    }
+    ctx.write("(");
+}
+
+fn write_tenant(tenant: &from::TenantField, ctx: &mut super::SqlContext) {
+    ctx.write_tenant(&tenant.table_name)
+}
+
 impl<T: Clone, V: Clone+super::ToSql, M: Clone, RT: Clone, RL: Clone> super::ToSql for insert_query::InsertQuery<T, V, M, RT, RL> {
     fn to_sql(&self, ctx: &mut super::SqlContext) -> String {
-        let mut sql = format!("INSERT INTO {}", self.get_into().get_table_name());
+        ctx.capture(|ctx| self.write_sql(ctx))
+    }
+
+    fn write_sql(&self, ctx: &mut super::SqlContext) {
+        ctx.write("INSERT INTO ");
+        ctx.write(self.get_into().get_table_name());
+        let tenant = self.get_into().get_tenant();
 
-        let maybe_cols = self.get_cols().as_ref();
-        if maybe_cols.is_some() {
-            let cols = maybe_cols.unwrap();
+        let mut cols_str: Vec<String> = match *self.get_cols() {
+            Some(ref cols) => cols.iter().map(|col| col.to_sql(ctx)).collect(),
+            None => vec![]
+        };
 
-            if !cols.is_empty() {
-                let cols_str: Vec<String> = cols.iter().map(|col| col.to_sql(ctx)).collect();
-                sql = format!("{} ({})", sql, cols_str.join(", "))
+        if let Some(ref tenant) = tenant {
+            match self.get_values() {
+                &insert_query::Insert::DefaultValues => (),
+                _ if cols_str.is_empty() => {
+                    ctx.error(error::Error::MissingColumns { table: tenant.table_name.to_string() })
+                },
+                _ => ()
             }
+            cols_str.push(tenant.name.to_string())
         }
 
-        sql = format!("{} {}", sql, self.get_values().to_sql(ctx));
-
-        match self.get_returning() {
-            &Some(ref select) => sql = format!("{} RETURNING {}", sql, select.to_sql(ctx)),
-            &None => ()
+        if !cols_str.is_empty() {
+            ctx.write(" (");
+            ctx.write(&cols_str.join(", "));
+            ctx.write(")");
         }
 
-        sql
+        ctx.newline();
+        write_values(self.get_values(), tenant.as_ref(), ctx);
+
+        if let Some(ref select) = *self.get_returning() {
+            ctx.newline();
+            ctx.write("RETURNING ");
+            select.write_sql(ctx);
+        }
     }
 }
 
@@ -1,4 +1,96 @@
+use super::super::from;
 use super::super::join;
+use super::super::predicate::{SharedPredicate, TenantPredicate, ToAndPredicate, ToIsNullPredicate};
+use super::super::select_query::{self, Deleted, Scoped};
+use super::from::FromToSql;
+use super::predicate::PredicateToSql;
+use super::ToSql;
+
+/// Filters of a table: its tenant and its soft-deleted rows according to `deleted`.
+pub fn filters(from: &from::SharedFrom, deleted: Deleted) -> Vec<SharedPredicate> {
+    let mut filters = vec![];
+    filters.extend(TenantPredicate::filter(from.get_tenant()));
+    filters.extend(from.get_soft_delete().and_then(|field| match deleted {
+        Deleted::Exclude => Some(field.is_null()),
+        Deleted::Include => None,
+        Deleted::Only => Some(field.not_null())
+    }));
+    filters
+}
+
+/// Table wrapped into a subquery with its filters and the alias of the table, `None` if it
+/// has no filters.
+///
+/// Outer joins keep rows of their preserved side whatever `ON` says, and `WHERE` would drop
+/// the rows extended with NULLs, so the preserved side is filtered before it is joined.
+pub fn filtered(from: &from::SharedFrom, deleted: Deleted) -> Option<from::FromSelect<(), select_query::LimitMany, ()>> {
+    let table = match from.as_table() {
+        Some(table) if !filters(from, deleted).is_empty() => table,
+        _ => return None
+    };
+
+    let select = select_query::SelectQuery::new(select_query::Select::All, from.clone()).unscoped();
+    let select = match deleted {
+        Deleted::Exclude => select,
+        Deleted::Include => select.with_deleted(),
+        Deleted::Only => select.only_deleted()
+    };
+    Some(select.alias(table.get_table_alias().as_ref().unwrap_or(table.get_table_name())))
+}
+
+/// Whether the join extends rows joined before it with NULLs, i.e. they aren't preserved.
+pub fn extends_preceding(join: &join::Join) -> bool {
+    match *join {
+        join::Join::ConditionedJoin{ref join_type, ..} => matches!(*join_type,
+            join::ConditionedJoinType::RightJoin | join::ConditionedJoinType::RightOuterJoin |
+            join::ConditionedJoinType::FullJoin | join::ConditionedJoinType::FullOuterJoin),
+        join::Join::UnconditionedJoin{ref join_type, ..} => matches!(*join_type,
+            join::UnconditionedJoinType::NaturalRightJoin | join::UnconditionedJoinType::NaturalFullJoin)
+    }
+}
+
+/// Writes the join with the filters of the joined table, see `filters`.
+///
+/// `INNER` and `LEFT` joins filter the joined table in their `ON` clause. It is preserved by
+/// other conditioned joins and natural joins have no `ON` clause, so the table is joined as
+/// a filtered subquery then, see `filtered`.
+pub fn write_join(join: &join::Join, with_deleted: bool, ctx: &mut super::SqlContext) {
+    let deleted = if with_deleted { Deleted::Include } else { Deleted::Exclude };
+    match *join {
+        join::Join::ConditionedJoin{ref join_type, ref from, ref on} => {
+            join_type.write_sql(ctx);
+            ctx.write(" ");
+            let on = match join_type {
+                &join::ConditionedJoinType::InnerJoin | &join::ConditionedJoinType::LeftJoin |
+                &join::ConditionedJoinType::LeftOuterJoin => {
+                    from.as_sql().write_from_sql(ctx);
+                    filters(from, deleted).into_iter().fold(on.clone(), |on, filter| on.and(filter))
+                },
+                _ => {
+                    write_from(from, deleted, ctx);
+                    on.clone()
+                }
+            };
+            ctx.write(" ON ");
+            ctx.indent();
+            on.write_sql(false, ctx);
+            ctx.dedent();
+        },
+        join::Join::UnconditionedJoin{ref join_type, ref from} => {
+            join_type.write_sql(ctx);
+            ctx.write(" ");
+            write_from(from, deleted, ctx);
+        }
+    }
+}
+
+/// Writes the table, as a filtered subquery if it has filters, see `filtered`.
+pub fn write_from(from: &from::SharedFrom, deleted: Deleted, ctx: &mut super::SqlContext) {
+    match filtered(from, deleted) {
+        Some(filtered) => filtered.write_from_sql(ctx),
+        None => from.as_sql().write_from_sql(ctx)
+    }
+}
 
 impl super::ToSql for join::ConditionedJoinType {
     fn to_sql(&self, _ctx: &mut super::SqlContext) -> String {
@@ -28,13 +120,10 @@ impl super::ToSql for join::UnconditionedJoinType {
 
 impl super::ToSql for join::Join {
     fn to_sql(&self, ctx: &mut super::SqlContext) -> String {
-        match self {
-            &join::Join::ConditionedJoin{ref join_type, ref from, ref on} => {
-                format!("{} {} ON {}", join_type.to_sql(ctx), from.as_sql().to_from_sql(ctx), on.to_sql(false, ctx))
-            },
-            &join::Join::UnconditionedJoin{ref join_type, ref from} => {
-                format!("{} {}", join_type.to_sql(ctx), from.as_sql().to_from_sql(ctx))
-            }
-        }
+        ctx.capture(|ctx| self.write_sql(ctx))
+    }
+
+    fn write_sql(&self, ctx: &mut super::SqlContext) {
+        write_join(self, false, ctx)
     }
 }
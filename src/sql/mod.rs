@@ -1,11 +1,18 @@
+use std::{fmt, mem, sync};
+
+use super::error;
+
 pub use self::predicate::PredicateToSql;
-pub use self::value::ToPredicateValue;
+pub use self::value::{ToPredicateValue, Value, NamedValues};
 #[cfg(feature = "postgres")] pub use self::value::AsPostgresValue;
 pub use self::from::FromToSql;
+pub use self::template::Template;
+pub use self::fingerprint::Fingerprint;
 pub use self::adapter::{
     SqlAdapter,
     PostgreSqlAdapter,
     MysqlAdapter,
+    write_literal,
 };
 
 pub mod delete;
@@ -24,60 +31,317 @@ pub mod from;
 pub mod update;
 pub mod value;
 pub mod adapter;
+pub mod template;
+pub mod fingerprint;
 
 pub trait QueryToSql: ToSql {
-    fn to_final_sql(&self, ctx: &mut SqlContext) -> String {
-        let mut sql = format!("{};", self.to_sql(ctx));
-        let mut idx = ctx.get_expl_placeholders_count() + 1;
-        for i in 0..ctx.get_impl_placeholders_count() {
-            sql = sql.replace(&format!("$${}", i), &ctx.adapter().placeholder(idx));
-            idx += 1
+    /// Renders the query with numbered placeholders, failing on the first error met.
+    fn to_final_sql(&self, ctx: &mut SqlContext) -> error::Result<String> {
+        let raw = render(self, ctx)?;
+
+        let count = ctx.get_expl_placeholders_count() + ctx.get_impl_placeholders_count();
+        let max = ctx.adapter().max_binds();
+        if count > max && !ctx.is_normalized() {
+            return Err(error::Error::TooManyPlaceholders { count, max });
+        }
+
+        // Implicit placeholders are numbered after the explicit ones
+        let first = ctx.get_expl_placeholders_count() + 1;
+        Ok(replace_markers(&raw, |idx, sql| {
+            if ctx.is_normalized() {
+                sql.push('?')
+            } else {
+                ctx.adapter().write_placeholder(first + idx, sql)
+            }
+        }))
+    }
+
+    /// Renders the query with the held values inlined as literals of the adapter's dialect.
+    /// Meant for logs and SQL consoles, never execute the result.
+    fn to_debug_sql(&self, ctx: &mut SqlContext) -> error::Result<DebugSql> {
+        let raw = render(self, ctx)?;
+
+        // Named placeholders have no value yet and stay placeholders
+        let first = ctx.get_expl_placeholders_count() + 1;
+        Ok(DebugSql(replace_markers(&raw, |idx, sql| {
+            match ctx.values().get(idx) {
+                Some(value) if !ctx.get_named().iter().any(|&(_, named)| named == idx) => {
+                    ctx.adapter().write_literal(value, sql)
+                },
+                _ => ctx.adapter().write_placeholder(first + idx, sql)
+            }
+        })))
+    }
+
+    /// Renders the query and takes the bound values out of the context.
+    fn build(&self, ctx: &mut SqlContext) -> error::Result<CompiledQuery> {
+        let sql = self.to_final_sql(ctx)?;
+        Ok(CompiledQuery { sql, params: ctx.take_values(), named: ctx.take_named() })
+    }
+
+    /// Renders the query once into a template to be bound many times.
+    fn prepare(&self, ctx: &mut SqlContext) -> error::Result<Template> {
+        Ok(Template::new(self.build(ctx)?))
+    }
+
+    /// Renders the normalized form of the query, equal for queries differing only in values:
+    /// values and `NULL`s become `?`, `IN` lists and inserted rows collapse to one.
+    fn fingerprint(&self, ctx: &mut SqlContext) -> error::Result<Fingerprint> {
+        let normalized = ctx.is_normalized();
+        ctx.set_normalized(true);
+        let sql = self.to_final_sql(ctx);
+        ctx.set_normalized(normalized);
+        Ok(Fingerprint::new(&sql?))
+    }
+}
+
+// Renders the query with its implicit placeholders left as markers.
+fn render<Q: ToSql + ?Sized>(query: &Q, ctx: &mut SqlContext) -> error::Result<String> {
+    let mut raw = ctx.capture(|ctx| query.write_sql(ctx));
+    raw.push(';');
+
+    match ctx.take_error() {
+        Some(err) => Err(err),
+        None => Ok(raw)
+    }
+}
+
+// Markers are delimited by PLACEHOLDER_MARK, so every odd part of the split is an index.
+fn replace_markers<F: FnMut(usize, &mut String)>(raw: &str, mut placeholder: F) -> String {
+    let mut sql = String::with_capacity(raw.len());
+    for (i, part) in raw.split(PLACEHOLDER_MARK).enumerate() {
+        match part.parse::<usize>() {
+            Ok(idx) if i % 2 == 1 => placeholder(idx, &mut sql),
+            _ => sql.push_str(part)
         }
+    }
+    sql
+}
+
+/// SQL with the values inlined, see `QueryToSql::to_debug_sql`. It is deliberately not
+/// a `String` so it can't be handed to a driver by mistake.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DebugSql(String);
+
+impl DebugSql {
+    pub fn as_str(&self) -> &str { &self.0 }
+}
 
-        sql
+impl fmt::Display for DebugSql {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// SQL of a query together with the values bound to its implicit placeholders.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompiledQuery {
+    pub sql: String,
+    /// Values numbered after explicit placeholders, in placeholder order.
+    pub params: Vec<Value>,
+    /// Named placeholders with their index in `params`, where they are `Value::Null` until bound.
+    pub named: Vec<(String, usize)>,
+}
+
+impl CompiledQuery {
+    /// Returns `params` with every named placeholder filled from `values`. Names are looked
+    /// up once each in placeholder order, so the first unbound one is reported.
+    pub fn bind<N: NamedValues + ?Sized>(&self, values: &N) -> error::Result<Vec<Value>> {
+        let mut params = self.params.clone();
+        let mut bound: Vec<(&str, Value)> = vec![];
+        for &(ref name, idx) in self.named.iter() {
+            let value = match bound.iter().find(|&&(other, _)| other == name) {
+                Some((_, value)) => value.clone(),
+                None => match values.named_value(name) {
+                    Some(value) => {
+                        bound.push((name, value.clone()));
+                        value
+                    },
+                    None => return Err(error::Error::UnboundPlaceholder { name: name.to_string() })
+                }
+            };
+            params[idx] = value;
+        }
+        Ok(params)
     }
 }
 
 pub trait ToSql {
     fn to_sql(&self, ctx: &mut SqlContext) -> String;
+
+    /// Writes the SQL into the buffer of the context. Implement it instead of
+    /// relying on `to_sql` to skip the intermediate `String`.
+    fn write_sql(&self, ctx: &mut SqlContext) {
+        let sql = self.to_sql(ctx);
+        ctx.write(&sql)
+    }
 }
 
 #[cfg(feature = "postgres")]
-pub type BoxedValue = Box<::postgres::types::ToSql + 'static>;
+pub type BoxedValue = Box<dyn postgres::types::ToSql + 'static>;
 #[cfg(not(feature = "postgres"))]
-pub type BoxedValue = Box<ToPredicateValue>;
-pub type BoxedAdapter = Box<SqlAdapter + 'static>;
+pub type BoxedValue = Box<dyn ToPredicateValue>;
+pub type BoxedAdapter = Box<dyn SqlAdapter + 'static>;
+
+pub type SharedTenant = sync::Arc<Box<dyn ToPredicateValue + 'static>>;
+
+// Delimits implicit placeholders until they get numbered, can't occur in SQL text.
+const PLACEHOLDER_MARK: char = '\u{0}';
+
+fn marker(idx: usize) -> String {
+    format!("{}{}{}", PLACEHOLDER_MARK, idx, PLACEHOLDER_MARK)
+}
 
 #[derive(Debug)]
 pub struct SqlContext {
-    impl_placeholders: u8,
-    expl_placeholders: u8,
+    impl_placeholders: usize,
+    expl_placeholders: usize,
     placeholder_data: Vec<BoxedValue>,
-    adapter: Box<SqlAdapter + 'static>,
+    values: Vec<Value>,
+    named: Vec<(String, usize)>,
+    adapter: Box<dyn SqlAdapter + 'static>,
+    tenant: Option<SharedTenant>,
+    error: Option<error::Error>,
+    buffer: String,
+    normalized: bool,
+    pretty: bool,
+    depth: usize,
 }
 
 impl SqlContext {
-    pub fn new(adapter: Box<SqlAdapter + 'static>) -> SqlContext {
+    pub fn new(adapter: Box<dyn SqlAdapter + 'static>) -> SqlContext {
         SqlContext {
-            impl_placeholders: 0u8,
-            expl_placeholders: 0u8,
+            impl_placeholders: 0,
+            expl_placeholders: 0,
             placeholder_data: vec![],
+            values: vec![],
+            named: vec![],
             adapter: adapter,
+            tenant: None,
+            error: None,
+            buffer: String::new(),
+            normalized: false,
+            pretty: false,
+            depth: 0,
         }
     }
 
-    pub fn hold(&mut self, val: BoxedValue) -> String {
+    /// Appends SQL to the buffer rendering is written to.
+    pub fn write(&mut self, sql: &str) {
+        self.buffer.push_str(sql)
+    }
+
+    /// Length of the SQL written so far.
+    pub fn written(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Renders every clause on its own line and indents nested parts.
+    pub fn set_pretty(&mut self, pretty: bool) {
+        self.pretty = pretty;
+    }
+
+    pub fn is_pretty(&self) -> bool {
+        self.pretty
+    }
+
+    /// Breaks the line at the current indentation in pretty mode, writes a space otherwise.
+    pub fn newline(&mut self) {
+        if self.pretty {
+            self.buffer.push('\n');
+            for _ in 0..self.depth { self.buffer.push_str("    ") }
+        } else {
+            self.buffer.push(' ')
+        }
+    }
+
+    /// Indents lines started by `newline` one level deeper.
+    pub fn indent(&mut self) {
+        self.depth += 1;
+    }
+
+    pub fn dedent(&mut self) {
+        self.depth -= 1;
+    }
+
+    /// Runs `f` on an empty buffer and returns what it has written.
+    pub fn capture<F: FnOnce(&mut SqlContext)>(&mut self, f: F) -> String {
+        let outer = mem::take(&mut self.buffer);
+        f(self);
+        mem::replace(&mut self.buffer, outer)
+    }
+
+    fn push_value(&mut self, val: BoxedValue, value: Value) -> usize {
         self.placeholder_data.push(val);
-        let res = format!("$${}", self.impl_placeholders);
+        self.values.push(value);
         self.impl_placeholders += 1;
-        res
+        self.impl_placeholders - 1
+    }
+
+    pub fn hold(&mut self, val: BoxedValue, value: Value) -> String {
+        let idx = self.push_value(val, value);
+        marker(idx)
+    }
+
+    /// Same as `hold`, but writes the placeholder to the buffer.
+    pub fn write_hold(&mut self, val: BoxedValue, value: Value) {
+        let idx = self.push_value(val, value);
+        self.buffer.push(PLACEHOLDER_MARK);
+        fmt::Write::write_fmt(&mut self.buffer, format_args!("{}", idx)).unwrap();
+        self.buffer.push(PLACEHOLDER_MARK);
+    }
+
+    /// Reserves an implicit placeholder for a named one, reusing its position when the adapter can.
+    pub fn hold_named(&mut self, name: &str) -> String {
+        if self.adapter.numbered_placeholders() {
+            if let Some(&(_, idx)) = self.named.iter().find(|(held, _)| held == name) {
+                return marker(idx)
+            }
+        }
+
+        let idx = self.impl_placeholders;
+        self.named.push((name.to_string(), idx));
+        // Stays NULL in the context, the value is bound later by name.
+        self.hold(Box::new(None::<String>), Value::Null)
+    }
+
+    pub fn get_named(&self) -> &[(String, usize)] {
+        &self.named
+    }
+
+    pub fn take_named(&mut self) -> Vec<(String, usize)> {
+        mem::take(&mut self.named)
+    }
+
+    /// Records the error of rendering, only the first one is kept.
+    pub fn error(&mut self, error: error::Error) {
+        if self.error.is_none() {
+            self.error = Some(error);
+        }
+    }
+
+    /// Raw SQL text, reporting `Error::NulInRaw` if it contains the NUL character which
+    /// delimits placeholders during rendering.
+    pub fn raw(&mut self, content: &str) -> String {
+        if content.contains(PLACEHOLDER_MARK) {
+            self.error(error::Error::NulInRaw { content: content.to_string() });
+        }
+        content.to_string()
+    }
+
+    pub fn get_error(&self) -> &Option<error::Error> {
+        &self.error
+    }
+
+    pub fn take_error(&mut self) -> Option<error::Error> {
+        self.error.take()
     }
 
-    pub fn get_impl_placeholders_count(&self) -> u8 {
+    pub fn get_impl_placeholders_count(&self) -> usize {
         self.impl_placeholders
     }
 
-    pub fn get_expl_placeholders_count(&self) -> u8 {
+    pub fn get_expl_placeholders_count(&self) -> usize {
         self.expl_placeholders
     }
 
@@ -85,11 +349,69 @@ impl SqlContext {
         &self.adapter
     }
 
-    pub fn expl_indexed_placeholder(&mut self, idx: u8) {
+    /// Renders every value as `?` and collapses `IN` lists, see `QueryToSql::fingerprint`.
+    pub fn set_normalized(&mut self, normalized: bool) {
+        self.normalized = normalized;
+    }
+
+    pub fn is_normalized(&self) -> bool {
+        self.normalized
+    }
+
+    pub fn expl_indexed_placeholder(&mut self, idx: usize) {
         if idx > self.expl_placeholders { self.expl_placeholders = idx; }
     }
 
     pub fn data(&self) -> &[BoxedValue] {
         &self.placeholder_data
     }
+
+    pub fn values(&self) -> &[Value] {
+        &self.values
+    }
+
+    pub fn take_values(&mut self) -> Vec<Value> {
+        mem::take(&mut self.values)
+    }
+
+    /// Sets the tenant injected into every query touching a tenant-scoped table.
+    pub fn set_tenant<V: ToPredicateValue + 'static>(&mut self, tenant: V) {
+        self.tenant = Some(sync::Arc::new(Box::new(tenant)));
+    }
+
+    pub fn unset_tenant(&mut self) {
+        self.tenant = None;
+    }
+
+    pub fn get_tenant(&self) -> &Option<SharedTenant> {
+        &self.tenant
+    }
+
+    /// Renders the tenant value, records an error if no tenant is set.
+    pub fn hold_tenant(&mut self, table_name: &str) -> String {
+        self.capture(|ctx| ctx.write_tenant(table_name))
+    }
+
+    /// Writes a `NULL` value, as `?` when normalized like any other value.
+    pub fn write_null(&mut self) {
+        self.write(if self.normalized { "?" } else { "NULL" })
+    }
+
+    /// Same as `hold_tenant`, but writes the value to the buffer.
+    pub fn write_tenant(&mut self, table_name: &str) {
+        match self.tenant.clone() {
+            Some(tenant) => tenant.write_predicate_value(self),
+            None => {
+                self.error(error::Error::MissingTenant { table: table_name.to_string() });
+                self.write("NULL")
+            }
+        }
+    }
+}
+
+impl fmt::Write for SqlContext {
+    fn write_str(&mut self, sql: &str) -> fmt::Result {
+        self.write(sql);
+        Ok(())
+    }
 }
@@ -1,11 +1,14 @@
 use super::super::placeholder;
+use super::super::visit;
 use super::value::ToPredicateValue;
 
 impl ToPredicateValue for placeholder::Placeholder {
     fn to_predicate_value(&self, ctx: &mut super::SqlContext) -> String {
         ctx.expl_indexed_placeholder(self.get_idx());
-        ctx.adapter().placeholder(self.get_idx())
+        if ctx.is_normalized() { "?".to_string() } else { ctx.adapter().placeholder(self.get_idx()) }
     }
+
+    fn value_node(&self) -> visit::Node<'_> { visit::Node::Placeholder(self) }
 }
 
 impl super::ToSql for placeholder::Placeholder {
@@ -13,3 +16,17 @@ impl super::ToSql for placeholder::Placeholder {
         self.to_predicate_value(ctx)
     }
 }
+
+impl ToPredicateValue for placeholder::NamedPlaceholder {
+    fn to_predicate_value(&self, ctx: &mut super::SqlContext) -> String {
+        ctx.hold_named(self.get_name())
+    }
+
+    fn value_node(&self) -> visit::Node<'_> { visit::Node::NamedPlaceholder(self) }
+}
+
+impl super::ToSql for placeholder::NamedPlaceholder {
+    fn to_sql(&self, ctx: &mut super::SqlContext) -> String {
+        self.to_predicate_value(ctx)
+    }
+}
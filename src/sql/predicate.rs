@@ -4,78 +4,281 @@ use super::super::predicate::{
     is_null,
     or,
     raw,
+    tenant,
     exclude,
     and,
+    all,
+    any,
     in_,
     like,
     range,
     inequality
 };
-use super::value::{self};
+use super::super::error;
+use super::super::visit;
+use super::value::{self, ToPredicateValue};
 
+/// Rendering of predicates. `negation` is only ever true for predicates that are
+/// `is_invertible`, `NOT` of any other predicate is rendered as `NOT (predicate)`.
 pub trait PredicateToSql {
     fn to_sql(&self, negation: bool, context: &mut super::SqlContext) -> String;
+
+    /// Writes the predicate into the buffer of the context, see `ToSql::write_sql`.
+    fn write_sql(&self, negation: bool, ctx: &mut super::SqlContext) {
+        let sql = self.to_sql(negation, ctx);
+        ctx.write(&sql)
+    }
+
+    /// Whether `to_sql` with `negation` renders a predicate equivalent to `NOT (predicate)`
+    /// under SQL's three-valued logic, e.g. `x != 1` for `x = 1`.
+    fn is_invertible(&self) -> bool { false }
+}
+
+// Writes `NOT predicate`, inverted when the predicate can do it and as `NOT (predicate)` otherwise.
+fn write_negated(predicate: &predicate::SharedPredicate, ctx: &mut super::SqlContext) {
+    if predicate.is_invertible() {
+        return predicate.write_sql(true, ctx)
+    }
+
+    ctx.write("NOT (");
+    match binding(&***predicate, false) {
+        Binding::Junction(_) if ctx.is_pretty() => {
+            ctx.indent();
+            ctx.newline();
+            predicate.write_sql(false, ctx);
+            ctx.dedent();
+            ctx.newline();
+        },
+        _ => predicate.write_sql(false, ctx)
+    }
+    ctx.write(")");
+}
+
+// How an operand of AND or OR binds, as rendered with the given negation.
+enum Binding {
+    Atom,
+    Junction(&'static str),
+    Opaque
+}
+
+fn binding(predicate: &dyn predicate::Predicate, negation: bool) -> Binding {
+    if negation && !predicate.is_invertible() {
+        // Rendered as `NOT (predicate)`
+        return Binding::Atom
+    }
+
+    match predicate.predicate_node() {
+        visit::PredicateNode::And(..) | visit::PredicateNode::All(..) => Binding::Junction("AND"),
+        visit::PredicateNode::Or(..) | visit::PredicateNode::Any(..) => Binding::Junction("OR"),
+        visit::PredicateNode::InRange { .. } => Binding::Junction("AND"),
+        visit::PredicateNode::Exclude(inner) => binding(&***inner, !negation),
+        visit::PredicateNode::Raw(_) | visit::PredicateNode::Other => Binding::Opaque,
+        _ => Binding::Atom
+    }
+}
+
+// Every operand is parenthesized, except in pretty mode where only the ones binding
+// looser than `op` are, on lines of their own.
+fn write_junction(left: &predicate::SharedPredicate, right: &predicate::SharedPredicate, op: &str, ctx: &mut super::SqlContext) {
+    if !ctx.is_pretty() {
+        ctx.write("(");
+        left.write_sql(false, ctx);
+        ctx.write(") ");
+        ctx.write(op);
+        ctx.write(" (");
+        right.write_sql(false, ctx);
+        ctx.write(")");
+        return
+    }
+
+    write_operand(left, op, ctx);
+    ctx.newline();
+    ctx.write(op);
+    ctx.write(" ");
+    write_operand(right, op, ctx);
+}
+
+// Operands of `AllPredicate` and `AnyPredicate` are only parenthesized when they bind looser
+// than `op`, in every mode.
+fn write_list(predicates: &[predicate::SharedPredicate], op: &str, ctx: &mut super::SqlContext) {
+    if predicates.is_empty() {
+        ctx.write(if op == "AND" { "true" } else { "false" });
+        return
+    }
+
+    for (i, predicate) in predicates.iter().enumerate() {
+        if i > 0 {
+            ctx.newline();
+            ctx.write(op);
+            ctx.write(" ");
+        }
+        write_operand(predicate, op, ctx);
+    }
+}
+
+fn write_operand(operand: &predicate::SharedPredicate, op: &str, ctx: &mut super::SqlContext) {
+    match binding(&***operand, false) {
+        Binding::Junction(inner) if inner != op && ctx.is_pretty() => {
+            ctx.write("(");
+            ctx.indent();
+            ctx.newline();
+            operand.write_sql(false, ctx);
+            ctx.dedent();
+            ctx.newline();
+            ctx.write(")");
+        },
+        Binding::Junction(inner) if inner != op => {
+            ctx.write("(");
+            operand.write_sql(false, ctx);
+            ctx.write(")");
+        },
+        Binding::Opaque => {
+            ctx.write("(");
+            operand.write_sql(false, ctx);
+            ctx.write(")");
+        },
+        _ => operand.write_sql(false, ctx)
+    }
 }
 
 impl<F: value::ToPredicateValue, T: value::ToPredicateValue> PredicateToSql for is::IsPredicate<F, T> {
     fn to_sql(&self, negation: bool, ctx: &mut super::SqlContext) -> String {
-        let op = if negation { "!=" } else { "=" };
-        format!("{} {} {}", self.get_field().to_predicate_value(ctx), op, self.get_value().to_predicate_value(ctx))
+        ctx.capture(|ctx| self.write_sql(negation, ctx))
+    }
+
+    fn write_sql(&self, negation: bool, ctx: &mut super::SqlContext) {
+        self.get_field().write_predicate_value(ctx);
+        ctx.write(if negation { " != " } else { " = " });
+        self.get_value().write_predicate_value(ctx);
     }
+
+    fn is_invertible(&self) -> bool { true }
 }
 
 impl<F: value::ToPredicateValue> PredicateToSql for is_null::IsNullPredicate<F> {
     fn to_sql(&self, negation: bool, ctx: &mut super::SqlContext) -> String {
-        let op = if !negation && self.is_null() { "IS NULL" } else { "IS NOT NULL" };
+        let op = if self.is_null() != negation { "IS NULL" } else { "IS NOT NULL" };
         format!("{} {}", self.get_field().to_predicate_value(ctx), op)
     }
+
+    fn is_invertible(&self) -> bool { true }
 }
 
 impl PredicateToSql for predicate::SharedPredicate {
     fn to_sql(&self, negation: bool, ctx: &mut super::SqlContext) -> String {
         (**self).to_sql(negation, ctx)
     }
+
+    fn write_sql(&self, negation: bool, ctx: &mut super::SqlContext) {
+        (**self).write_sql(negation, ctx)
+    }
+
+    fn is_invertible(&self) -> bool {
+        (**self).is_invertible()
+    }
 }
 
 impl PredicateToSql for or::OrPredicate {
-    fn to_sql(&self, negation: bool, ctx: &mut super::SqlContext) -> String {
-        let left = self.left.to_sql(negation, ctx);
-        let right = self.right.to_sql(negation, ctx);
-        if !negation {
-            format!("({}) OR ({})", left, right)
-        } else {
-            format!("({}) AND ({})", left, right)
-        }
+    fn to_sql(&self, _negation: bool, ctx: &mut super::SqlContext) -> String {
+        ctx.capture(|ctx| self.write_sql(false, ctx))
+    }
+
+    fn write_sql(&self, _negation: bool, ctx: &mut super::SqlContext) {
+        write_junction(&self.left, &self.right, "OR", ctx)
     }
 }
 
 impl PredicateToSql for raw::RawPredicate {
-    fn to_sql(&self, negation: bool, _ctx: &mut super::SqlContext) -> String {
-        let maybe_not = if negation { "NOT " } else { "" };
-        format!("{}{}", maybe_not, self.content.to_string())
+    fn to_sql(&self, _negation: bool, ctx: &mut super::SqlContext) -> String {
+        ctx.raw(&self.content)
     }
 }
 
 impl PredicateToSql for exclude::ExcludePredicate {
     fn to_sql(&self, negation: bool, ctx: &mut super::SqlContext) -> String {
-        self.predicate.to_sql(!negation, ctx)
+        ctx.capture(|ctx| self.write_sql(negation, ctx))
+    }
+
+    fn write_sql(&self, negation: bool, ctx: &mut super::SqlContext) {
+        if negation {
+            self.predicate.write_sql(false, ctx)
+        } else {
+            write_negated(&self.predicate, ctx)
+        }
+    }
+
+    fn is_invertible(&self) -> bool { true }
+}
+
+impl PredicateToSql for tenant::TenantPredicate {
+    fn to_sql(&self, negation: bool, ctx: &mut super::SqlContext) -> String {
+        ctx.capture(|ctx| self.write_sql(negation, ctx))
+    }
+
+    fn write_sql(&self, negation: bool, ctx: &mut super::SqlContext) {
+        self.get_field().write_predicate_value(ctx);
+        ctx.write(if negation { " != " } else { " = " });
+        ctx.write_tenant(&self.get_field().table_name)
     }
+
+    fn is_invertible(&self) -> bool { true }
 }
 
 impl PredicateToSql for and::AndPredicate {
-    fn to_sql(&self, negation: bool, ctx: &mut super::SqlContext) -> String {
-        let left = self.left.to_sql(negation, ctx);
-        let right = self.right.to_sql(negation, ctx);
-        format!("({}) AND ({})", left, right)
+    fn to_sql(&self, _negation: bool, ctx: &mut super::SqlContext) -> String {
+        ctx.capture(|ctx| self.write_sql(false, ctx))
+    }
+
+    fn write_sql(&self, _negation: bool, ctx: &mut super::SqlContext) {
+        write_junction(&self.left, &self.right, "AND", ctx)
+    }
+}
+
+impl PredicateToSql for all::AllPredicate {
+    fn to_sql(&self, _negation: bool, ctx: &mut super::SqlContext) -> String {
+        ctx.capture(|ctx| self.write_sql(false, ctx))
+    }
+
+    fn write_sql(&self, _negation: bool, ctx: &mut super::SqlContext) {
+        write_list(&self.predicates, "AND", ctx)
+    }
+}
+
+impl PredicateToSql for any::AnyPredicate {
+    fn to_sql(&self, _negation: bool, ctx: &mut super::SqlContext) -> String {
+        ctx.capture(|ctx| self.write_sql(false, ctx))
+    }
+
+    fn write_sql(&self, _negation: bool, ctx: &mut super::SqlContext) {
+        write_list(&self.predicates, "OR", ctx)
     }
 }
 
 impl<F: value::ToPredicateValue, T: value::ToPredicateValue> PredicateToSql for in_::InPredicate<F, T> {
     fn to_sql(&self, negation: bool, ctx: &mut super::SqlContext) -> String {
-        let maybe_not = if negation { "NOT " } else { "" };
-        let values = self.get_values().to_predicate_value(ctx);
-        format!("{} {}IN ({})", self.get_field().to_predicate_value(ctx), maybe_not, values)
+        ctx.capture(|ctx| self.write_sql(negation, ctx))
     }
+
+    fn write_sql(&self, negation: bool, ctx: &mut super::SqlContext) {
+        self.get_field().write_predicate_value(ctx);
+        ctx.write(if negation { " NOT IN " } else { " IN " });
+        match self.get_values().value_node() {
+            // Lists of any length share the fingerprint of the query.
+            visit::Node::List(_) if ctx.is_normalized() => ctx.write("(?)"),
+            visit::Node::Select(select) if ctx.is_pretty() => super::select::write_subquery(select, ctx),
+            _ => {
+                ctx.write("(");
+                let start = ctx.written();
+                self.get_values().write_predicate_value(ctx);
+                if ctx.written() == start {
+                    ctx.error(error::Error::EmptyInList);
+                }
+                ctx.write(")");
+            }
+        }
+    }
+
+    fn is_invertible(&self) -> bool { true }
 }
 
 impl<F: value::ToPredicateValue, T: value::ToPredicateValue> PredicateToSql for like::LikePredicate<F, T> {
@@ -85,43 +288,22 @@ impl<F: value::ToPredicateValue, T: value::ToPredicateValue> PredicateToSql for
         let values = self.get_value().to_predicate_value(ctx);
         format!("{} {}{} {}", self.get_field().to_predicate_value(ctx), maybe_not, like, values)
     }
+
+    fn is_invertible(&self) -> bool { true }
 }
 
 impl<F: value::ToPredicateValue, T1: value::ToPredicateValue, T2: value::ToPredicateValue> PredicateToSql for range::InRangePredicate<F, T1, T2> {
-    fn to_sql(&self, negation: bool, ctx: &mut super::SqlContext) -> String {
+    fn to_sql(&self, _negation: bool, ctx: &mut super::SqlContext) -> String {
         let ref name = self.get_field().to_predicate_value(ctx);
         let from = self.get_from().to_predicate_value(ctx);
         let to = self.get_to().to_predicate_value(ctx);
-        match self.get_bounds() {
-            &range::InRangeBounds::IncludeBoth => {
-                if !negation {
-                    format!("{} >= {} AND {} <= {}", name, from, name, to)
-                } else {
-                    format!("{} < {} OR {} > {}", name, from, name, to)
-                }
-            },
-            &range::InRangeBounds::ExcludeBoth => {
-                if !negation {
-                    format!("{} > {} AND {} < {}", name, from, name, to)
-                } else {
-                    format!("{} <= {} OR {} >= {}", name, from, name, to)
-                }
-            },
-            &range::InRangeBounds::ExcludeLeft => {
-                if !negation {
-                    format!("{} > {} AND {} <= {}", name, from, name, to)
-                } else {
-                    format!("{} <= {} OR {} > {}", name, from, name, to)
-                }
-            },
-            &range::InRangeBounds::ExcludeRight => {
-                if !negation {
-                    format!("{} >= {} AND {} < {}", name, from, name, to)
-                } else {
-                    format!("{} < {} OR {} >= {}", name, from, name, to)
-                }
-            }
-        }
+        let (from_op, to_op) = match *self.get_bounds() {
+            range::InRangeBounds::IncludeBoth => (">=", "<="),
+            range::InRangeBounds::ExcludeBoth => (">", "<"),
+            range::InRangeBounds::ExcludeLeft => (">", "<="),
+            range::InRangeBounds::ExcludeRight => (">=", "<")
+        };
+        format!("{} {} {} AND {} {} {}", name, from_op, from, name, to_op, to)
     }
 }
 
@@ -160,4 +342,6 @@ impl<F: value::ToPredicateValue, T: value::ToPredicateValue> PredicateToSql for
             }
         }
     }
+
+    fn is_invertible(&self) -> bool { true }
 }
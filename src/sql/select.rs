@@ -1,14 +1,39 @@
 use super::super::from;
 use super::super::select_query;
+use super::super::visit;
+use super::super::predicate::TenantPredicate;
 use super::ToSql;
-use std::fmt;
+use std::fmt::{self, Write};
 
 impl<T, L, M> super::from::FromToSql for from::FromSelect<T, L, M> {
     fn to_from_sql(&self, ctx: &mut super::SqlContext) -> String {
-        format!("({}) as {}", self.select.to_sql(ctx), self.alias.to_string())
+        ctx.capture(|ctx| self.write_from_sql(ctx))
+    }
+
+    fn write_from_sql(&self, ctx: &mut super::SqlContext) {
+        if ctx.is_pretty() {
+            write_subquery(&self.select, ctx);
+        } else {
+            ctx.write("(");
+            self.select.write_sql(ctx);
+            ctx.write(")");
+        }
+        ctx.write(" as ");
+        ctx.write(&self.alias);
     }
 }
 
+/// Writes a parenthesized subquery, indented on its own lines in pretty mode.
+pub fn write_subquery<S: ToSql + ?Sized>(select: &S, ctx: &mut super::SqlContext) {
+    ctx.write("(");
+    ctx.indent();
+    if ctx.is_pretty() { ctx.newline() }
+    select.write_sql(ctx);
+    ctx.dedent();
+    if ctx.is_pretty() { ctx.newline() }
+    ctx.write(")");
+}
+
 impl ToSql for select_query::SelectFor {
     fn to_sql(&self, _ctx: &mut super::SqlContext) -> String {
         match self {
@@ -22,53 +47,96 @@ impl ToSql for select_query::SelectFor {
 
 impl<T, L, M> ToSql for select_query::SelectQuery<T, L, M> {
     fn to_sql(&self, ctx: &mut super::SqlContext) -> String {
-        let mut sql = "SELECT".to_string();
+        ctx.capture(|ctx| self.write_sql(ctx))
+    }
+
+    fn write_sql(&self, ctx: &mut super::SqlContext) {
+        ctx.write("SELECT ");
 
         if self.get_distinct().is_some() {
-            sql = format!("{} {}", sql, self.get_distinct().as_ref().unwrap().to_sql(ctx));
+            self.get_distinct().as_ref().unwrap().write_sql(ctx);
+            ctx.write(" ");
         }
 
-        sql = format!("{} {} FROM {}",
-            sql,
-            self.get_select().to_sql(ctx),
-            self.get_from().as_sql().to_from_sql(ctx)
-        );
+        self.get_select().write_sql(ctx);
+        ctx.newline();
+        ctx.write("FROM ");
+        // Rows of the table may be extended with NULLs by a join, which `WHERE` would drop
+        let nullable = self.get_joins().iter().any(super::join::extends_preceding);
+        if nullable {
+            super::join::write_from(self.get_from(), self.get_deleted(), ctx);
+        } else {
+            self.get_from().as_sql().write_from_sql(ctx);
+        }
 
-        if !self.get_joins().is_empty() {
-            let joins: Vec<String> = self.get_joins().iter().map(|join| join.to_sql(ctx)).collect();
-            sql = format!("{} {}", sql, joins.join(" "))
+        let with_deleted = self.get_deleted() == select_query::Deleted::Include;
+        ctx.indent();
+        for join in self.get_joins().iter() {
+            ctx.newline();
+            super::join::write_join(join, with_deleted, ctx);
         }
+        ctx.dedent();
 
-        if self.get_where().is_some() {
-            sql = format!("{} WHERE {}", sql, self.get_where().as_ref().unwrap().to_sql(false, ctx));
+        let mut predicates = vec![];
+        if !nullable || self.get_from().as_table().is_none() {
+            predicates.extend(TenantPredicate::filter(self.get_from().get_tenant()));
+            predicates.extend(self.get_deleted_predicate());
+        }
+        predicates.extend(self.get_scope().clone());
+        predicates.extend(self.get_where().clone());
+
+        if let Some(predicate) = select_query::and_all(predicates) {
+            ctx.newline();
+            ctx.write("WHERE ");
+            ctx.indent();
+            predicate.write_sql(false, ctx);
+            ctx.dedent();
         }
 
         if self.get_group_by().is_some() {
-            sql = format!("{}{}", sql, self.get_group_by().as_ref().unwrap().to_sql(ctx));
+            self.get_group_by().as_ref().unwrap().write_sql(ctx);
         }
 
         if self.get_having().is_some() {
-            sql = format!("{} HAVING {}", sql, self.get_having().as_ref().unwrap().to_sql(false, ctx));
+            ctx.newline();
+            ctx.write("HAVING ");
+            ctx.indent();
+            self.get_having().as_ref().unwrap().write_sql(false, ctx);
+            ctx.dedent();
         }
 
-        if !self.get_order_by().is_empty() {
-            let orders: Vec<String> = self.get_order_by().iter().map(|ord| ord.to_sql(ctx)).collect();
-            sql = format!("{} ORDER BY {}", sql, orders.join(", "))
+        for (i, ord) in self.get_order_by().iter().enumerate() {
+            if i == 0 {
+                ctx.newline();
+                ctx.write("ORDER BY ");
+            } else {
+                ctx.write(", ");
+            }
+            ord.write_sql(ctx);
         }
 
         if self.get_limit().is_some() {
-            sql = format!("{} LIMIT {}", sql, self.get_limit().unwrap())
+            ctx.newline();
+            if ctx.is_normalized() {
+                ctx.write("LIMIT ?");
+            } else {
+                let _ = write!(ctx, "LIMIT {}", self.get_limit().unwrap());
+            }
         }
 
         if self.get_offset().is_some() {
-            sql = format!("{} OFFSET {}", sql, self.get_offset().unwrap())
+            ctx.newline();
+            if ctx.is_normalized() {
+                ctx.write("OFFSET ?");
+            } else {
+                let _ = write!(ctx, "OFFSET {}", self.get_offset().unwrap());
+            }
         }
 
         if self.get_for().is_some() {
-            sql = format!("{} {}", sql, self.get_for().as_ref().unwrap().to_sql(ctx))
+            ctx.newline();
+            self.get_for().as_ref().unwrap().write_sql(ctx);
         }
-
-        sql
     }
 }
 
@@ -78,20 +146,33 @@ impl ToSql for select_query::SharedSelectQuery {
     fn to_sql(&self, ctx: &mut super::SqlContext) -> String {
         (**self).to_sql(ctx)
     }
+
+    fn write_sql(&self, ctx: &mut super::SqlContext) {
+        (**self).write_sql(ctx)
+    }
 }
 
 impl ToSql for select_query::Select {
     fn to_sql(&self, ctx: &mut super::SqlContext) -> String {
+        ctx.capture(|ctx| self.write_sql(ctx))
+    }
+
+    fn write_sql(&self, ctx: &mut super::SqlContext) {
         match self {
             &select_query::Select::Only(ref fields) => {
-                let defs: Vec<String> = fields.iter().map(|f| f.expression_as_sql().to_sql(ctx)).collect();
-                defs.join(", ")
+                for (i, f) in fields.iter().enumerate() {
+                    if i > 0 { ctx.write(", ") }
+                    f.expression_as_sql().write_sql(ctx);
+                }
             },
-            &select_query::Select::All => "*".to_string()
+            &select_query::Select::All => ctx.write("*")
         }
     }
 }
 
-impl<T: fmt::Debug, L: fmt::Debug, M: fmt::Debug> super::ToPredicateValue for select_query::SelectQuery<T, L, M> {
+impl<T: Clone + fmt::Debug, L: Clone + fmt::Debug, M: Clone + fmt::Debug> super::ToPredicateValue for select_query::SelectQuery<T, L, M> {
     fn to_predicate_value(&self, ctx: &mut super::SqlContext) -> String { self.to_sql(ctx) }
+    fn write_predicate_value(&self, ctx: &mut super::SqlContext) { self.write_sql(ctx) }
+    fn value_node(&self) -> visit::Node<'_> { visit::Node::Select(self) }
+    fn rewrite_value(&self, rewriter: &mut dyn visit::Rewriter) -> Option<Self> { Some(self.rewrite(rewriter)) }
 }
@@ -0,0 +1,36 @@
+#[cfg(feature = "postgres")] use postgres;
+
+use super::super::error;
+#[cfg(feature = "postgres")] use super::super::model;
+use super::{CompiledQuery, NamedValues, Value};
+
+/// Query rendered once, bound with new values on every call.
+#[derive(Clone, Debug)]
+pub struct Template {
+    compiled: CompiledQuery,
+}
+
+impl Template {
+    pub fn new(compiled: CompiledQuery) -> Template {
+        Template { compiled }
+    }
+
+    pub fn get_sql(&self) -> &str { &self.compiled.sql }
+    pub fn get_params(&self) -> &[Value] { &self.compiled.params }
+    pub fn get_named(&self) -> &[(String, usize)] { &self.compiled.named }
+
+    /// Values of the query with named placeholders taken from `values`, see `CompiledQuery::bind`.
+    pub fn bind<N: NamedValues + ?Sized>(&self, values: &N) -> error::Result<Vec<Value>> {
+        self.compiled.bind(values)
+    }
+
+    /// Binds the values and runs the statement, prepared once per connection.
+    #[cfg(feature = "postgres")]
+    pub fn query<M: model::Model, N: NamedValues + ?Sized>(&self, conn: &dyn postgres::GenericConnection, values: &N) -> error::Result<Vec<M>> {
+        let params = self.bind(values)?;
+        let params: Vec<&dyn postgres::types::ToSql> = params.iter().map(|v| v as &dyn postgres::types::ToSql).collect();
+        let stmt = conn.prepare_cached(&self.compiled.sql)?;
+        let rows = stmt.query(&params)?;
+        Ok(rows.iter().map(|row| M::from_row(&row)).collect())
+    }
+}
@@ -1,6 +1,10 @@
 use super::super::update_query;
+use super::super::select_query;
+use super::super::error;
+use super::super::predicate::TenantPredicate;
 use super::from::FromToSql;
 use super::value;
+use super::predicate::PredicateToSql;
 
 impl<F: value::ToPredicateValue, T: value::ToPredicateValue> super::ToSql for update_query::FieldUpdate<F, T> {
     fn to_sql(&self, ctx: &mut super::SqlContext) -> String {
@@ -10,42 +14,63 @@ impl<F: value::ToPredicateValue, T: value::ToPredicateValue> super::ToSql for up
 
 impl<T, L, M> super::ToSql for update_query::UpdateQuery<T, L, M> {
     fn to_sql(&self, ctx: &mut super::SqlContext) -> String {
-        let mut sql = "UPDATE".to_string();
+        ctx.capture(|ctx| self.write_sql(ctx))
+    }
 
-        if self.is_only() {
-            sql = format!("{} ONLY", sql)
-        }
+    fn write_sql(&self, ctx: &mut super::SqlContext) {
+        ctx.write(if self.is_only() { "UPDATE ONLY " } else { "UPDATE " });
+        self.get_table().write_from_sql(ctx);
 
-        sql = format!("{} {}", sql, self.get_table().to_from_sql(ctx));
+        if self.get_updates().is_empty() {
+            ctx.error(error::Error::EmptyUpdate);
+        }
 
-        let updates_str: Vec<String> = self.get_updates().iter().map(|upd| upd.to_sql(ctx)).collect();
-        sql = format!("{} SET {}", sql, updates_str.join(", "));
+        ctx.newline();
+        ctx.write("SET ");
+        for (i, upd) in self.get_updates().iter().enumerate() {
+            if i > 0 { ctx.write(", ") }
+            upd.write_sql(ctx);
+        }
 
         if self.get_from().is_some() {
             let from = self.get_from().as_ref().unwrap();
-            if !from.is_empty() {
-                let tables_str: Vec<String> = from.iter().map(|v| v.as_sql().to_from_sql(ctx)).collect();
-                sql = format!("{} FROM {}", sql, tables_str.join(", "))
+            for (i, table) in from.iter().enumerate() {
+                if i == 0 {
+                    ctx.newline();
+                    ctx.write("FROM ");
+                } else {
+                    ctx.write(", ");
+                }
+                table.as_sql().write_from_sql(ctx);
             }
         }
 
-        match self.get_where().as_ref() {
-            Some(predicate) => {
-                sql = format!("{} WHERE {}", sql, predicate.to_sql(false, ctx))
-            },
-            None if !self.is_all() => {
-                // http://devopsreactions.tumblr.com/post/47352638154/almost-ran-update-without-where
-                sql = format!("{} WHERE true = false", sql)
-            },
-            _ => ()
-        }
+        if self.get_where().is_none() && !self.is_all() {
+            // http://devopsreactions.tumblr.com/post/47352638154/almost-ran-update-without-where
+            ctx.newline();
+            ctx.write("WHERE true = false")
+        } else {
+            let mut predicates = vec![];
+            predicates.extend(TenantPredicate::filter(self.get_table().get_tenant()));
+            for from in self.get_from().iter().flat_map(|tables| tables.iter()) {
+                predicates.extend(super::join::filters(from, select_query::Deleted::Exclude));
+            }
+            predicates.extend(select_query::scoped_where(self.get_scope(), self.get_where()));
 
-        match self.get_returning() {
-            &Some(ref select) => sql = format!("{} RETURNING {}", sql, select.to_sql(ctx)),
-            &None => ()
+            if let Some(predicate) = select_query::and_all(predicates) {
+                ctx.newline();
+                ctx.write("WHERE ");
+                ctx.indent();
+                predicate.write_sql(false, ctx);
+                ctx.dedent();
+            }
         }
 
-        sql
+        if let Some(ref select) = *self.get_returning() {
+            ctx.newline();
+            ctx.write("RETURNING ");
+            select.write_sql(ctx);
+        }
     }
 }
 
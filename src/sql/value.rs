@@ -2,21 +2,110 @@ use serde_json;
 use chrono;
 use uuid;
 use std::fmt;
+use std::collections::HashMap;
 #[cfg(feature = "postgres")] use postgres;
 
 use expression;
 use field;
+use visit;
 
 use super::ToSql;
 
-pub trait ToPredicateValue: fmt::Debug {
+pub trait ToPredicateValue: fmt::Debug + Send + Sync {
     fn to_predicate_value(&self, ctx: &mut super::SqlContext) -> String;
+
+    /// Writes the value into the buffer of the context, see `ToSql::write_sql`.
+    fn write_predicate_value(&self, ctx: &mut super::SqlContext) {
+        let value = self.to_predicate_value(ctx);
+        ctx.write(&value)
+    }
+
+    /// Structure of the value for visitors.
+    fn value_node(&self) -> visit::Node<'_> { visit::Node::Other }
+
+    /// Copy of the value with its subqueries rewritten, `None` if it has none.
+    fn rewrite_value(&self, _rewriter: &mut dyn visit::Rewriter) -> Option<Self> where Self: Sized { None }
+}
+
+/// Bound value of a compiled query, independent of any driver.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Value {
+    Null,
+    Bool(bool),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    F32(f32),
+    F64(f64),
+    String(String),
+    Bytes(Vec<u8>),
+    Json(serde_json::Value),
+    DateTime(chrono::NaiveDateTime),
+    Uuid(uuid::Uuid),
+}
+
+/// Source of values for named placeholders, see `CompiledQuery::bind`.
+pub trait NamedValues {
+    fn named_value(&self, name: &str) -> Option<Value>;
+}
+
+impl NamedValues for HashMap<String, Value> {
+    fn named_value(&self, name: &str) -> Option<Value> {
+        self.get(name).cloned()
+    }
+}
+
+impl NamedValues for HashMap<&str, Value> {
+    fn named_value(&self, name: &str) -> Option<Value> {
+        self.get(name).cloned()
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl postgres::types::ToSql for Value {
+    fn to_sql(&self, ty: &postgres::types::Type, out: &mut Vec<u8>) -> Result<postgres::types::IsNull, Box<dyn std::error::Error + Sync + Send>> {
+        match *self {
+            Value::Null => Ok(postgres::types::IsNull::Yes),
+            Value::Bool(ref v) => postgres::types::ToSql::to_sql_checked(v, ty, out),
+            Value::I8(ref v) => postgres::types::ToSql::to_sql_checked(v, ty, out),
+            Value::I16(ref v) => postgres::types::ToSql::to_sql_checked(v, ty, out),
+            Value::I32(ref v) => postgres::types::ToSql::to_sql_checked(v, ty, out),
+            Value::I64(ref v) => postgres::types::ToSql::to_sql_checked(v, ty, out),
+            Value::F32(ref v) => postgres::types::ToSql::to_sql_checked(v, ty, out),
+            Value::F64(ref v) => postgres::types::ToSql::to_sql_checked(v, ty, out),
+            Value::String(ref v) => postgres::types::ToSql::to_sql_checked(v, ty, out),
+            Value::Bytes(ref v) => postgres::types::ToSql::to_sql_checked(v, ty, out),
+            Value::Json(ref v) => postgres::types::ToSql::to_sql_checked(v, ty, out),
+            Value::DateTime(ref v) => postgres::types::ToSql::to_sql_checked(v, ty, out),
+            Value::Uuid(ref v) => postgres::types::ToSql::to_sql_checked(v, ty, out),
+        }
+    }
+
+    // The type is checked against the wrapped value in `to_sql`.
+    fn accepts(_: &postgres::types::Type) -> bool {
+        true
+    }
+
+    fn to_sql_checked(&self, ty: &postgres::types::Type, out: &mut Vec<u8>) -> Result<postgres::types::IsNull, Box<dyn std::error::Error + Sync + Send>> {
+        postgres::types::ToSql::to_sql(self, ty, out)
+    }
+}
+
+impl<T> From<Option<T>> for Value where Value: From<T> {
+    fn from(value: Option<T>) -> Value {
+        match value {
+            Some(value) => Value::from(value),
+            None => Value::Null
+        }
+    }
 }
 
 // Trait to connect Deuterium and rust-postgres
 #[cfg(feature = "postgres")]
 pub trait AsPostgresValue: postgres::types::ToSql + Sized {
-    fn as_postgres_value(&self) -> &postgres::types::ToSql {
+    fn as_postgres_value(&self) -> &dyn postgres::types::ToSql {
         self
     }
 }
@@ -30,52 +119,121 @@ macro_rules! to_predicate_for_field {
     )
 }
 
-impl<T: Clone + fmt::Debug> ToPredicateValue for field::NamedField<T> {
+impl<T: Clone + fmt::Debug + 'static> ToPredicateValue for field::NamedField<T> {
     fn to_predicate_value(&self, ctx: &mut super::SqlContext) -> String { self.to_sql(ctx) }
+    fn value_node(&self) -> visit::Node<'_> { visit::Node::Field(self) }
 }
 
 macro_rules! raw_value_to_predicate_value {
-    ($t:ty) => (
+    ($t:ty, $variant:ident) => (
         #[cfg(feature = "postgres")]
         impl AsPostgresValue for $t {}
 
         impl ToPredicateValue for $t {
             fn to_predicate_value(&self, ctx: &mut super::SqlContext) -> String {
-                ctx.hold(Box::new(self.clone()))
+                ctx.hold(Box::new(self.clone()), Value::$variant(self.clone()))
+            }
+
+            fn write_predicate_value(&self, ctx: &mut super::SqlContext) {
+                ctx.write_hold(Box::new(self.clone()), Value::$variant(self.clone()))
+            }
+
+            fn value_node(&self) -> visit::Node<'_> {
+                visit::Node::Value(Value::$variant(self.clone()))
+            }
+        }
+
+        impl From<$t> for Value {
+            fn from(value: $t) -> Value {
+                Value::$variant(value)
             }
         }
     )
 }
 
-raw_value_to_predicate_value!(bool);
-raw_value_to_predicate_value!(i8);
-raw_value_to_predicate_value!(i16);
-raw_value_to_predicate_value!(i32);
-raw_value_to_predicate_value!(i64);
-raw_value_to_predicate_value!(f32);
-raw_value_to_predicate_value!(f64);
-raw_value_to_predicate_value!(String);
-raw_value_to_predicate_value!(Vec<u8>);
-raw_value_to_predicate_value!(serde_json::Value);
-raw_value_to_predicate_value!(chrono::NaiveDateTime);
-raw_value_to_predicate_value!(uuid::Uuid);
+raw_value_to_predicate_value!(bool, Bool);
+raw_value_to_predicate_value!(i8, I8);
+raw_value_to_predicate_value!(i16, I16);
+raw_value_to_predicate_value!(i32, I32);
+raw_value_to_predicate_value!(i64, I64);
+raw_value_to_predicate_value!(f32, F32);
+raw_value_to_predicate_value!(f64, F64);
+raw_value_to_predicate_value!(String, String);
+raw_value_to_predicate_value!(Vec<u8>, Bytes);
+raw_value_to_predicate_value!(serde_json::Value, Json);
+raw_value_to_predicate_value!(chrono::NaiveDateTime, DateTime);
+raw_value_to_predicate_value!(uuid::Uuid, Uuid);
 
 impl ToPredicateValue for expression::RawExpression {
-    fn to_predicate_value(&self, _ctx: &mut super::SqlContext) -> String {
-        self.content.to_string()
+    fn to_predicate_value(&self, ctx: &mut super::SqlContext) -> String {
+        ctx.raw(&self.content)
     }
+
+    fn value_node(&self) -> visit::Node<'_> { visit::Node::Raw(self) }
+}
+
+impl<T> ToPredicateValue for expression::CastExpression<T> {
+    fn to_predicate_value(&self, ctx: &mut super::SqlContext) -> String {
+        self.get_expression().expression_as_sql().to_sql(ctx)
+    }
+
+    fn write_predicate_value(&self, ctx: &mut super::SqlContext) {
+        self.get_expression().expression_as_sql().write_sql(ctx)
+    }
+
+    fn value_node(&self) -> visit::Node<'_> { self.get_expression().expression_node() }
+}
+
+impl ToPredicateValue for Value {
+    fn to_predicate_value(&self, ctx: &mut super::SqlContext) -> String {
+        ctx.capture(|ctx| self.write_predicate_value(ctx))
+    }
+
+    fn write_predicate_value(&self, ctx: &mut super::SqlContext) {
+        match self {
+            &Value::Null => ctx.write_null(),
+            value => ctx.write_hold(Box::new(value.clone()), value.clone())
+        }
+    }
+
+    fn value_node(&self) -> visit::Node<'_> { visit::Node::Value(self.clone()) }
+}
+
+impl ToSql for Value {
+    fn to_sql(&self, ctx: &mut super::SqlContext) -> String { self.to_predicate_value(ctx) }
+    fn write_sql(&self, ctx: &mut super::SqlContext) { self.write_predicate_value(ctx) }
 }
 
 macro_rules! extended_impl {
     ($t:ty) => (
-        impl ToSql for $t { fn to_sql(&self, ctx: &mut super::SqlContext) -> String { self.to_predicate_value(ctx) } }
-        impl ToSql for Option<$t> { fn to_sql(&self, ctx: &mut super::SqlContext) -> String { self.to_predicate_value(ctx) } }
+        impl ToSql for $t {
+            fn to_sql(&self, ctx: &mut super::SqlContext) -> String { self.to_predicate_value(ctx) }
+            fn write_sql(&self, ctx: &mut super::SqlContext) { self.write_predicate_value(ctx) }
+        }
+        impl ToSql for Option<$t> {
+            fn to_sql(&self, ctx: &mut super::SqlContext) -> String { self.to_predicate_value(ctx) }
+            fn write_sql(&self, ctx: &mut super::SqlContext) { self.write_predicate_value(ctx) }
+        }
 
         impl ToPredicateValue for Option<$t> {
             fn to_predicate_value(&self, ctx: &mut super::SqlContext) -> String {
                 match self {
                     &Some(ref predicate) => predicate.to_predicate_value(ctx),
-                    &None => "NULL".to_string()
+                    &None => ctx.capture(|ctx| ctx.write_null())
+                }
+            }
+
+            fn write_predicate_value(&self, ctx: &mut super::SqlContext) {
+                match self {
+                    &Some(ref predicate) => predicate.write_predicate_value(ctx),
+                    &None => ctx.write_null()
+                }
+            }
+
+            fn value_node(&self) -> visit::Node<'_> {
+                match self {
+                    &Some(ref value) => value.value_node(),
+                    &None => visit::Node::Value(Value::Null)
                 }
             }
         }
@@ -100,8 +258,18 @@ extended_impl!(expression::RawExpression);
 
 impl<T: ToPredicateValue> ToPredicateValue for Vec<T> {
     fn to_predicate_value(&self, ctx: &mut super::SqlContext) -> String {
-        let values: Vec<String> = self.iter().map(|v| v.to_predicate_value(ctx)).collect();
-        values.join(", ")
+        ctx.capture(|ctx| self.write_predicate_value(ctx))
+    }
+
+    fn write_predicate_value(&self, ctx: &mut super::SqlContext) {
+        for (i, value) in self.iter().enumerate() {
+            if i > 0 { ctx.write(", ") }
+            value.write_predicate_value(ctx);
+        }
+    }
+
+    fn value_node(&self) -> visit::Node<'_> {
+        visit::Node::List(self.iter().map(|value| value.value_node()).collect())
     }
 }
 
@@ -109,4 +277,8 @@ impl<T: ToPredicateValue> ToSql for Vec<T> {
     fn to_sql(&self, ctx: &mut super::SqlContext) -> String {
         self.to_predicate_value(ctx)
     }
+
+    fn write_sql(&self, ctx: &mut super::SqlContext) {
+        self.write_predicate_value(ctx)
+    }
 }
@@ -0,0 +1,201 @@
+use std::{any, fmt, ops};
+
+use super::field::{self, Field};
+use super::from::Table;
+use super::expression::RawExpression;
+use super::insert_query::InsertValue;
+use super::model::Model;
+use super::predicate::ToAndPredicate;
+use super::select_query::{self, Queryable};
+use super::update_query::{self, FieldUpd};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TrackedError {
+    /// The field belongs to another table than the model.
+    ForeignColumn { column: String, table: String },
+    /// The model has no such column or its type differs.
+    UnknownColumn(String),
+    /// The column is part of the primary key, which the update matches the row by.
+    PrimaryKey(String),
+    /// The version column is bumped by the update and can't be set.
+    VersionColumn(String),
+    /// The version column isn't `i16`, `i32` or `i64`.
+    UnsupportedVersion(String)
+}
+
+impl fmt::Display for TrackedError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TrackedError::ForeignColumn { ref column, ref table } => {
+                write!(f, "column `{}` belongs to table `{}`, not to the model", column, table)
+            },
+            TrackedError::UnknownColumn(ref column) => write!(f, "model has no column `{}` of this type", column),
+            TrackedError::PrimaryKey(ref column) => write!(f, "primary key column `{}` can't be set", column),
+            TrackedError::VersionColumn(ref column) => write!(f, "version column `{}` can't be set", column),
+            TrackedError::UnsupportedVersion(ref column) => {
+                write!(f, "version column `{}` must be i16, i32 or i64", column)
+            },
+        }
+    }
+}
+
+pub type TrackedResult<T> = Result<T, TrackedError>;
+
+/// Change-tracking wrapper around a model.
+///
+/// Remembers columns assigned through `set` and builds an `UPDATE` with only
+/// those columns.
+#[derive(Clone, Debug)]
+pub struct Tracked<M> {
+    model: M,
+    changed: Vec<String>,
+    version: Option<String>,
+}
+
+impl<M: Model> Tracked<M> {
+    pub fn new(model: M) -> Tracked<M> {
+        Tracked {
+            model,
+            changed: vec![],
+            version: None,
+        }
+    }
+
+    /// Enables optimistic locking on the version column: the update bumps it
+    /// and only matches the row with the current version. The column must be
+    /// an `i16`, `i32` or `i64` of the model.
+    pub fn with_version(mut self, version: &dyn Field) -> TrackedResult<Tracked<M>> {
+        let name = version.name().to_string();
+        check_table::<M>(version)?;
+        if is_primary_key::<M>(&name) {
+            return Err(TrackedError::PrimaryKey(name))
+        }
+        match self.model.value_of(&name) {
+            Some(value) if bump(value).is_some() => (),
+            Some(_) => return Err(TrackedError::UnsupportedVersion(name)),
+            None => return Err(TrackedError::UnknownColumn(name))
+        }
+
+        self.changed.retain(|column| *column != name);
+        self.version = Some(name);
+        Ok(self)
+    }
+
+    pub fn get_changed(&self) -> &Vec<String> { &self.changed }
+    pub fn get_version(&self) -> &Option<String> { &self.version }
+
+    pub fn is_changed(&self) -> bool {
+        !self.changed.is_empty()
+    }
+
+    /// Assigns the value and marks the column as changed. Primary key columns can't
+    /// be set, the update matches the row by them.
+    pub fn set<T: Clone + any::Any>(&mut self, field: &field::NamedField<T>, value: T) -> TrackedResult<&mut Tracked<M>> {
+        let name = field.name().to_string();
+        check_table::<M>(field)?;
+        match self.model.value_of(&name) {
+            Some(current) if current.is::<T>() => (),
+            _ => return Err(TrackedError::UnknownColumn(name))
+        }
+        if is_primary_key::<M>(&name) {
+            return Err(TrackedError::PrimaryKey(name))
+        }
+        if self.version.as_ref() == Some(&name) {
+            return Err(TrackedError::VersionColumn(name))
+        }
+
+        self.model.assign(&name, &value);
+
+        if !self.changed.contains(&name) {
+            self.changed.push(name);
+        }
+
+        Ok(self)
+    }
+
+    /// Forgets all changes without saving them.
+    pub fn reset(&mut self) {
+        self.changed.clear();
+    }
+
+    /// Forgets all changes after the update was executed and matched the row, and
+    /// bumps the version of the model like the update did in the database.
+    pub fn saved(&mut self) {
+        if !self.changed.is_empty() {
+            if let Some(ref version) = self.version {
+                let next = self.model.value_of(version).and_then(bump);
+                if let Some(next) = next {
+                    self.model.assign(version, &*next);
+                }
+            }
+        }
+        self.changed.clear();
+    }
+
+    pub fn into_inner(self) -> M {
+        self.model
+    }
+
+    /// Builds an `UPDATE` of the changed columns, `None` if nothing was changed.
+    pub fn update_query(&self) -> Option<update_query::UpdateQuery<(), select_query::NoResult, M>> {
+        if self.changed.is_empty() {
+            return None
+        }
+
+        let mut query = update_query::UpdateQuery::new(&M::table());
+        for column in self.changed.iter() {
+            if let Some(update) = self.model.field_update(column) {
+                query = query.field_update(update);
+            }
+        }
+
+        let mut where_ = self.model.primary_key_predicate();
+        if let Some(ref version) = self.version {
+            let bump = update_query::FieldUpdate {
+                field: RawExpression::new(version),
+                value: InsertValue::new(&RawExpression::new(&format!("{} + 1", version)))
+            };
+            query = query.field_update(bump.upcast_field_update());
+
+            // `with_version` checked that the model has the column.
+            if let Some(current) = self.model.column_predicate(version) {
+                where_ = where_.and(current);
+            }
+        }
+
+        Some(query.where_(where_))
+    }
+}
+
+fn check_table<M: Model>(field: &dyn Field) -> TrackedResult<()> {
+    if field.table_name() != M::table().get_table_name() {
+        return Err(TrackedError::ForeignColumn {
+            column: field.name().to_string(),
+            table: field.table_name().to_string()
+        })
+    }
+    Ok(())
+}
+
+fn is_primary_key<M: Model>(column: &str) -> bool {
+    M::primary_key().iter().any(|field| field.name() == column)
+}
+
+// Next value of a version column.
+fn bump(value: &dyn any::Any) -> Option<Box<dyn any::Any>> {
+    if let Some(value) = value.downcast_ref::<i16>() {
+        return Some(Box::new(value + 1))
+    }
+    if let Some(value) = value.downcast_ref::<i32>() {
+        return Some(Box::new(value + 1))
+    }
+    value.downcast_ref::<i64>().map(|value| Box::new(value + 1) as Box<dyn any::Any>)
+}
+
+impl<M> ops::Deref for Tracked<M> {
+    type Target = M;
+
+    fn deref(&self) -> &M {
+        &self.model
+    }
+}
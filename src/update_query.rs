@@ -1,5 +1,4 @@
-use std::mem;
-use std::rc;
+use std::sync;
 use std::marker;
 use std::fmt;
 
@@ -10,12 +9,24 @@ use super::predicate;
 use super::expression;
 use super::sql;
 use super::field;
+use super::model;
+use super::visit;
 
-pub trait FieldUpd: sql::ToSql + fmt::Debug {
+pub trait FieldUpd: sql::ToSql + fmt::Debug + Send + Sync {
     fn upcast_field_update(&self) -> SharedFieldUpdate;
+
+    /// Updated column for visitors.
+    fn field_node(&self) -> visit::Node<'_> { visit::Node::Other }
+
+    /// Assigned value for visitors.
+    fn value_node(&self) -> visit::Node<'_> { visit::Node::Other }
+
+    /// Copy of the update with its assigned value rewritten, see `visit::rewrite_update`.
+    fn rewrite_value(&self, _rewriter: &mut dyn visit::Rewriter) -> SharedFieldUpdate { self.upcast_field_update() }
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(bound(serialize = "F: ::serde::Serialize", deserialize = "F: ::serde::Deserialize<'de>")))]
 pub struct FieldUpdate<F, T: fmt::Debug> {
     pub field: F,
     pub value: insert_query::InsertValue<T>
@@ -31,14 +42,26 @@ impl<F, T: fmt::Debug> FieldUpdate<F, T> {
     }
 }
 
-type BoxedFieldUpdate = Box<FieldUpd + 'static>;
-type SharedFieldUpdate = rc::Rc<BoxedFieldUpdate>;
+pub type BoxedFieldUpdate = Box<dyn FieldUpd + 'static>;
+pub type SharedFieldUpdate = sync::Arc<BoxedFieldUpdate>;
 
 impl<F, T> FieldUpd for FieldUpdate<F, T>
     where F: Clone + sql::ToPredicateValue + 'static,
           T: Clone + sql::ToPredicateValue + 'static {
     fn upcast_field_update(&self) -> SharedFieldUpdate {
-        rc::Rc::new(Box::new(self.clone()))
+        sync::Arc::new(Box::new(self.clone()))
+    }
+
+    fn field_node(&self) -> visit::Node<'_> {
+        self.field.value_node()
+    }
+
+    fn value_node(&self) -> visit::Node<'_> {
+        self.value.value_node()
+    }
+
+    fn rewrite_value(&self, rewriter: &mut dyn visit::Rewriter) -> SharedFieldUpdate {
+        FieldUpdate { field: self.field.clone(), value: self.value.rewrite(rewriter) }.upcast_field_update()
     }
 }
 
@@ -83,21 +106,38 @@ pub trait Updatable<M>: from::Table + Sized {
     fn update(&self) -> UpdateQuery<(), select_query::NoResult, M> {
         UpdateQuery::new(self)
     }
+
+    fn update_from(&self, model: &M) -> UpdateQuery<(), select_query::NoResult, M> where M: model::Model {
+        let mut query = UpdateQuery::new(self);
+        query.updates = model.field_updates();
+        query.where_ = Some(model.primary_key_predicate());
+        query
+    }
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct UpdateQuery<T, L, M> {
     only: bool,
+    #[cfg_attr(feature = "serde", serde(with = "::serialize::shared"))]
     table: from::SharedTable,
+    #[cfg_attr(feature = "serde", serde(with = "::serialize::shared"))]
     updates: Vec<SharedFieldUpdate>,
+    #[cfg_attr(feature = "serde", serde(with = "::serialize::shared"))]
     from: Option<Vec<from::SharedFrom>>,
+    #[cfg_attr(feature = "serde", serde(with = "::serialize::shared"))]
+    scope: Option<predicate::SharedPredicate>,
+    #[cfg_attr(feature = "serde", serde(with = "::serialize::shared"))]
     where_: Option<predicate::SharedPredicate>,
     all: bool,
     returning: Option<select_query::Select>,
 
-    _marker_t: marker::PhantomData<T>,
-    _marker_l: marker::PhantomData<L>,
-    _marker_m: marker::PhantomData<M>
+    #[cfg_attr(feature = "serde", serde(skip))]
+    _marker_t: marker::PhantomData<fn() -> T>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    _marker_l: marker::PhantomData<fn() -> L>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    _marker_m: marker::PhantomData<fn() -> M>
 }
 
 impl<T, L, M> UpdateQuery<T, L, M> {
@@ -107,17 +147,37 @@ impl<T, L, M> UpdateQuery<T, L, M> {
     pub fn get_table(&self) -> &from::SharedTable { &self.table }
     pub fn get_updates(&self) -> &Vec<SharedFieldUpdate> { &self.updates }
     pub fn get_from(&self) -> &Option<Vec<from::SharedFrom>> { &self.from }
+    pub fn get_scope(&self) -> &Option<predicate::SharedPredicate> { &self.scope }
     pub fn get_where(&self) -> &Option<predicate::SharedPredicate> { &self.where_ }
     pub fn get_returning(&self) -> &Option<select_query::Select> { &self.returning }
 }
 
 impl<T, L, M> UpdateQuery<T, L, M> {
-    pub fn new(table: &from::Table) -> UpdateQuery<T, L, M> {
+    // Rebuilds the query with other type parameters
+    fn retype<T2, L2, M2>(self) -> UpdateQuery<T2, L2, M2> {
+        UpdateQuery {
+            only: self.only,
+            table: self.table,
+            updates: self.updates,
+            from: self.from,
+            scope: self.scope,
+            where_: self.where_,
+            all: self.all,
+            returning: self.returning,
+
+            _marker_t: marker::PhantomData,
+            _marker_l: marker::PhantomData,
+            _marker_m: marker::PhantomData,
+        }
+    }
+
+    pub fn new(table: &dyn from::Table) -> UpdateQuery<T, L, M> {
         UpdateQuery {
             only: false,
             table: table.upcast_table(),
             updates: vec![],
             from: None,
+            scope: select_query::and_all(table.get_scopes()),
             where_: None,
             all: false,
             returning: None,
@@ -133,7 +193,7 @@ impl<T, L, M> UpdateQuery<T, L, M> {
         self
     }
 
-    pub fn from(mut self, from: &from::From) -> UpdateQuery<T, L, M> {
+    pub fn from(mut self, from: &dyn from::From) -> UpdateQuery<T, L, M> {
         if self.from.is_none() {
             self.from = Some(vec![])
         }
@@ -147,11 +207,26 @@ impl<T, L, M> UpdateQuery<T, L, M> {
         self
     }
 
+    pub fn field_update(mut self, update: SharedFieldUpdate) -> UpdateQuery<T, L, M> {
+        self.updates.push(update);
+        self
+    }
+
     pub fn all(mut self) -> UpdateQuery<T, L, M> {
         self.where_ = None;
         self.all = true;
         self
     }
+
+    /// Rebuilds the assignments, sources, predicates and returned expressions of the query with `rewriter`.
+    pub fn rewrite<R: visit::Rewriter + ?Sized>(mut self, rewriter: &mut R) -> UpdateQuery<T, L, M> {
+        self.updates = self.updates.iter().map(|update| visit::rewrite_update(rewriter, update)).collect();
+        self.from = self.from.map(|from| from.into_iter().map(|f| rewriter.rewrite_from(f)).collect());
+        self.scope = self.scope.map(|p| visit::rewrite_predicate(rewriter, &p));
+        self.where_ = self.where_.map(|p| visit::rewrite_predicate(rewriter, &p));
+        self.returning = self.returning.map(|returning| visit::rewrite_select_list(rewriter, &returning));
+        self
+    }
 }
 
 returning_for!(UpdateQuery);
@@ -161,3 +236,8 @@ impl<T:Clone, L:Clone, M:Clone> select_query::Queryable for UpdateQuery<T, L, M>
     fn set_where(&mut self, predicate: predicate::SharedPredicate) { self.where_ = Some(predicate); }
     fn unset_where(&mut self) { self.where_ = None; }
 }
+
+impl<T:Clone, L:Clone, M:Clone> select_query::Scoped for UpdateQuery<T, L, M> {
+    fn get_scope(&self) -> &Option<predicate::SharedPredicate> { &self.scope }
+    fn unset_scope(&mut self) { self.scope = None; }
+}
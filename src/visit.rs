@@ -0,0 +1,410 @@
+use std::fmt;
+
+use super::expression;
+use super::field;
+use super::from;
+use super::join;
+use super::placeholder;
+use super::predicate::{self, ToSharedPredicate};
+use super::select_query::{self, AbstractSelectQuery};
+use super::insert_query;
+use super::update_query;
+use super::delete_query;
+use super::sql::Value;
+
+/// Structural view of an expression or of a value taking part in a predicate.
+pub enum Node<'a> {
+    Field(&'a dyn field::Field),
+    /// Literal bound as a placeholder.
+    Value(Value),
+    Raw(&'a expression::RawExpression),
+    Placeholder(&'a placeholder::Placeholder),
+    NamedPlaceholder(&'a placeholder::NamedPlaceholder),
+    List(Vec<Node<'a>>),
+    Select(&'a dyn AbstractSelectQuery),
+    /// Aggregate function by name, `COUNT(*)` has no arguments.
+    Function(&'static str, Vec<Node<'a>>),
+    /// `DEFAULT` of inserts and updates.
+    Default,
+    /// Node deuterium doesn't know the structure of.
+    Other
+}
+
+impl<'a> fmt::Debug for Node<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Node::Field(field) => write!(f, "Field({:?})", field.name()),
+            Node::Value(ref value) => write!(f, "Value({:?})", value),
+            Node::Raw(raw) => write!(f, "Raw({:?})", raw.content),
+            Node::Placeholder(placeholder) => write!(f, "Placeholder({:?})", placeholder),
+            Node::NamedPlaceholder(placeholder) => write!(f, "NamedPlaceholder({:?})", placeholder),
+            Node::List(ref nodes) => write!(f, "List({:?})", nodes),
+            Node::Select(_) => write!(f, "Select(..)"),
+            Node::Function(name, ref args) => write!(f, "Function({:?}, {:?})", name, args),
+            Node::Default => write!(f, "Default"),
+            Node::Other => write!(f, "Other"),
+        }
+    }
+}
+
+/// Structural view of a predicate.
+#[derive(Debug)]
+pub enum PredicateNode<'a> {
+    Is { field: Node<'a>, value: Node<'a> },
+    IsNull { field: Node<'a>, is_null: bool },
+    In { field: Node<'a>, values: Node<'a> },
+    Like { field: Node<'a>, value: Node<'a>, case_sensitive: bool },
+    InRange { field: Node<'a>, from: Node<'a>, to: Node<'a>, bounds: predicate::InRangeBounds },
+    Inequality { field: Node<'a>, value: Node<'a>, inequality: predicate::Inequality },
+    And(&'a predicate::SharedPredicate, &'a predicate::SharedPredicate),
+    Or(&'a predicate::SharedPredicate, &'a predicate::SharedPredicate),
+    All(&'a [predicate::SharedPredicate]),
+    Any(&'a [predicate::SharedPredicate]),
+    Exclude(&'a predicate::SharedPredicate),
+    Raw(&'a predicate::RawPredicate),
+    Tenant(&'a predicate::TenantPredicate),
+    Other
+}
+
+// Node of plain Rust values and raw expressions.
+pub trait ToNode {
+    fn to_node(&self) -> Node<'_>;
+}
+
+macro_rules! value_to_node {
+    ($t:ty) => (
+        impl ToNode for $t {
+            fn to_node(&self) -> Node<'_> { Node::Value(Value::from(self.clone())) }
+        }
+
+        impl ToNode for Option<$t> {
+            fn to_node(&self) -> Node<'_> { Node::Value(Value::from(self.clone())) }
+        }
+    )
+}
+
+value_to_node!(bool);
+value_to_node!(i8);
+value_to_node!(i16);
+value_to_node!(i32);
+value_to_node!(i64);
+value_to_node!(f32);
+value_to_node!(f64);
+value_to_node!(String);
+value_to_node!(Vec<u8>);
+value_to_node!(::serde_json::Value);
+value_to_node!(::chrono::NaiveDateTime);
+value_to_node!(::uuid::Uuid);
+
+impl ToNode for expression::RawExpression {
+    fn to_node(&self) -> Node<'_> { Node::Raw(self) }
+}
+
+impl ToNode for Value {
+    fn to_node(&self) -> Node<'_> { Node::Value(self.clone()) }
+}
+
+impl ToNode for Option<expression::RawExpression> {
+    fn to_node(&self) -> Node<'_> {
+        match *self {
+            Some(ref raw) => Node::Raw(raw),
+            None => Node::Value(Value::Null)
+        }
+    }
+}
+
+/// Walks the query AST. Every method visits the children by default, override
+/// the ones of interest and call the matching `walk_*` function to keep descending.
+pub trait Visitor {
+    fn visit_select(&mut self, query: &dyn AbstractSelectQuery) { walk_select(self, query) }
+    fn visit_from(&mut self, from: &dyn from::From) { walk_from(self, from) }
+    fn visit_table(&mut self, _table: &dyn from::Table) { }
+    fn visit_join(&mut self, join: &join::Join) { walk_join(self, join) }
+    fn visit_predicate(&mut self, predicate: &dyn predicate::Predicate) { walk_predicate(self, predicate) }
+    fn visit_update(&mut self, update: &dyn update_query::FieldUpd) { walk_update(self, update) }
+    fn visit_node(&mut self, node: &Node) { walk_node(self, node) }
+    fn visit_field(&mut self, _field: &dyn field::Field) { }
+    fn visit_value(&mut self, _value: &Value) { }
+    fn visit_placeholder(&mut self, _placeholder: &placeholder::Placeholder) { }
+    fn visit_named_placeholder(&mut self, _placeholder: &placeholder::NamedPlaceholder) { }
+}
+
+pub fn walk_select<V: Visitor + ?Sized>(visitor: &mut V, query: &dyn AbstractSelectQuery) {
+    let parts = query.get_parts();
+
+    if let Some(ref distinct) = *parts.distinct {
+        for expression in distinct.get_on().iter().flat_map(|on| on.iter()) {
+            visitor.visit_node(&expression.expression_node())
+        }
+    }
+
+    walk_select_list(visitor, parts.select);
+    visitor.visit_from(&***parts.from);
+
+    for join in parts.joins.iter() {
+        visitor.visit_join(join);
+    }
+
+    for predicate in parts.scope.iter().chain(parts.where_.iter()) {
+        visitor.visit_predicate(&***predicate);
+    }
+
+    for group_by in parts.group_by.iter() {
+        for expression in group_by.get_by().iter() {
+            visitor.visit_node(&expression.expression_node());
+        }
+    }
+
+    for having in parts.having.iter() {
+        visitor.visit_predicate(&***having);
+    }
+
+    for order_by in parts.order_by.iter() {
+        visitor.visit_node(&order_by.get_by().expression_node());
+    }
+}
+
+/// Walks selected expressions, also used for `RETURNING`.
+pub fn walk_select_list<V: Visitor + ?Sized>(visitor: &mut V, select: &select_query::Select) {
+    match *select {
+        select_query::Select::Only(ref expressions) => for expression in expressions.iter() {
+            visitor.visit_node(&expression.expression_node())
+        },
+        select_query::Select::All => ()
+    }
+}
+
+pub fn walk_from<V: Visitor + ?Sized>(visitor: &mut V, from: &dyn from::From) {
+    match (from.as_table(), from.as_select()) {
+        (Some(table), _) => visitor.visit_table(table),
+        (None, Some(select)) => visitor.visit_select(select),
+        (None, None) => ()
+    }
+}
+
+pub fn walk_join<V: Visitor + ?Sized>(visitor: &mut V, join: &join::Join) {
+    match *join {
+        join::Join::ConditionedJoin { ref from, ref on, .. } => {
+            visitor.visit_from(&***from);
+            visitor.visit_predicate(&***on);
+        },
+        join::Join::UnconditionedJoin { ref from, .. } => visitor.visit_from(&***from)
+    }
+}
+
+pub fn walk_predicate<V: Visitor + ?Sized>(visitor: &mut V, predicate: &dyn predicate::Predicate) {
+    match predicate.predicate_node() {
+        PredicateNode::Is { field, value } | PredicateNode::Like { field, value, .. } |
+        PredicateNode::Inequality { field, value, .. } => {
+            visitor.visit_node(&field);
+            visitor.visit_node(&value);
+        },
+        PredicateNode::IsNull { field, .. } => visitor.visit_node(&field),
+        PredicateNode::In { field, values } => {
+            visitor.visit_node(&field);
+            visitor.visit_node(&values);
+        },
+        PredicateNode::InRange { field, from, to, .. } => {
+            visitor.visit_node(&field);
+            visitor.visit_node(&from);
+            visitor.visit_node(&to);
+        },
+        PredicateNode::And(left, right) | PredicateNode::Or(left, right) => {
+            visitor.visit_predicate(&***left);
+            visitor.visit_predicate(&***right);
+        },
+        PredicateNode::All(predicates) | PredicateNode::Any(predicates) => for predicate in predicates.iter() {
+            visitor.visit_predicate(&***predicate)
+        },
+        PredicateNode::Exclude(predicate) => visitor.visit_predicate(&***predicate),
+        PredicateNode::Tenant(tenant) => visitor.visit_field(tenant.get_field()),
+        PredicateNode::Raw(_) | PredicateNode::Other => ()
+    }
+}
+
+pub fn walk_update<V: Visitor + ?Sized>(visitor: &mut V, update: &dyn update_query::FieldUpd) {
+    visitor.visit_node(&update.field_node());
+    visitor.visit_node(&update.value_node());
+}
+
+pub fn walk_node<V: Visitor + ?Sized>(visitor: &mut V, node: &Node) {
+    match *node {
+        Node::Field(field) => visitor.visit_field(field),
+        Node::Value(ref value) => visitor.visit_value(value),
+        Node::List(ref nodes) | Node::Function(_, ref nodes) => for node in nodes.iter() {
+            visitor.visit_node(node)
+        },
+        Node::Select(select) => visitor.visit_select(select),
+        Node::Placeholder(placeholder) => visitor.visit_placeholder(placeholder),
+        Node::NamedPlaceholder(placeholder) => visitor.visit_named_placeholder(placeholder),
+        Node::Raw(_) | Node::Default | Node::Other => ()
+    }
+}
+
+/// Query that can be walked by a `Visitor`.
+pub trait Visit {
+    fn accept<V: Visitor + ?Sized>(&self, visitor: &mut V);
+}
+
+impl<T: Clone, L: Clone, M: Clone> Visit for select_query::SelectQuery<T, L, M> {
+    fn accept<V: Visitor + ?Sized>(&self, visitor: &mut V) {
+        visitor.visit_select(self)
+    }
+}
+
+impl<T: Clone, V: Clone + insert_query::InsertRow, M: Clone, RT: Clone, RL: Clone> Visit for insert_query::InsertQuery<T, V, M, RT, RL> {
+    fn accept<Vi: Visitor + ?Sized>(&self, visitor: &mut Vi) {
+        visitor.visit_table(&***self.get_into());
+
+        for col in self.get_cols().iter().flat_map(|cols| cols.iter()) {
+            visitor.visit_field(&***col);
+        }
+
+        match *self.get_values() {
+            insert_query::Insert::DefaultValues => (),
+            insert_query::Insert::Values(ref rows) => for row in rows.iter() {
+                for node in row.row_nodes().iter() {
+                    visitor.visit_node(node);
+                }
+            },
+            insert_query::Insert::UntypedValues(ref rows) => for row in rows.iter() {
+                for value in row.iter() {
+                    visitor.visit_node(&value.value_node());
+                }
+            },
+            insert_query::Insert::FromSelect(ref select) => visitor.visit_select(select)
+        }
+
+        for returning in self.get_returning().iter() {
+            walk_select_list(visitor, returning);
+        }
+    }
+}
+
+impl<T, L, M> Visit for update_query::UpdateQuery<T, L, M> {
+    fn accept<V: Visitor + ?Sized>(&self, visitor: &mut V) {
+        visitor.visit_table(&***self.get_table());
+
+        for update in self.get_updates().iter() {
+            visitor.visit_update(&***update);
+        }
+
+        for from in self.get_from().iter().flat_map(|from| from.iter()) {
+            visitor.visit_from(&***from);
+        }
+
+        for predicate in self.get_scope().iter().chain(self.get_where().iter()) {
+            visitor.visit_predicate(&***predicate);
+        }
+
+        for returning in self.get_returning().iter() {
+            walk_select_list(visitor, returning);
+        }
+    }
+}
+
+impl<T, L, M> Visit for delete_query::DeleteQuery<T, L, M> {
+    fn accept<V: Visitor + ?Sized>(&self, visitor: &mut V) {
+        visitor.visit_table(&***self.get_table());
+
+        for from in self.get_using().iter().flat_map(|using| using.iter()) {
+            visitor.visit_from(&***from);
+        }
+
+        for predicate in self.get_scope().iter().chain(self.get_where().iter()) {
+            visitor.visit_predicate(&***predicate);
+        }
+
+        for returning in self.get_returning().iter() {
+            walk_select_list(visitor, returning);
+        }
+    }
+}
+
+/// Rebuilds parts of a query. Rewriting is bottom-up: children of `AND`, `OR` and
+/// `NOT`, subqueries of operands and assigned values first, then the rebuilt part itself.
+pub trait Rewriter {
+    fn rewrite_predicate(&mut self, predicate: predicate::SharedPredicate) -> predicate::SharedPredicate { predicate }
+    fn rewrite_from(&mut self, from: from::SharedFrom) -> from::SharedFrom { from }
+    fn rewrite_expression(&mut self, expression: expression::SharedExpression) -> expression::SharedExpression { expression }
+    fn rewrite_update(&mut self, update: update_query::SharedFieldUpdate) -> update_query::SharedFieldUpdate { update }
+}
+
+// Passes a possibly unsized rewriter where a trait object is expected.
+struct DynRewriter<'a, R: Rewriter + ?Sized + 'a>(&'a mut R);
+
+impl<'a, R: Rewriter + ?Sized + 'a> Rewriter for DynRewriter<'a, R> {
+    fn rewrite_predicate(&mut self, predicate: predicate::SharedPredicate) -> predicate::SharedPredicate {
+        self.0.rewrite_predicate(predicate)
+    }
+
+    fn rewrite_from(&mut self, from: from::SharedFrom) -> from::SharedFrom {
+        self.0.rewrite_from(from)
+    }
+
+    fn rewrite_expression(&mut self, expression: expression::SharedExpression) -> expression::SharedExpression {
+        self.0.rewrite_expression(expression)
+    }
+
+    fn rewrite_update(&mut self, update: update_query::SharedFieldUpdate) -> update_query::SharedFieldUpdate {
+        self.0.rewrite_update(update)
+    }
+}
+
+pub fn rewrite_predicate<R: Rewriter + ?Sized>(rewriter: &mut R, predicate: &predicate::SharedPredicate) -> predicate::SharedPredicate {
+    let rebuilt = match predicate.predicate_node() {
+        PredicateNode::And(left, right) => predicate::AndPredicate {
+            left: rewrite_predicate(rewriter, left),
+            right: rewrite_predicate(rewriter, right)
+        }.upcast(),
+        PredicateNode::Or(left, right) => predicate::OrPredicate {
+            left: rewrite_predicate(rewriter, left),
+            right: rewrite_predicate(rewriter, right)
+        }.upcast(),
+        PredicateNode::All(predicates) => predicate::AllPredicate {
+            predicates: predicates.iter().map(|p| rewrite_predicate(rewriter, p)).collect()
+        }.upcast(),
+        PredicateNode::Any(predicates) => predicate::AnyPredicate {
+            predicates: predicates.iter().map(|p| rewrite_predicate(rewriter, p)).collect()
+        }.upcast(),
+        PredicateNode::Exclude(inner) => predicate::ExcludePredicate {
+            predicate: rewrite_predicate(rewriter, inner)
+        }.upcast(),
+        _ => predicate.rewrite_operands(&mut DynRewriter(rewriter)).unwrap_or_else(|| predicate.clone())
+    };
+
+    rewriter.rewrite_predicate(rebuilt)
+}
+
+pub fn rewrite_expression<R: Rewriter + ?Sized>(rewriter: &mut R, expression: &expression::SharedExpression) -> expression::SharedExpression {
+    let rebuilt = expression.rewrite_subqueries(&mut DynRewriter(rewriter)).unwrap_or_else(|| expression.clone());
+    rewriter.rewrite_expression(rebuilt)
+}
+
+pub fn rewrite_update<R: Rewriter + ?Sized>(rewriter: &mut R, update: &update_query::SharedFieldUpdate) -> update_query::SharedFieldUpdate {
+    let rebuilt = update.rewrite_value(&mut DynRewriter(rewriter));
+    rewriter.rewrite_update(rebuilt)
+}
+
+pub fn rewrite_select_list<R: Rewriter + ?Sized>(rewriter: &mut R, select: &select_query::Select) -> select_query::Select {
+    match *select {
+        select_query::Select::Only(ref expressions) => {
+            select_query::Select::Only(expressions.iter().map(|e| rewrite_expression(rewriter, e)).collect())
+        },
+        select_query::Select::All => select_query::Select::All
+    }
+}
+
+pub fn rewrite_join<R: Rewriter + ?Sized>(rewriter: &mut R, join: &join::Join) -> join::Join {
+    match *join {
+        join::Join::ConditionedJoin { join_type, ref from, ref on } => join::Join::ConditionedJoin {
+            join_type,
+            from: rewriter.rewrite_from(from.clone()),
+            on: rewrite_predicate(rewriter, on)
+        },
+        join::Join::UnconditionedJoin { join_type, ref from } => join::Join::UnconditionedJoin {
+            join_type,
+            from: rewriter.rewrite_from(from.clone())
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use deuterium::*;
+
+#[test]
+fn build() {
+
+    let jedi_table = TableDef::new("jedi");
+    let name = NamedField::<String>::field_of("name", &jedi_table);
+    let force_level = NamedField::<i8>::field_of("force_level", &jedi_table);
+
+    let query = jedi_table.select_1(&name).where_(name.is("Luke".to_string()).and(force_level.gt(100i8)));
+    let compiled = query.build(&mut SqlContext::new(Box::new(sql::MysqlAdapter))).unwrap();
+
+    assert_eq!(compiled.sql, "SELECT name FROM jedi WHERE (name = ?) AND (force_level > ?);");
+    assert_eq!(compiled.params, vec![Value::String("Luke".to_string()), Value::I8(100)]);
+}
+
+#[test]
+fn build_insert() {
+
+    let jedi_table = TableDef::new("jedi");
+    let name = NamedField::<String>::field_of("name", &jedi_table);
+    let side = NamedField::<bool>::field_of("side", &jedi_table);
+
+    let mut query = jedi_table.insert_fields(&[&name, &side]);
+    query.push_untyped(&["Luke".to_string().as_expr(), true.as_expr()]);
+    query.push_untyped(&["Vader".to_string().as_expr(), false.as_expr()]);
+
+    let compiled = query.build(&mut SqlContext::new(Box::new(sql::PostgreSqlAdapter))).unwrap();
+    assert_eq!(compiled.sql, "INSERT INTO jedi (name, side) VALUES\n    ($1, $2),\n    ($3, $4);");
+    assert_eq!(compiled.params, vec![
+        Value::String("Luke".to_string()), Value::Bool(true),
+        Value::String("Vader".to_string()), Value::Bool(false)
+    ]);
+}
+
+#[test]
+fn build_error() {
+
+    let jedi_table = TableDef::new("jedi");
+    assert!(jedi_table.update().all().build(&mut SqlContext::new(Box::new(sql::PostgreSqlAdapter))).is_err());
+}
+
+#[test]
+fn value_from() {
+    assert_eq!(Value::from(Some(42i32)), Value::I32(42));
+    assert_eq!(Value::from(None::<String>), Value::Null);
+}
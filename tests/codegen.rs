@@ -0,0 +1,189 @@
+use deuterium::*;
+use deuterium::codegen::{self, ColumnType, SkippedColumn};
+use super::codegen_schema;
+
+#[test]
+fn codegen_ddl() {
+
+    let tables = codegen::parse_ddl("
+        -- Jedi order
+        CREATE TABLE IF NOT EXISTS jedi (
+            id serial PRIMARY KEY,
+            name character varying(255) NOT NULL DEFAULT 'Luke',
+            force_level int4,
+            info jsonb,
+            created_at timestamp without time zone NOT NULL DEFAULT now(),
+            CONSTRAINT jedi_name UNIQUE (name)
+        );
+
+        CREATE INDEX jedi_name_idx ON jedi (name);
+
+        CREATE TABLE public.padawan (
+            jedi_id int8 REFERENCES jedi (id) ON DELETE CASCADE,
+            uid uuid,
+            PRIMARY KEY (uid)
+        );
+    ").unwrap();
+
+    assert_eq!(tables.len(), 2);
+
+    let jedi = &tables[0];
+    assert_eq!(jedi.name, "jedi");
+    let cols: Vec<(&str, ColumnType, bool, bool)> = jedi.columns.iter()
+        .map(|c| (&c.name[..], c.column_type, c.nullable, c.primary_key)).collect();
+    assert_eq!(cols, vec![
+        ("id", ColumnType::I32, false, true),
+        ("name", ColumnType::String, false, false),
+        ("force_level", ColumnType::I32, true, false),
+        ("info", ColumnType::Json, true, false),
+        ("created_at", ColumnType::Timespec, false, false),
+    ]);
+
+    let padawan = &tables[1];
+    assert_eq!(padawan.name, "public.padawan");
+    assert_eq!(padawan.columns[0].column_type, ColumnType::I64);
+    assert!(padawan.columns[0].nullable);
+    assert!(padawan.columns[1].primary_key);
+    assert!(!padawan.columns[1].nullable);
+}
+
+#[test]
+fn codegen_constraints() {
+
+    let tables = codegen::parse_ddl("
+        CREATE TABLE jedi (
+            id int4 NOT NULL,
+            master_id int4 NOT NULL DEFAULT NULL REFERENCES jedi (id) ON DELETE SET NULL ON UPDATE NO ACTION,
+            padawan_id int4 REFERENCES jedi MATCH FULL ON DELETE SET NULL,
+            rank int4 DEFAULT -1 NOT NULL,
+            check text NOT NULL CHECK (check <> ''),
+            unique bool,
+            primary int8,
+            CONSTRAINT jedi_pk PRIMARY KEY (id),
+            UNIQUE (master_id),
+            CHECK (rank > 0)
+        );
+    ").unwrap();
+
+    let cols: Vec<(&str, ColumnType, bool, bool)> = tables[0].columns.iter()
+        .map(|c| (&c.name[..], c.column_type, c.nullable, c.primary_key)).collect();
+    assert_eq!(cols, vec![
+        ("id", ColumnType::I32, false, true),
+        ("master_id", ColumnType::I32, false, false),
+        ("padawan_id", ColumnType::I32, true, false),
+        ("rank", ColumnType::I32, false, false),
+        ("check", ColumnType::String, false, false),
+        ("unique", ColumnType::Bool, true, false),
+        ("primary", ColumnType::I64, true, false),
+    ]);
+}
+
+#[test]
+fn codegen_unsupported_types() {
+
+    let tables = codegen::parse_ddl("CREATE TABLE jedi (id int4, born timestamptz, joined date NOT NULL, credits numeric(10, 2));").unwrap();
+    assert_eq!(tables[0].columns.len(), 1);
+    assert_eq!(tables[0].skipped, vec![
+        SkippedColumn { table: "jedi".to_string(), name: "born".to_string(), sql_type: "timestamptz".to_string() },
+        SkippedColumn { table: "jedi".to_string(), name: "joined".to_string(), sql_type: "date".to_string() },
+        SkippedColumn { table: "jedi".to_string(), name: "credits".to_string(), sql_type: "numeric(10,2)".to_string() },
+    ]);
+    assert_eq!(tables[0].skipped[0].to_string(), "skipped column jedi.born of unsupported type `timestamptz`");
+
+    let source = codegen::generate(&tables);
+    assert!(source.contains("/// Columns of unsupported types are left out: `born` (timestamptz), `joined` (date), `credits` (numeric(10,2)).\n"));
+}
+
+#[test]
+fn codegen_json() {
+
+    let tables = codegen::parse_schema(r#"{"tables": [
+        {"name": "jedi", "columns": [
+            {"name": "id", "type": "int4", "primary_key": true},
+            {"name": "type", "type": "text", "nullable": false},
+            {"name": "side", "type": "bool"}
+        ]}
+    ]}"#).unwrap();
+
+    assert_eq!(tables[0].columns[0].column_type, ColumnType::I32);
+    assert!(!tables[0].columns[0].nullable);
+    assert!(!tables[0].columns[1].nullable);
+    assert!(tables[0].columns[2].nullable);
+
+    let tables = codegen::parse_schema(r#"[
+        {"table_name": "jedi", "column_name": "id", "data_type": "integer", "is_nullable": "NO"},
+        {"table_name": "sith", "column_name": "name", "data_type": "text", "is_nullable": "YES"},
+        {"table_name": "jedi", "column_name": "name", "data_type": "text", "is_nullable": "NO"}
+    ]"#).unwrap();
+
+    assert_eq!(tables.len(), 2);
+    assert_eq!(tables[0].columns.len(), 2);
+    assert_eq!(tables[1].columns[0].column_type, ColumnType::String);
+
+    // Same-named tables of other schemas stay apart, primary keys come from joined constraints
+    let tables = codegen::parse_schema(r#"[
+        {"table_schema": "public", "table_name": "users", "column_name": "id", "data_type": "integer",
+         "is_nullable": "NO", "constraint_type": "PRIMARY KEY"},
+        {"table_schema": "public", "table_name": "users", "column_name": "id", "data_type": "integer",
+         "is_nullable": "NO", "constraint_type": "UNIQUE"},
+        {"table_schema": "auth", "table_name": "users", "column_name": "login", "data_type": "text",
+         "is_nullable": "NO", "constraint_type": null},
+        {"table_schema": "auth", "table_name": "users", "column_name": "created_at", "data_type": "timestamp with time zone",
+         "is_nullable": "NO", "constraint_type": null}
+    ]"#).unwrap();
+
+    assert_eq!(tables.iter().map(|t| &t.name[..]).collect::<Vec<&str>>(), vec!["users", "auth.users"]);
+    assert_eq!(tables[0].columns.len(), 1);
+    assert!(tables[0].columns[0].primary_key);
+    assert!(!tables[1].columns[0].primary_key);
+    assert_eq!(tables[1].skipped[0].sql_type, "timestamp with time zone");
+    assert_eq!(codegen::rust::struct_ident(&tables[1].name), "AuthUsersTable");
+}
+
+#[test]
+fn codegen_rust() {
+
+    let tables = codegen::parse_ddl("CREATE TABLE jedi_order (id int4 PRIMARY KEY, type text, info json NOT NULL);").unwrap();
+    let source = codegen::generate(&tables);
+
+    assert!(source.contains("pub struct JediOrderTable {\n    pub table: TableDef,\n    pub id: I32Field,\n    pub type_: OptionalStringField,\n    pub info: JsonField,\n}"));
+    assert!(source.contains("            type_: OptionalStringField::field_of(\"type\", &table),\n"));
+    assert!(source.contains("TableDef::new_with_alias(\"jedi_order\", alias)"));
+    assert!(source.contains("        vec![&self.id]\n"));
+}
+
+const SCHEMA: &str = "
+    CREATE TABLE jedi (
+        id serial PRIMARY KEY,
+        name varchar(255) NOT NULL,
+        type text,
+        force_level int2 NOT NULL DEFAULT NULL,
+        master_id int4 REFERENCES jedi (id) ON DELETE SET NULL,
+        born timestamptz
+    );
+
+    CREATE TABLE auth.users (login text NOT NULL, PRIMARY KEY (login));
+";
+
+// `codegen_schema.rs` is the generated source for `SCHEMA`, compiled as part of the tests.
+#[test]
+fn codegen_compiles() {
+
+    let source = codegen::generate(&codegen::parse_ddl(SCHEMA).unwrap());
+    assert_eq!(source, include_str!("codegen_schema.rs"));
+
+    let jedi = codegen_schema::JediTable::new();
+    assert_sql!(jedi.table.select_2(&jedi.name, &jedi.type_).where_(jedi.force_level.gt(10)),
+        "SELECT name, type FROM jedi WHERE force_level > $1;");
+    assert_eq!(jedi.primary_key().len(), 1);
+
+    let padawan = codegen_schema::JediTable::alias("padawan");
+    assert_sql!(padawan.table.select_1(&padawan.master_id).where_(padawan.id.is(1)),
+        "SELECT padawan.master_id FROM jedi AS padawan WHERE padawan.id = $1;");
+
+    let users = codegen_schema::AuthUsersTable::new();
+    assert_sql!(users.table.select_1(&users.login), "SELECT login FROM auth.users;");
+    assert_eq!(users.primary_key().len(), 1);
+    let users = codegen_schema::AuthUsersTable::alias("u");
+    assert_sql!(users.table.select_all(), "SELECT * FROM auth.users AS u;");
+}
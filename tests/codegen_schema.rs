@@ -0,0 +1,69 @@
+// This file is generated by deuterium-codegen, do not edit it manually.
+
+use deuterium::*;
+
+/// Table `jedi`.
+///
+/// Columns of unsupported types are left out: `born` (timestamptz).
+#[derive(Clone, Debug)]
+pub struct JediTable {
+    pub table: TableDef,
+    pub id: I32Field,
+    pub name: StringField,
+    pub type_: OptionalStringField,
+    pub force_level: I16Field,
+    pub master_id: OptionalI32Field,
+}
+
+impl JediTable {
+    pub fn new() -> JediTable {
+        JediTable::with_table(TableDef::new("jedi"))
+    }
+
+    pub fn alias(alias: &str) -> JediTable {
+        JediTable::with_table(TableDef::new_with_alias("jedi", alias))
+    }
+
+    fn with_table(table: TableDef) -> JediTable {
+        JediTable {
+            id: I32Field::field_of("id", &table),
+            name: StringField::field_of("name", &table),
+            type_: OptionalStringField::field_of("type", &table),
+            force_level: I16Field::field_of("force_level", &table),
+            master_id: OptionalI32Field::field_of("master_id", &table),
+            table,
+        }
+    }
+
+    pub fn primary_key(&self) -> Vec<&dyn Field> {
+        vec![&self.id]
+    }
+}
+
+/// Table `auth.users`.
+#[derive(Clone, Debug)]
+pub struct AuthUsersTable {
+    pub table: TableDef,
+    pub login: StringField,
+}
+
+impl AuthUsersTable {
+    pub fn new() -> AuthUsersTable {
+        AuthUsersTable::with_table(TableDef::new("auth.users"))
+    }
+
+    pub fn alias(alias: &str) -> AuthUsersTable {
+        AuthUsersTable::with_table(TableDef::new_with_alias("auth.users", alias))
+    }
+
+    fn with_table(table: TableDef) -> AuthUsersTable {
+        AuthUsersTable {
+            login: StringField::field_of("login", &table),
+            table,
+        }
+    }
+
+    pub fn primary_key(&self) -> Vec<&dyn Field> {
+        vec![&self.login]
+    }
+}
@@ -0,0 +1,82 @@
+use chrono::NaiveDate;
+use serde_json;
+use uuid::Uuid;
+use deuterium::*;
+
+fn debug<Q: QueryToSql>(query: &Q, adapter: Box<dyn sql::SqlAdapter>) -> String {
+    query.to_debug_sql(&mut SqlContext::new(adapter)).unwrap().to_string()
+}
+
+#[test]
+fn debug_sql() {
+
+    let jedi_table = TableDef::new("jedi");
+    let name = NamedField::<String>::field_of("name", &jedi_table);
+    let force_level = NamedField::<f64>::field_of("force_level", &jedi_table);
+    let side = NamedField::<bool>::field_of("side", &jedi_table);
+
+    let query = jedi_table.select_all().where_(
+        name.in_(vec!["Obi-Wan".to_string(), "Qui-Gon's \\ padawan".to_string()])
+            .and(force_level.gt(0.5f64))
+            .and(side.is(true))
+    );
+
+    assert_eq!(debug(&query, Box::new(sql::PostgreSqlAdapter)),
+        "SELECT * FROM jedi WHERE ((name IN ('Obi-Wan', 'Qui-Gon''s \\ padawan')) AND (force_level > 0.5)) AND (side = TRUE);");
+    assert_eq!(debug(&query, Box::new(sql::MysqlAdapter)),
+        "SELECT * FROM jedi WHERE ((name IN ('Obi-Wan', 'Qui-Gon''s \\\\ padawan')) AND (force_level > 0.5)) AND (side = TRUE);");
+
+    // The parameterized rendering is unaffected
+    assert_sql!(query, "SELECT * FROM jedi WHERE ((name IN ($1, $2)) AND (force_level > $3)) AND (side = $4);");
+}
+
+#[test]
+fn debug_sql_types() {
+
+    let jedi_table = TableDef::new("jedi");
+    let avatar = NamedField::<Vec<u8>>::field_of("avatar", &jedi_table);
+    let bio = NamedField::<serde_json::Value>::field_of("bio", &jedi_table);
+    let born_at = NamedField::<chrono::NaiveDateTime>::field_of("born_at", &jedi_table);
+    let uid = NamedField::<Uuid>::field_of("uid", &jedi_table);
+    let master = NamedField::<Option<String>>::field_of("master", &jedi_table);
+
+    let query = jedi_table.update()
+        .field(avatar.set(&vec![0xde, 0xad]))
+        .field(bio.set(&serde_json::from_str::<serde_json::Value>(r#"{"rank":"knight"}"#).unwrap()))
+        .field(born_at.set(&NaiveDate::from_ymd_opt(2015, 1, 2).unwrap().and_hms_opt(3, 4, 5).unwrap()))
+        .field(uid.set(&Uuid::parse_str("936da01f-9abd-4d9d-80c7-02af85c822a8").unwrap()))
+        .field(master.set(&None::<String>))
+        .all();
+
+    assert_eq!(debug(&query, Box::new(sql::PostgreSqlAdapter)),
+        "UPDATE jedi SET avatar = '\\xdead'::bytea, bio = '{\"rank\":\"knight\"}', born_at = '2015-01-02 03:04:05', \
+         uid = '936da01f-9abd-4d9d-80c7-02af85c822a8', master = NULL;");
+    assert_eq!(debug(&query, Box::new(sql::MysqlAdapter)),
+        "UPDATE jedi SET avatar = X'dead', bio = '{\"rank\":\"knight\"}', born_at = '2015-01-02 03:04:05', \
+         uid = '936da01f-9abd-4d9d-80c7-02af85c822a8', master = NULL;");
+}
+
+#[test]
+fn debug_sql_named() {
+
+    let jedi_table = TableDef::new("jedi");
+    let name = NamedField::<String>::field_of("name", &jedi_table);
+    let side = NamedField::<bool>::field_of("side", &jedi_table);
+
+    let query = jedi_table.select_all().where_(name.is(Placeholder::named("name")).and(side.is(false)));
+    assert_eq!(debug(&query, Box::new(sql::PostgreSqlAdapter)), "SELECT * FROM jedi WHERE (name = $1) AND (side = FALSE);");
+}
+
+#[test]
+fn debug_sql_floats() {
+
+    let jedi_table = TableDef::new("jedi");
+    let height = NamedField::<f32>::field_of("height", &jedi_table);
+    let weight = NamedField::<f64>::field_of("weight", &jedi_table);
+
+    let query = jedi_table.select_all()
+        .where_(height.is(0.1f32).and(weight.is(0.1f64)).and(height.lt(f32::INFINITY)));
+
+    assert_eq!(debug(&query, Box::new(sql::PostgreSqlAdapter)),
+        "SELECT * FROM jedi WHERE ((height = 0.1) AND (weight = 0.1)) AND (height < 'Infinity');");
+}
@@ -0,0 +1,99 @@
+use std::sync::Arc;
+use deuterium::*;
+
+#[derive(Debug)]
+struct TinyAdapter;
+
+impl sql::SqlAdapter for TinyAdapter {
+    fn placeholder(&self, idx: usize) -> String {
+        format!("${}", idx)
+    }
+
+    fn max_binds(&self) -> usize { 100 }
+}
+
+fn pg_sql<Q: QueryToSql>(query: &Q) -> Result<String, Error> {
+    query.to_final_sql(&mut SqlContext::new(Box::new(sql::PostgreSqlAdapter)))
+}
+
+#[test]
+fn error_distinct_on_mysql() {
+    let jedi_table = TableDef::new("jedi");
+    let name = NamedField::<String>::field_of("name", &jedi_table);
+
+    let query = jedi_table.select_all().distinct_on(&[&name]);
+    assert!(pg_sql(&query).is_ok());
+
+    match query.to_final_sql(&mut SqlContext::new(Box::new(sql::MysqlAdapter))) {
+        Err(Error::Unsupported { feature, adapter }) => {
+            assert_eq!(feature, "DISTINCT ON");
+            assert_eq!(adapter, "MySQL");
+        },
+        other => panic!("unexpected result: {:?}", other)
+    }
+}
+
+#[test]
+fn error_empty_in_list() {
+    let jedi_table = TableDef::new("jedi");
+    let force_level = NamedField::<i8>::field_of("force_level", &jedi_table);
+
+    let query = jedi_table.select_all().where_(force_level.in_(Vec::<i8>::new()));
+    match pg_sql(&query) {
+        Err(Error::EmptyInList) => (),
+        other => panic!("unexpected result: {:?}", other)
+    }
+}
+
+#[test]
+fn error_empty_update() {
+    let jedi_table = TableDef::new("jedi");
+
+    match pg_sql(&jedi_table.update().all()) {
+        Err(Error::EmptyUpdate) => (),
+        other => panic!("unexpected result: {:?}", other)
+    }
+}
+
+#[test]
+fn error_too_many_placeholders() {
+    let jedi_table = TableDef::new("jedi");
+    let force_level = NamedField::<i32>::field_of("force_level", &jedi_table);
+
+    let query = jedi_table.select_all().where_(force_level.in_((0..300).collect::<Vec<i32>>()));
+    assert!(pg_sql(&query).is_ok());
+
+    match query.to_final_sql(&mut SqlContext::new(Box::new(TinyAdapter))) {
+        Err(Error::TooManyPlaceholders { count, max }) => {
+            assert_eq!(count, 300);
+            assert_eq!(max, 100);
+        },
+        other => panic!("unexpected result: {:?}", other)
+    }
+}
+
+#[test]
+fn error_nul_in_raw() {
+
+    let jedi_table = TableDef::new("jedi");
+    let name = NamedField::<String>::field_of("name", &jedi_table);
+
+    let raw = Arc::new(Box::new(RawPredicate::new("name <> '\u{0}1\u{0}'")) as Box<dyn Predicate>);
+    let query = jedi_table.select_all().where_(raw.and(name.is("Luke".to_string())));
+    match pg_sql(&query) {
+        Err(Error::NulInRaw { ref content }) => assert_eq!(content, "name <> '\u{0}1\u{0}'"),
+        other => panic!("unexpected result: {:?}", other)
+    }
+
+    let query = jedi_table.select(&[&RawExpression::new("'\u{0}'")]);
+    match query.to_debug_sql(&mut SqlContext::new(Box::new(sql::PostgreSqlAdapter))) {
+        Err(Error::NulInRaw { .. }) => (),
+        other => panic!("unexpected result: {:?}", other)
+    }
+}
+
+#[test]
+fn error_display() {
+    let err = Error::Unsupported { feature: "DISTINCT ON", adapter: "MySQL" };
+    assert_eq!(err.to_string(), "DISTINCT ON is not supported by MySQL");
+}
@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use deuterium::*;
+use deuterium::eval::{eval, matches, EvalError};
+
+fn luke() -> HashMap<&'static str, Value> {
+    let mut row = HashMap::new();
+    row.insert("name", Value::String("Luke Skywalker".to_string()));
+    row.insert("force_level", Value::I32(100));
+    row.insert("rating", Value::F64(4.5));
+    row.insert("master", Value::Null);
+    row
+}
+
+#[test]
+fn eval_predicates() {
+
+    let jedi_table = TableDef::new("jedi");
+    let name = NamedField::<String>::field_of("name", &jedi_table);
+    let force_level = NamedField::<i32>::field_of("force_level", &jedi_table);
+    let rating = NamedField::<f64>::field_of("rating", &jedi_table);
+    let master = NamedField::<Option<String>>::field_of("master", &jedi_table);
+    let row = luke();
+
+    assert_eq!(eval(&name.is("Luke Skywalker".to_string()), &row), Ok(Some(true)));
+    assert_eq!(eval(&force_level.gt(99), &row), Ok(Some(true)));
+    assert_eq!(eval(&force_level.lt(100), &row), Ok(Some(false)));
+    assert_eq!(eval(&rating.gte(4.5f64), &row), Ok(Some(true)));
+    assert_eq!(eval(&force_level.in_range(1, 100), &row), Ok(Some(true)));
+    assert_eq!(eval(&force_level.in_range_exclude_right(1, 100), &row), Ok(Some(false)));
+    assert_eq!(eval(&force_level.in_(vec![1, 100]), &row), Ok(Some(true)));
+    assert_eq!(eval(&master.is_null(), &row), Ok(Some(true)));
+    assert_eq!(eval(&master.not_null().exclude(), &row), Ok(Some(true)));
+
+    assert_eq!(eval(&name.like("Luke%".to_string()), &row), Ok(Some(true)));
+    assert_eq!(eval(&name.like("luke%".to_string()), &row), Ok(Some(false)));
+    assert_eq!(eval(&name.ilike("luke%".to_string()), &row), Ok(Some(true)));
+    assert_eq!(eval(&name.like("L_ke Sky%er".to_string()), &row), Ok(Some(true)));
+    assert_eq!(eval(&name.like("L_e%".to_string()), &row), Ok(Some(false)));
+    assert_eq!(eval(&name.like("Luke\\%".to_string()), &row), Ok(Some(false)));
+    assert_eq!(eval(&name.like("%S%y%w%k".to_string()), &row), Ok(Some(false)));
+    assert_eq!(eval(&name.like("%S%y%w%k%".to_string()), &row), Ok(Some(true)));
+    assert_eq!(eval(&name.like("%%_".to_string()), &row), Ok(Some(true)));
+
+    // Backtracking only to the last `%` keeps this from taking exponential time
+    let a = NamedField::<String>::field_of("a", &jedi_table);
+    let mut many = HashMap::new();
+    many.insert("a", Value::String("a".repeat(100)));
+    assert_eq!(eval(&a.like(format!("{}b", "%a".repeat(20))), &many), Ok(Some(false)));
+    assert_eq!(eval(&a.like(format!("{}%", "%a".repeat(20))), &many), Ok(Some(true)));
+
+    assert!(matches(&name.like("%Sky%".to_string()).and(force_level.gt(50)), &row).unwrap());
+    assert!(matches(&name.is("Leia".to_string()).or(force_level.gt(50)), &row).unwrap());
+}
+
+#[test]
+fn eval_null_logic() {
+
+    let jedi_table = TableDef::new("jedi");
+    let force_level = NamedField::<i32>::field_of("force_level", &jedi_table);
+    let master = NamedField::<Option<String>>::field_of("master", &jedi_table);
+    let row = luke();
+
+    let unknown = master.is("Yoda".to_string());
+    assert_eq!(eval(&unknown, &row), Ok(None));
+    assert_eq!(eval(&unknown.exclude(), &row), Ok(None));
+    assert_eq!(eval(&unknown.and(force_level.gt(0)), &row), Ok(None));
+    assert_eq!(eval(&unknown.and(force_level.lt(0)), &row), Ok(Some(false)));
+    assert_eq!(eval(&unknown.or(force_level.gt(0)), &row), Ok(Some(true)));
+    assert_eq!(eval(&unknown.or(force_level.lt(0)), &row), Ok(None));
+    assert_eq!(matches(&unknown.exclude(), &row), Ok(false));
+
+    // NULL in the list makes a miss unknown, a hit stays true
+    let nullable = NamedField::<Option<i32>>::field_of("force_level", &jedi_table);
+    assert_eq!(eval(&nullable.in_(vec![Some(1), None]), &row), Ok(None));
+    assert_eq!(eval(&nullable.in_(vec![Some(100), None]), &row), Ok(Some(true)));
+}
+
+#[test]
+fn eval_errors() {
+
+    let jedi_table = TableDef::new("jedi");
+    let side = NamedField::<bool>::field_of("side", &jedi_table);
+    let name = NamedField::<i32>::field_of("name", &jedi_table);
+    let row = luke();
+
+    assert_eq!(eval(&side.is(true), &row), Err(EvalError::UnknownField("side".to_string())));
+    assert_eq!(eval(&name.gt(1), &row), Err(EvalError::TypeMismatch(Value::String("Luke Skywalker".to_string()), Value::I32(1))));
+    assert_eq!(eval(&Arc::new(Box::new(RawPredicate::new("name = 'Luke'")) as Box<dyn Predicate>), &row).unwrap_err().to_string(),
+        "raw predicate `name = 'Luke'` can't be evaluated in memory");
+    let title = NamedField::<String>::field_of("name", &jedi_table);
+    assert_eq!(eval(&title.is(Placeholder::new(1)), &row), Err(EvalError::Unsupported("placeholder".to_string())));
+}
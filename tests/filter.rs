@@ -0,0 +1,72 @@
+use chrono;
+use serde_json;
+use deuterium::*;
+use deuterium::filter::{Filters, FilterError, Op};
+
+fn jedi_filters() -> (TableDef, Filters) {
+    let jedi_table = TableDef::new("jedi");
+    let filters = Filters::new()
+        .field("name", &NamedField::<String>::field_of("name", &jedi_table))
+        .field("age", &NamedField::<i32>::field_of("age", &jedi_table))
+        .field("side", &NamedField::<bool>::field_of("side", &jedi_table))
+        .field("master", &NamedField::<Option<String>>::field_of("master", &jedi_table))
+        .field("born_at", &NamedField::<chrono::NaiveDateTime>::field_of("born_at", &jedi_table));
+    (jedi_table, filters)
+}
+
+fn filter(filters: &Filters, json: &str) -> Result<SharedPredicate, FilterError> {
+    filters.predicate(&serde_json::from_str(json).unwrap())
+}
+
+#[test]
+fn filter_predicates() {
+
+    let (jedi_table, filters) = jedi_filters();
+    let query = |json: &str| jedi_table.select_all().where_(filter(&filters, json).unwrap());
+
+    assert_sql!(query(r#"{"field": "age", "op": "gte", "value": 18}"#), "SELECT * FROM jedi WHERE age >= $1;");
+    assert_sql!(query(r#"{"field": "age", "op": "between", "value": [18, 30]}"#),
+        "SELECT * FROM jedi WHERE age >= $1 AND age <= $2;");
+    assert_sql!(query(r#"{"field": "name", "op": "in", "value": ["Luke", "Leia"]}"#),
+        "SELECT * FROM jedi WHERE name IN ($1, $2);");
+    assert_sql!(query(r#"{"field": "master", "op": "eq", "value": null}"#), "SELECT * FROM jedi WHERE master IS NULL;");
+    assert_sql!(query(r#"{"field": "master", "op": "ilike", "value": "obi%"}"#), "SELECT * FROM jedi WHERE master ILIKE $1;");
+    assert_sql!(query(r#"{"field": "born_at", "op": "lt", "value": "2015-01-02T03:04:05"}"#),
+        "SELECT * FROM jedi WHERE born_at < $1;");
+
+    assert_sql!(query(r#"{"and": [
+            {"field": "side", "op": "eq", "value": true},
+            {"or": [{"field": "name", "op": "like", "value": "Sky%"}, {"field": "master", "op": "not_null"}]},
+            {"field": "age", "op": "lt", "value": 900}
+        ]}"#),
+        "SELECT * FROM jedi WHERE ((side = $1) AND ((name LIKE $2) OR (master IS NOT NULL))) AND (age < $3);");
+}
+
+#[test]
+fn filter_errors() {
+
+    let (_, filters) = jedi_filters();
+    let error = |json: &str| filter(&filters, json).unwrap_err();
+
+    assert_eq!(error(r#"{"field": "password", "op": "eq", "value": "x"}"#), FilterError::UnknownField("password".to_string()));
+    assert_eq!(error(r#"{"field": "age", "op": "approx", "value": 18}"#), FilterError::UnknownOperator("approx".to_string()));
+    assert_eq!(error(r#"{"field": "age", "op": "like", "value": "1%"}"#),
+        FilterError::UnsupportedOperator { field: "age".to_string(), op: Op::Like });
+    assert_eq!(error(r#"{"field": "name", "op": "is_null"}"#),
+        FilterError::UnsupportedOperator { field: "name".to_string(), op: Op::IsNull });
+
+    assert_eq!(error(r#"{"field": "age", "op": "gte", "value": "18"}"#).to_string(),
+        "field `age` expects an integer fitting i32, got `\"18\"`");
+    assert_eq!(error(r#"{"field": "age", "op": "gte", "value": 3000000000}"#).to_string(),
+        "field `age` expects an integer fitting i32, got `3000000000`");
+    assert_eq!(error(r#"{"field": "name", "op": "eq", "value": null}"#).to_string(),
+        "field `name` expects a string, got `null`");
+    assert_eq!(error(r#"{"field": "age", "op": "between", "value": [18]}"#).to_string(),
+        "field `age` expects a list of two bounds, got `[18]`");
+    assert_eq!(error(r#"{"field": "name", "op": "in", "value": ["Luke", 1]}"#).to_string(),
+        "field `name` expects a string, got `1`");
+
+    assert_eq!(error(r#"{"and": []}"#).to_string(), "malformed filter: `and` expects a non-empty list of filters");
+    assert_eq!(error(r#"{"op": "eq", "value": 1}"#).to_string(), "malformed filter: condition without a `field` name");
+    assert_eq!(error(r#"[]"#).to_string(), "malformed filter: expected an object, got `[]`");
+}
@@ -0,0 +1,105 @@
+use deuterium::*;
+
+fn fingerprint<Q: QueryToSql>(query: &Q) -> Fingerprint {
+    query.fingerprint(&mut SqlContext::new(Box::new(sql::PostgreSqlAdapter))).unwrap()
+}
+
+#[test]
+fn fingerprint_values() {
+
+    let jedi_table = TableDef::new("jedi");
+    let name = NamedField::<String>::field_of("name", &jedi_table);
+    let force_level = NamedField::<i8>::field_of("force_level", &jedi_table);
+
+    let luke = jedi_table.select_1(&name)
+        .where_(name.is("Luke".to_string()).and(force_level.in_(vec![100i8, 120i8])))
+        .limit(10);
+    let yoda = jedi_table.select_1(&name)
+        .where_(name.is("Yoda".to_string()).and(force_level.in_(vec![127i8])))
+        .limit(1);
+
+    assert_eq!(fingerprint(&luke).get_sql(), "SELECT name FROM jedi WHERE (name = ?) AND (force_level IN (?)) LIMIT ?;");
+    assert_eq!(fingerprint(&luke), fingerprint(&yoda));
+    assert_eq!(fingerprint(&luke).get_hash(), 0xbcd4e852c0598da9);
+
+    let other = jedi_table.select_1(&name).where_(name.is("Luke".to_string()).or(force_level.in_(vec![100i8])));
+    assert!(fingerprint(&luke).get_hash() != fingerprint(&other).get_hash());
+
+    // Rendering as usual is unaffected
+    assert_sql!(luke, "SELECT name FROM jedi WHERE (name = $1) AND (force_level IN ($2, $3)) LIMIT 10;");
+}
+
+#[test]
+fn fingerprint_placeholders() {
+
+    let jedi_table = TableDef::new("jedi");
+    let name = NamedField::<String>::field_of("name", &jedi_table);
+    let side = NamedField::<bool>::field_of("side", &jedi_table);
+
+    let positional = jedi_table.select_1(&name).where_(name.is(Placeholder::new(1)).and(side.is(true)));
+    let named = jedi_table.select_1(&name).where_(name.is(Placeholder::named("name")).and(side.is(false)));
+
+    assert_eq!(fingerprint(&positional), fingerprint(&named));
+}
+
+#[test]
+fn fingerprint_whitespace() {
+
+    let jedi_table = TableDef::new("jedi");
+    let name = NamedField::<String>::field_of("name", &jedi_table);
+
+    let mut query = jedi_table.insert_fields(&[&name]);
+    query.push_untyped(&["Luke".to_string().as_expr()]);
+    query.push_untyped(&["Vader".to_string().as_expr()]);
+
+    assert_eq!(fingerprint(&query).get_sql(), "INSERT INTO jedi (name) VALUES (?);");
+    assert_eq!(Fingerprint::new("SELECT  'a  b'\n FROM jedi;").get_sql(), "SELECT 'a  b' FROM jedi;");
+}
+
+#[test]
+fn fingerprint_nulls() {
+
+    let jedi_table = TableDef::new("jedi");
+    let name = NamedField::<Option<String>>::field_of("name", &jedi_table);
+
+    let luke = jedi_table.select_all().where_(name.is(Some("Luke".to_string())));
+    let nobody = jedi_table.select_all().where_(name.is(None::<String>));
+
+    assert_eq!(fingerprint(&nobody).get_sql(), "SELECT * FROM jedi WHERE name = ?;");
+    assert_eq!(fingerprint(&luke), fingerprint(&nobody));
+    assert_sql!(nobody, "SELECT * FROM jedi WHERE name = NULL;");
+}
+
+#[test]
+fn fingerprint_bulk_insert() {
+
+    let jedi_table = TableDef::new("jedi");
+    let name = NamedField::<String>::field_of("name", &jedi_table);
+
+    let mut one = jedi_table.insert_fields(&[&name]);
+    one.push_untyped(&["Luke".to_string().as_expr()]);
+
+    let mut many = jedi_table.insert_1_for_test(&name);
+    many.push((InsertValue::new("Luke".to_string().as_expr()), ));
+    many.push((InsertValue::new("Vader".to_string().as_expr()), ));
+    many.push((InsertValue::new("Yoda".to_string().as_expr()), ));
+
+    assert_eq!(fingerprint(&many).get_sql(), "INSERT INTO jedi (name) VALUES (?);");
+    assert_eq!(fingerprint(&one), fingerprint(&many));
+}
+
+#[test]
+fn fingerprint_missing_tenant() {
+
+    let jedi_table = TableDef::new("jedi").tenant("tenant_id");
+
+    let query = jedi_table.select_all();
+    match query.fingerprint(&mut SqlContext::new(Box::new(sql::PostgreSqlAdapter))) {
+        Err(Error::MissingTenant { ref table }) => assert_eq!(table, "jedi"),
+        other => panic!("unexpected result: {:?}", other)
+    }
+
+    let mut ctx = SqlContext::new(Box::new(sql::PostgreSqlAdapter));
+    ctx.set_tenant(42i32);
+    assert_eq!(query.fingerprint(&mut ctx).unwrap().get_sql(), "SELECT * FROM jedi WHERE jedi.tenant_id = ?;");
+}
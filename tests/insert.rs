@@ -1,4 +1,5 @@
 use deuterium::*;
+use deuterium::sql::SqlAdapter;
 
 #[test]
 fn insert_default() {
@@ -34,4 +35,25 @@ fn insert_returning() {
     let mut query = jedi_table.insert_fields(&[&name, &side]).returning_1(&name.qual());
     query.push_untyped(&["Luke".to_string().as_expr(), true.as_expr()]);
     assert_sql!(query, "INSERT INTO jedi (name, side) VALUES\n    ($1, $2) RETURNING jedi.name;");
+}
+
+#[test]
+fn insert_split() {
+
+    let jedi_table = TableDef::new("jedi");
+    let name = NamedField::<String>::field_of("name", &jedi_table);
+    let side = NamedField::<bool>::field_of("side", &jedi_table);
+
+    let mut query = jedi_table.insert_fields(&[&name, &side]);
+    for name in ["Luke", "Leia", "Han"].iter() {
+        query.push_untyped(&[name.to_string().as_expr(), true.as_expr()]);
+    }
+
+    let queries = query.split(4);
+    assert_eq!(queries.len(), 2);
+    assert_sql!(queries[0], "INSERT INTO jedi (name, side) VALUES\n    ($1, $2),\n    ($3, $4);");
+    assert_sql!(queries[1], "INSERT INTO jedi (name, side) VALUES\n    ($1, $2);");
+
+    assert_eq!(query.split(sql::PostgreSqlAdapter.max_binds()).len(), 1);
+    assert_eq!(jedi_table.insert_all().split(1).len(), 1);
 }
\ No newline at end of file
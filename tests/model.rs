@@ -0,0 +1,91 @@
+use deuterium::*;
+
+#[derive(Clone, Debug, Model)]
+#[model(table = "jedi")]
+pub struct Jedi {
+    #[primary_key]
+    pub id: i32,
+    pub name: String,
+    pub force_level: Option<i8>,
+}
+
+fn luke() -> Jedi {
+    Jedi { id: 1, name: "Luke".to_string(), force_level: Some(100) }
+}
+
+#[test]
+fn model_select() {
+
+    let jedi = JediTable::new();
+
+    let query: SelectQuery<(), LimitMany, Jedi> = jedi.select_all().where_(jedi.name.is("Luke".to_string()));
+    assert_sql!(query, "SELECT * FROM jedi WHERE name = $1;");
+
+    let jedi = JediTable::alias("j");
+    let query = jedi.select_1(&jedi.force_level);
+    assert_sql!(query, "SELECT j.force_level FROM jedi AS j;");
+}
+
+#[test]
+fn model_insert() {
+
+    let jedi = JediTable::new();
+
+    let query = jedi.insert(&luke());
+    assert_sql!(query, "INSERT INTO jedi (id, name, force_level) VALUES\n    ($1, $2, $3);");
+
+    let mut ctx = SqlContext::new(Box::new(sql::PostgreSqlAdapter));
+    query.to_final_sql(&mut ctx).unwrap();
+    assert_eq!(ctx.data().len(), 3);
+
+    let yoda = Jedi { id: 2, name: "Yoda".to_string(), force_level: None };
+    let query = jedi.insert_many(&[luke(), yoda]);
+    assert_sql!(query, "INSERT INTO jedi (id, name, force_level) VALUES\n    ($1, $2, $3),\n    ($4, $5, NULL);");
+}
+
+#[test]
+fn model_update() {
+
+    let jedi = JediTable::new();
+
+    let query = jedi.update_from(&luke());
+    assert_sql!(query, "UPDATE jedi SET name = $1, force_level = $2 WHERE id = $3;");
+
+    let query = jedi.update_from(&luke()).returning_all();
+    assert_sql!(query, "UPDATE jedi SET name = $1, force_level = $2 WHERE id = $3 RETURNING *;");
+}
+
+#[test]
+fn model_delete() {
+
+    let jedi = JediTable::new();
+
+    let query: DeleteQuery<(), NoResult, Jedi> = jedi.delete().where_(luke().primary_key_predicate());
+    assert_sql!(query, "DELETE FROM jedi WHERE id = $1;");
+}
+
+#[derive(Clone, Debug, Model)]
+#[model(table = "membership", table_struct = "Memberships")]
+pub struct Membership {
+    pub rank: String,
+    #[primary_key]
+    pub jedi_id: i32,
+    #[primary_key]
+    pub order_id: i32,
+}
+
+#[test]
+fn model_composite_key() {
+
+    let membership = Memberships::new();
+    let knight = Membership { rank: "Knight".to_string(), jedi_id: 1, order_id: 2 };
+
+    let query = membership.insert(&knight);
+    assert_sql!(query, "INSERT INTO membership (rank, jedi_id, order_id) VALUES\n    ($1, $2, $3);");
+
+    let query = membership.update_from(&knight);
+    assert_sql!(query, "UPDATE membership SET rank = $1 WHERE (jedi_id = $2) AND (order_id = $3);");
+
+    let query = membership.delete().where_(knight.primary_key_predicate());
+    assert_sql!(query, "DELETE FROM membership WHERE (jedi_id = $1) AND (order_id = $2);");
+}
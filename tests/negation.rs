@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use deuterium::*;
+use deuterium::eval::eval;
+use deuterium::parse::{parse_sql, Statement};
+
+fn row(force_level: Option<i32>, name: Option<&str>) -> HashMap<&'static str, Value> {
+    let mut row = HashMap::new();
+    row.insert("force_level", force_level.map(Value::I32).unwrap_or(Value::Null));
+    row.insert("name", name.map(|name| Value::String(name.to_string())).unwrap_or(Value::Null));
+    row
+}
+
+// Rows below, at, between and above the constants of the predicates, and with NULLs.
+fn rows() -> Vec<HashMap<&'static str, Value>> {
+    vec![
+        row(Some(0), Some("Luke")),
+        row(Some(10), Some("Leia")),
+        row(Some(15), Some("Han")),
+        row(Some(20), Some("Lando")),
+        row(Some(30), None),
+        row(None, Some("Luke")),
+        row(None, None)
+    ]
+}
+
+fn shared<P: Predicate + 'static>(predicate: P) -> SharedPredicate {
+    Arc::new(Box::new(predicate) as Box<dyn Predicate>)
+}
+
+// Rebuilds the leaves of a parsed predicate to columns and bound values, so it can be evaluated.
+struct Bind(Vec<Value>);
+
+impl Bind {
+    fn operand(&self, node: &Node) -> CastExpression<RawExpression> {
+        CastExpression::new(match node {
+            &Node::Raw(raw) => NamedField::<RawExpression>::new(&raw.content, "jedi").upcast_expression(),
+            &Node::Placeholder(placeholder) => self.0[placeholder.get_idx() - 1].clone().upcast_expression(),
+            node => panic!("unexpected operand {:?}", node)
+        })
+    }
+}
+
+impl Rewriter for Bind {
+    fn rewrite_predicate(&mut self, predicate: SharedPredicate) -> SharedPredicate {
+        match predicate.predicate_node() {
+            PredicateNode::Is { field, value } => shared(IsPredicate::new(self.operand(&field), self.operand(&value))),
+            PredicateNode::IsNull { field, is_null } => shared(IsNullPredicate::new(self.operand(&field), is_null)),
+            PredicateNode::In { field, values: Node::List(values) } => {
+                shared(InPredicate::new(self.operand(&field), values.iter().map(|value| self.operand(value)).collect::<Vec<_>>()))
+            },
+            PredicateNode::Like { field, value, case_sensitive } => {
+                shared(LikePredicate::new(self.operand(&field), self.operand(&value), case_sensitive))
+            },
+            PredicateNode::Inequality { field, value, inequality } => {
+                shared(InequalityPredicate::new(self.operand(&field), self.operand(&value), inequality))
+            },
+            _ => predicate
+        }
+    }
+}
+
+// Renders the query, checks its condition is `sql` and that, parsed back, it has the truth
+// value of `expected` for every row, unknown included.
+fn assert_renders<Q: QueryToSql>(query: &Q, sql: &str, expected: &SharedPredicate) {
+    let compiled = query.build(&mut SqlContext::new(Box::new(sql::PostgreSqlAdapter))).unwrap();
+    assert_eq!(compiled.sql, format!("SELECT * FROM jedi WHERE {};", sql));
+
+    let rendered = match parse_sql(&compiled.sql).unwrap() {
+        Statement::Select(select) => select.rewrite(&mut Bind(compiled.params)).get_where().clone().unwrap(),
+        statement => panic!("unexpected statement {:?}", statement)
+    };
+
+    for row in rows() {
+        assert_eq!(eval(&rendered, &row), eval(expected, &row), "{} and {:?} differ for {:?}", sql, expected, row);
+    }
+}
+
+#[test]
+fn negation_matrix() {
+
+    let jedi_table = TableDef::new("jedi");
+    let force_level = NamedField::<Option<i32>>::field_of("force_level", &jedi_table);
+    let name = NamedField::<Option<String>>::field_of("name", &jedi_table);
+    let luke = Some("Luke".to_string());
+    let query = |predicate: &SharedPredicate| jedi_table.select_all().where_(predicate.exclude());
+
+    // Inverted in place, NULLs stay unknown
+    let cases = vec![
+        (force_level.is(Some(10)), force_level.lt(Some(10)).or(force_level.gt(Some(10))), "force_level != $1"),
+        (force_level.lt(Some(10)), force_level.gte(Some(10)), "force_level >= $1"),
+        (force_level.lte(Some(10)), force_level.gt(Some(10)), "force_level > $1"),
+        (force_level.gt(Some(10)), force_level.lte(Some(10)), "force_level <= $1"),
+        (force_level.gte(Some(10)), force_level.lt(Some(10)), "force_level < $1"),
+        (force_level.is_null(), force_level.not_null(), "force_level IS NOT NULL"),
+        (force_level.not_null(), force_level.is_null(), "force_level IS NULL"),
+        (name.in_(vec![luke.clone(), Some("Leia".to_string())]), name.is(luke.clone()).or(name.is(Some("Leia".to_string()))).exclude(),
+            "name NOT IN ($1, $2)")
+    ];
+
+    for (predicate, expected, sql) in cases {
+        assert_renders(&query(&predicate), sql, &expected);
+    }
+
+    // Wrapped in NOT (...) and still evaluated with De Morgan's laws
+    let cases = vec![
+        (force_level.in_range(Some(10), Some(20)), force_level.lt(Some(10)).or(force_level.gt(Some(20))),
+            "NOT (force_level >= $1 AND force_level <= $2)"),
+        (force_level.in_range_exclude(Some(10), Some(20)), force_level.lte(Some(10)).or(force_level.gte(Some(20))),
+            "NOT (force_level > $1 AND force_level < $2)"),
+        (force_level.gt(Some(5)).and(name.is(luke.clone())), force_level.lte(Some(5)).or(name.is(luke.clone()).exclude()),
+            "NOT ((force_level > $1) AND (name = $2))"),
+        (force_level.gt(Some(5)).or(name.is(luke.clone())), force_level.lte(Some(5)).and(name.is(luke.clone()).exclude()),
+            "NOT ((force_level > $1) OR (name = $2))")
+    ];
+
+    for (predicate, expected, sql) in cases {
+        assert_renders(&query(&predicate), sql, &expected);
+    }
+
+    assert_renders(&query(&name.like(Some("L%".to_string()))), "name NOT LIKE $1",
+        &name.like(Some("L%".to_string())).exclude());
+    assert_eq!(eval(&name.like(Some("L%".to_string())).exclude(), &row(None, None)), Ok(None));
+
+    // Double negation cancels out
+    assert_renders(&query(&force_level.in_range(Some(10), Some(20)).exclude()), "force_level >= $1 AND force_level <= $2",
+        &force_level.gte(Some(10)).and(force_level.lte(Some(20))));
+    assert_renders(&query(&force_level.not_null().exclude()), "force_level IS NOT NULL", &force_level.not_null());
+}
+
+#[test]
+fn negation_opaque() {
+
+    let jedi_table = TableDef::new("jedi");
+    let side = NamedField::<bool>::field_of("side", &jedi_table);
+    let raw = Arc::new(Box::new(RawPredicate::new("side OR force_level > 10")) as Box<dyn Predicate>);
+
+    assert_sql!(jedi_table.select_all().exclude(raw.clone()), "SELECT * FROM jedi WHERE NOT (side OR force_level > 10);");
+    assert_sql!(jedi_table.select_all().where_(side.is(true).and(raw.exclude())),
+        "SELECT * FROM jedi WHERE (side = $1) AND (NOT (side OR force_level > 10));");
+}
@@ -0,0 +1,113 @@
+use deuterium::*;
+use deuterium::parse::{parse_sql, ParseError, Statement};
+
+fn round_trip(input: &str) -> String {
+    parse_sql(input).unwrap().to_final_sql(&mut SqlContext::new(Box::new(sql::PostgreSqlAdapter))).unwrap()
+}
+
+#[test]
+fn parse_select() {
+    assert_eq!(round_trip("select * from jedi"), "SELECT * FROM jedi;");
+
+    assert_eq!(
+        round_trip("SELECT DISTINCT j.name, count(*) total FROM jedi AS j \
+                    LEFT JOIN planet p ON p.id = j.planet_id \
+                    WHERE j.force_level >= $1 AND (j.side = TRUE OR j.name LIKE 'Sky%') \
+                    GROUP BY j.name HAVING count(*) > 1 ORDER BY j.name DESC, total LIMIT 10 OFFSET 20"),
+        "SELECT DISTINCT j.name, count(*) AS total FROM jedi AS j LEFT JOIN planet AS p ON p.id = j.planet_id \
+         WHERE (j.force_level >= $1) AND ((j.side = TRUE) OR (j.name LIKE 'Sky%')) \
+         GROUP BY j.name HAVING count(*) > 1 ORDER BY j.name DESC, total ASC LIMIT 10 OFFSET 20;"
+    );
+
+    assert_eq!(round_trip("SELECT * FROM jedi OFFSET 20 LIMIT 10"), "SELECT * FROM jedi LIMIT 10 OFFSET 20;");
+
+    assert_eq!(
+        round_trip("SELECT name FROM jedi WHERE id IN (SELECT pilot_id FROM ship WHERE speed > 10) \
+                    AND side NOT IN (1, 2) AND master_id IS NOT NULL AND force_level BETWEEN 1 AND 100 FOR UPDATE"),
+        "SELECT name FROM jedi WHERE (((id IN (SELECT pilot_id FROM ship WHERE speed > 10)) AND (side NOT IN (1, 2))) \
+         AND (master_id IS NOT NULL)) AND (force_level >= 1 AND force_level <= 100) FOR UPDATE;"
+    );
+
+    assert_eq!(
+        round_trip("SELECT * FROM (SELECT name FROM jedi) AS j NATURAL JOIN padawan WHERE (length(name) + 1) > 3 AND NOT active;"),
+        "SELECT * FROM (SELECT name FROM jedi) as j NATURAL JOIN padawan WHERE ((length(name) + 1) > 3) AND (NOT (active));"
+    );
+}
+
+#[test]
+fn parse_modifications() {
+    assert_eq!(
+        round_trip("INSERT INTO jedi (name, force_level) VALUES ('Luke', $1), (DEFAULT, -1) RETURNING id"),
+        "INSERT INTO jedi (name, force_level) VALUES\n    ('Luke', $1),\n    (DEFAULT, -1) RETURNING id;"
+    );
+    assert_eq!(round_trip("insert into jedi default values"), "INSERT INTO jedi DEFAULT VALUES;");
+    assert_eq!(
+        round_trip("INSERT INTO archive SELECT * FROM jedi WHERE side = $1"),
+        "INSERT INTO archive SELECT * FROM jedi WHERE side = $1;"
+    );
+
+    assert_eq!(
+        round_trip("UPDATE jedi SET name = :name, rank = DEFAULT, force_level = force_level + 1 WHERE id = :id RETURNING *"),
+        "UPDATE jedi SET name = $1, rank = DEFAULT, force_level = force_level + 1 WHERE id = $2 RETURNING *;"
+    );
+    assert_eq!(round_trip("UPDATE jedi SET side = FALSE"), "UPDATE jedi SET side = FALSE;");
+
+    assert_eq!(
+        round_trip("DELETE FROM jedi USING planet WHERE planet.id = jedi.planet_id AND planet.name = $1"),
+        "DELETE FROM jedi USING planet WHERE (planet.id = jedi.planet_id) AND (planet.name = $1);"
+    );
+    assert_eq!(round_trip("DELETE FROM jedi"), "DELETE FROM jedi;");
+}
+
+#[test]
+fn parse_retarget() {
+    let statement = parse_sql("SELECT * FROM jedi WHERE name = $1 AND force_level IN ($2, $3)").unwrap();
+    assert_eq!(
+        statement.to_final_sql(&mut SqlContext::new(Box::new(sql::MysqlAdapter))).unwrap(),
+        "SELECT * FROM jedi WHERE (name = ?) AND (force_level IN (?, ?));"
+    );
+
+    let statement = parse_sql("UPDATE jedi SET name = ? WHERE id = ?").unwrap();
+    assert_eq!(
+        statement.to_final_sql(&mut SqlContext::new(Box::new(sql::PostgreSqlAdapter))).unwrap(),
+        "UPDATE jedi SET name = $1 WHERE id = $2;"
+    );
+
+    match statement {
+        Statement::Update(_) => (),
+        other => panic!("unexpected statement {:?}", other)
+    }
+}
+
+#[test]
+fn parse_rewrite() {
+    struct Archive;
+
+    impl Rewriter for Archive {
+        fn rewrite_from(&mut self, from: SharedFrom) -> SharedFrom {
+            match from.as_table() {
+                Some(table) if table.get_table_name() == "jedi" => TableDef::new("jedi_archive").upcast_from(),
+                _ => from
+            }
+        }
+    }
+
+    let statement = parse_sql("SELECT name FROM jedi WHERE side = $1").unwrap().rewrite(&mut Archive);
+    assert_sql!(statement, "SELECT name FROM jedi_archive WHERE side = $1;");
+}
+
+#[test]
+fn parse_errors() {
+    assert_eq!(parse_sql("SELECT * FROM jedi UNION SELECT * FROM sith").unwrap_err(), ParseError::Unsupported("UNION".to_string()));
+    assert_eq!(parse_sql("WITH t AS (SELECT 1) SELECT * FROM t").unwrap_err(), ParseError::Unsupported("WITH".to_string()));
+    assert_eq!(parse_sql("SELECT * FROM jedi LIMIT $1").unwrap_err(), ParseError::Unsupported("placeholder $1 as a row count".to_string()));
+    assert_eq!(parse_sql("SELECT * FROM jedi WHERE force_level + ? > 10").unwrap_err(), ParseError::Unsupported("placeholder ? inside an expression".to_string()));
+    assert_eq!(parse_sql("SELECT * FROM jedi WHERE name = lower($1)").unwrap_err(), ParseError::Unsupported("placeholder $1 inside an expression".to_string()));
+    assert_eq!(parse_sql("SELECT \"name\" FROM jedi").unwrap_err(), ParseError::Unsupported("quoted identifier \"name\"".to_string()));
+    assert_eq!(parse_sql("SELECT * FROM `jedi`").unwrap_err(), ParseError::Unsupported("quoted identifier `jedi`".to_string()));
+    assert_eq!(parse_sql("SELECT * FROM jedi WHERE").unwrap_err(), ParseError::UnexpectedEnd);
+    assert_eq!(parse_sql("INSERT INTO t (a) VALUES (1, 2)").unwrap_err(), ParseError::ValueCount { expected: 1, found: 2 });
+    assert_eq!(parse_sql("INSERT INTO t VALUES (1, 2), (3)").unwrap_err(), ParseError::ValueCount { expected: 2, found: 1 });
+    assert_eq!(parse_sql("SELECT * FROM jedi WHERE id = $0").unwrap_err(), ParseError::UnexpectedToken("$0".to_string()));
+    assert_eq!(parse_sql("SELECT * FROM jedi jedi2 sith").unwrap_err(), ParseError::UnexpectedToken("sith".to_string()));
+}
@@ -19,4 +19,102 @@ fn placeholder() {
     let query = jedi_table.select_1(&name).where_(name.is(Placeholder::new(1))).or(name.like(Placeholder::new(10))).or(name.is("Luke Skywalker".to_string()));
     assert_sql!(query, "SELECT name FROM jedi WHERE ((name = $1) OR (name LIKE $10)) OR (name = $11);");
 
-}
\ No newline at end of file
+}
+
+#[test]
+fn placeholder_many() {
+
+    let jedi_table = TableDef::new("jedi");
+    let force_level = NamedField::<i32>::field_of("force_level", &jedi_table);
+
+    let query = jedi_table.select_all().where_(force_level.in_((0..12).collect::<Vec<i32>>()));
+    assert_sql!(query, "SELECT * FROM jedi WHERE force_level IN ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12);");
+
+    let name = NamedField::<String>::field_of("name", &jedi_table);
+    let mut query = jedi_table.insert_fields(&[&name]);
+    for i in 0..300 {
+        query.push_untyped(&[format!("Jedi {}", i).as_expr()]);
+    }
+
+    let mut ctx = SqlContext::new(Box::new(sql::PostgreSqlAdapter));
+    let sql = query.to_final_sql(&mut ctx).unwrap();
+    assert!(sql.ends_with("($299),\n    ($300);"));
+    assert_eq!(ctx.data().len(), 300);
+}
+
+#[test]
+fn placeholder_named() {
+
+    let jedi_table = TableDef::new("jedi");
+    let name = NamedField::<String>::field_of("name", &jedi_table);
+    let master = NamedField::<String>::field_of("master", &jedi_table);
+
+    let query = jedi_table.select_1(&name)
+        .where_(name.is(Placeholder::named("name")))
+        .or(master.is("Yoda".to_string()))
+        .or(master.is(Placeholder::named("name")));
+    assert_sql!(query, "SELECT name FROM jedi WHERE ((name = $1) OR (master = $2)) OR (master = $1);");
+
+    let query = jedi_table.select_1(&name)
+        .where_(name.is(Placeholder::new(1)))
+        .or(master.is(Placeholder::named("master")));
+    assert_sql!(query, "SELECT name FROM jedi WHERE (name = $1) OR (master = $2);");
+}
+
+#[test]
+fn placeholder_named_bind() {
+    use std::collections::HashMap;
+
+    let jedi_table = TableDef::new("jedi");
+    let name = NamedField::<String>::field_of("name", &jedi_table);
+    let master = NamedField::<String>::field_of("master", &jedi_table);
+
+    let query = jedi_table.select_1(&name)
+        .where_(name.is(Placeholder::named("name")))
+        .or(master.is("Yoda".to_string()))
+        .or(master.is(Placeholder::named("name")));
+
+    let compiled = query.build(&mut SqlContext::new(Box::new(sql::MysqlAdapter))).unwrap();
+    assert_eq!(compiled.sql, "SELECT name FROM jedi WHERE ((name = ?) OR (master = ?)) OR (master = ?);");
+
+    let mut values = HashMap::new();
+    values.insert("name", Value::String("Luke".to_string()));
+    assert_eq!(compiled.bind(&values).unwrap(), vec![
+        Value::String("Luke".to_string()),
+        Value::String("Yoda".to_string()),
+        Value::String("Luke".to_string())
+    ]);
+
+    match compiled.bind(&HashMap::<String, Value>::new()) {
+        Err(Error::UnboundPlaceholder { ref name }) => assert_eq!(name, "name"),
+        other => panic!("unexpected result: {:?}", other)
+    }
+}
+
+#[derive(Clone, Debug, Model)]
+#[model(table = "jedi")]
+pub struct Jedi {
+    #[primary_key]
+    pub id: i32,
+    pub name: String,
+    pub master: String,
+}
+
+#[test]
+fn placeholder_named_model() {
+
+    let jedi = JediTable::new();
+    let query = jedi.select_all()
+        .where_(jedi.name.is(Placeholder::named("name")))
+        .or(jedi.master.is(Placeholder::named("master")));
+    let compiled = query.build(&mut SqlContext::new(Box::new(sql::PostgreSqlAdapter))).unwrap();
+
+    let luke = Jedi { id: 1, name: "Luke".to_string(), master: "Yoda".to_string() };
+    assert_eq!(compiled.bind(&luke).unwrap(), vec![Value::String("Luke".to_string()), Value::String("Yoda".to_string())]);
+    assert_eq!(luke.named_value("id"), Some(Value::I32(1)));
+    assert_eq!(luke.named_value("side"), None);
+
+    let first = Placeholder::new(1);
+    let copy = first;
+    assert_eq!(first.get_idx(), copy.get_idx());
+}
@@ -0,0 +1,103 @@
+use deuterium::*;
+
+fn pretty<Q: QueryToSql>(query: &Q) -> String {
+    let mut ctx = SqlContext::new(Box::new(sql::PostgreSqlAdapter));
+    ctx.set_pretty(true);
+    query.to_final_sql(&mut ctx).unwrap()
+}
+
+#[test]
+fn pretty_select() {
+
+    let jedi_table = TableDef::new("jedi");
+    let planet_table = TableDef::new("planet");
+    let name = NamedField::<String>::field_of("name", &jedi_table);
+    let force_level = NamedField::<i8>::field_of("force_level", &jedi_table);
+    let side = NamedField::<bool>::field_of("side", &jedi_table);
+    let planet_id = NamedField::<i32>::field_of("planet_id", &jedi_table);
+    let id = NamedField::<i32>::field_of("id", &planet_table);
+
+    let query = jedi_table.select_1(&name)
+        .inner_join(&planet_table, planet_id.is(id.clone()))
+        .where_(name.is("Luke".to_string()).and(force_level.gt(100i8).or(side.is(true))).and(side.is(false)))
+        .order_by(&name)
+        .limit(10);
+
+    assert_eq!(pretty(&query), "SELECT name
+FROM jedi
+    INNER JOIN planet ON planet_id = id
+WHERE name = $1
+    AND (
+        force_level > $2
+        OR side = $3
+    )
+    AND side = $4
+ORDER BY name ASC
+LIMIT 10;");
+}
+
+#[test]
+fn pretty_subqueries() {
+
+    let jedi_table = TableDef::new("jedi");
+    let ship_table = TableDef::new("ship");
+    let id = NamedField::<i32>::field_of("id", &jedi_table);
+    let name = NamedField::<String>::field_of("name", &jedi_table);
+    let pilot_id = NamedField::<i32>::field_of("pilot_id", &ship_table);
+
+    let pilots = ship_table.select_1(&pilot_id).where_(pilot_id.gt(10));
+    let query = jedi_table.select_1(&name).where_(id.in_(pilots)).alias("j").select_all();
+
+    assert_eq!(pretty(&query), "SELECT *
+FROM (
+    SELECT name
+    FROM jedi
+    WHERE id IN (
+            SELECT pilot_id
+            FROM ship
+            WHERE pilot_id > $1
+        )
+) as j;");
+}
+
+#[test]
+fn pretty_exclude() {
+
+    let jedi_table = TableDef::new("jedi");
+    let force_level = NamedField::<i8>::field_of("force_level", &jedi_table);
+    let side = NamedField::<bool>::field_of("side", &jedi_table);
+
+    // The negated OR keeps its structure under NOT
+    let query = jedi_table.select_all()
+        .where_(side.is(true).and(force_level.gt(100i8).or(side.is(false)).exclude()));
+
+    assert_eq!(pretty(&query), "SELECT *
+FROM jedi
+WHERE side = $1
+    AND NOT (
+        force_level > $2
+        OR side = $3
+    );");
+}
+
+#[test]
+fn pretty_update() {
+
+    let jedi_table = TableDef::new("jedi");
+    let name = NamedField::<String>::field_of("name", &jedi_table);
+    let side = NamedField::<bool>::field_of("side", &jedi_table);
+
+    let query = jedi_table.update().field(side.set(&true)).where_(name.is("Luke".to_string())).returning_all();
+    assert_eq!(pretty(&query), "UPDATE jedi
+SET side = $1
+WHERE name = $2
+RETURNING *;");
+
+    let mut query = jedi_table.insert_fields(&[&name]);
+    query.push_untyped(&["Luke".to_string().as_expr()]);
+    query.push_untyped(&["Vader".to_string().as_expr()]);
+    assert_eq!(pretty(&query), "INSERT INTO jedi (name)
+VALUES
+    ($1),
+    ($2);");
+}
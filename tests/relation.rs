@@ -0,0 +1,103 @@
+use deuterium::*;
+
+#[derive(Clone, Debug, Model)]
+#[model(table = "jedi")]
+pub struct Jedi {
+    #[primary_key]
+    pub id: i32,
+    pub name: String,
+}
+
+#[derive(Clone, Debug, Model)]
+#[model(table = "padawan")]
+pub struct Padawan {
+    #[primary_key]
+    pub id: i32,
+    pub master_id: i32,
+    pub name: String,
+}
+
+fn padawans() -> Relation<i32> {
+    let (jedi, padawan) = (JediTable::new(), PadawanTable::new());
+    Relation::has_many(&padawan, &padawan.master_id, &jedi.id)
+}
+
+fn master() -> Relation<i32> {
+    let (jedi, padawan) = (JediTable::new(), PadawanTable::new());
+    Relation::belongs_to(&jedi, &padawan.master_id, &jedi.id)
+}
+
+relations! {
+    pub trait JediRelations {
+        join_padawans => padawans().left();
+    }
+}
+
+relations! {
+    pub trait PadawanRelations {
+        join_master => master();
+    }
+}
+
+#[test]
+fn relation_join() {
+
+    let jedi = JediTable::new();
+    assert_sql!(jedi.select_all().join_padawans(),
+        "SELECT * FROM jedi LEFT JOIN padawan ON padawan.master_id = jedi.id;");
+
+    let padawan = PadawanTable::new();
+    assert_sql!(padawan.select_all().join_master().where_(jedi.name.qual().is("Yoda".to_string())),
+        "SELECT * FROM padawan INNER JOIN jedi ON padawan.master_id = jedi.id WHERE jedi.name = $1;");
+
+    let master = JediTable::alias("m");
+    let relation = Relation::belongs_to(&master, &padawan.master_id, &master.id);
+    assert_sql!(padawan.select_all().join_relation(&relation),
+        "SELECT * FROM padawan INNER JOIN jedi AS m ON padawan.master_id = m.id;");
+}
+
+#[test]
+fn relation_eager_load() {
+
+    let query: SelectQuery<(), LimitMany, Padawan> = padawans().children_query(vec![1, 2]);
+    assert_sql!(query, "SELECT * FROM padawan WHERE master_id IN ($1, $2);");
+
+    let parents = vec![
+        Jedi { id: 1, name: "Obi-Wan".to_string() },
+        Jedi { id: 2, name: "Yoda".to_string() },
+    ];
+    let children = vec![
+        Padawan { id: 10, master_id: 1, name: "Anakin".to_string() },
+        Padawan { id: 11, master_id: 1, name: "Luke".to_string() },
+    ];
+
+    let grouped = padawans().group(parents, children, |j: &Jedi| j.id, |p: &Padawan| p.master_id);
+    assert_eq!(grouped.len(), 2);
+    assert_eq!(grouped[0].1.iter().map(|p| p.id).collect::<Vec<i32>>(), vec![10, 11]);
+    assert!(grouped[1].1.is_empty());
+}
+
+#[test]
+fn relation_eager_load_belongs_to() {
+
+    let query: SelectQuery<(), LimitMany, Jedi> = master().children_query(vec![1, 2]);
+    assert_sql!(query, "SELECT * FROM jedi WHERE id IN ($1, $2);");
+
+    let alias = JediTable::alias("m");
+    let padawan = PadawanTable::new();
+    let query: SelectQuery<(), LimitMany, Jedi> = Relation::belongs_to(&alias, &padawan.master_id, &alias.id).children_query(vec![1]);
+    assert_sql!(query, "SELECT * FROM jedi AS m WHERE m.id IN ($1);");
+
+    let padawans = vec![
+        Padawan { id: 10, master_id: 2, name: "Anakin".to_string() },
+        Padawan { id: 11, master_id: 3, name: "Luke".to_string() },
+    ];
+    let masters = vec![
+        Jedi { id: 2, name: "Obi-Wan".to_string() },
+    ];
+
+    let grouped = master().group(padawans, masters, |p: &Padawan| p.master_id, |j: &Jedi| j.id);
+    assert_eq!(grouped.len(), 2);
+    assert_eq!(grouped[0].1.iter().map(|j| j.id).collect::<Vec<i32>>(), vec![2]);
+    assert!(grouped[1].1.is_empty());
+}
@@ -0,0 +1,48 @@
+use deuterium::*;
+
+fn jedi_table() -> TableDef {
+    let jedi = TableDef::new("jedi");
+    let deleted_at = NamedField::<Option<i32>>::field_of("deleted_at", &jedi);
+    jedi.scope(deleted_at.is_null())
+}
+
+fn strong<Q: Queryable>(query: &Q) -> Q {
+    let force_level = NamedField::<i8>::field_of("force_level", &TableDef::new("jedi"));
+    query.where_(force_level.gt(100))
+}
+
+#[test]
+fn scope_select() {
+
+    let jedi = jedi_table();
+    let name = NamedField::<String>::field_of("name", &jedi);
+
+    assert_sql!(jedi.select_all(), "SELECT * FROM jedi WHERE deleted_at IS NULL;");
+    assert_sql!(jedi.select_all().where_(name.is("Luke".to_string())),
+        "SELECT * FROM jedi WHERE (deleted_at IS NULL) AND (name = $1);");
+    assert_sql!(jedi.select_all().where_(name.is("Luke".to_string())).unscoped(),
+        "SELECT * FROM jedi WHERE name = $1;");
+    assert_sql!(jedi.select_all().apply_scope(strong),
+        "SELECT * FROM jedi WHERE (deleted_at IS NULL) AND (force_level > $1);");
+
+    let jedi = jedi.scope(name.like("L%".to_string()));
+    assert_sql!(jedi.select_all(), "SELECT * FROM jedi WHERE (deleted_at IS NULL) AND (name LIKE $1);");
+}
+
+#[test]
+fn scope_update_delete() {
+
+    let jedi = jedi_table();
+    let name = NamedField::<String>::field_of("name", &jedi);
+
+    assert_sql!(jedi.update().field(name.set(&"Luke".to_string())), "UPDATE jedi SET name = $1 WHERE true = false;");
+    assert_sql!(jedi.update().field(name.set(&"Luke".to_string())).all(),
+        "UPDATE jedi SET name = $1 WHERE deleted_at IS NULL;");
+    assert_sql!(jedi.update().field(name.set(&"Luke".to_string())).where_(name.is("Anakin".to_string())),
+        "UPDATE jedi SET name = $1 WHERE (deleted_at IS NULL) AND (name = $2);");
+
+    assert_sql!(jedi.delete(), "DELETE FROM jedi WHERE true = false;");
+    assert_sql!(jedi.delete().all(), "DELETE FROM jedi WHERE deleted_at IS NULL;");
+    assert_sql!(jedi.delete().all().unscoped(), "DELETE FROM jedi;");
+    assert_sql!(jedi.delete().apply_scope(strong), "DELETE FROM jedi WHERE (deleted_at IS NULL) AND (force_level > $1);");
+}
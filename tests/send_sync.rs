@@ -0,0 +1,36 @@
+use std::sync::Arc;
+use std::thread;
+use deuterium::*;
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn ast_is_send_sync() {
+    assert_send_sync::<SharedPredicate>();
+    assert_send_sync::<SharedExpression>();
+    assert_send_sync::<SharedFrom>();
+    assert_send_sync::<SharedTable>();
+    assert_send_sync::<SharedField>();
+    assert_send_sync::<SharedSelectQuery>();
+    assert_send_sync::<SharedFieldUpdate>();
+    assert_send_sync::<SelectQuery<(), LimitMany, ()>>();
+    assert_send_sync::<UpdateQuery<(), NoResult, ()>>();
+    assert_send_sync::<DeleteQuery<(), NoResult, ()>>();
+}
+
+#[test]
+fn query_shared_between_threads() {
+
+    let jedi_table = TableDef::new("jedi");
+    let name = NamedField::<String>::field_of("name", &jedi_table);
+    let query = Arc::new(jedi_table.select_all().where_(name.is("Luke".to_string())));
+
+    let handles: Vec<_> = (0..4).map(|_| {
+        let query = query.clone();
+        thread::spawn(move || query.to_final_sql(&mut SqlContext::new(Box::new(sql::PostgreSqlAdapter))))
+    }).collect();
+
+    for handle in handles {
+        assert_eq!(handle.join().unwrap().unwrap(), "SELECT * FROM jedi WHERE name = $1;");
+    }
+}
@@ -0,0 +1,114 @@
+use serde_json;
+use deuterium::*;
+
+fn render<Q: QueryToSql>(query: &Q, adapter: Box<dyn sql::SqlAdapter>) -> (String, Vec<Value>) {
+    let mut ctx = SqlContext::new(adapter);
+    ctx.set_tenant(42i32);
+    let sql = query.to_final_sql(&mut ctx).unwrap();
+    (sql, ctx.values().to_vec())
+}
+
+fn assert_same<Q: QueryToSql>(loaded: &Q, query: &Q) {
+    assert_eq!(render(loaded, Box::new(sql::PostgreSqlAdapter)), render(query, Box::new(sql::PostgreSqlAdapter)));
+    assert_eq!(render(loaded, Box::new(sql::MysqlAdapter)), render(query, Box::new(sql::MysqlAdapter)));
+}
+
+#[test]
+fn serde_select() {
+
+    let jedi_table = TableDef::new("jedi").soft_delete("deleted_at").tenant("tenant_id");
+    let planet_table = TableDef::new_with_alias("planet", "p");
+    let name = NamedField::<String>::field_of("name", &jedi_table);
+    let force_level = NamedField::<i32>::field_of("force_level", &jedi_table);
+    let side = NamedField::<bool>::field_of("side", &jedi_table);
+    let planet_id = NamedField::<i32>::field_of("planet_id", &jedi_table);
+    let master = NamedField::<Option<String>>::field_of("master", &jedi_table);
+    let id = NamedField::<i32>::field_of("id", &planet_table);
+
+    let query = jedi_table.select_2(&name, &force_level.sum())
+        .distinct()
+        .left_join(&planet_table, id.is(planet_id.clone()))
+        .where_(name.like("Sky%".to_string()).or(side.is(true))
+            .and(force_level.in_range(10, 100))
+            .and(planet_id.in_(jedi_table.select_1(&planet_id).where_(name.is(Placeholder::named("name")))))
+            .and(master.is_null().exclude()))
+        .group_by(&[&name])
+        .order_by(&name)
+        .limit_many(10)
+        .offset(20)
+        .for_update();
+
+    let json = serde_json::to_string(&query).unwrap();
+    let loaded: SelectQuery<(String, i64), LimitMany, ()> = serde_json::from_str(&json).unwrap();
+    assert_same(&loaded, &query);
+
+    let subquery = jedi_table.select_all().from_as("j").select_all();
+    let loaded: SelectQuery<(), LimitMany, ()> = serde_json::from_str(&serde_json::to_string(&subquery).unwrap()).unwrap();
+    assert_same(&loaded, &subquery);
+}
+
+#[test]
+fn serde_modifications() {
+
+    let jedi_table = TableDef::new("jedi").soft_delete("deleted_at");
+    let name = NamedField::<String>::field_of("name", &jedi_table);
+    let force_level = NamedField::<i32>::field_of("force_level", &jedi_table);
+
+    let mut insert = jedi_table.insert_fields(&[&name, &force_level]);
+    insert.push_untyped(&["Luke".to_string().as_expr(), 100i32.as_expr()]);
+    let insert = insert.returning_1(&name);
+    let json = serde_json::to_string(&insert).unwrap();
+    assert_same(&serde_json::from_str(&json).unwrap(), &insert);
+
+    let update = jedi_table.update()
+        .field(name.set(&"Anakin".to_string()))
+        .field(force_level.set_default())
+        .where_(name.is("Vader".to_string()))
+        .returning_all();
+    let json = serde_json::to_string(&update).unwrap();
+    assert_same(&serde_json::from_str(&json).unwrap(), &update);
+
+    let delete = jedi_table.delete().where_(force_level.lt(0));
+    let json = serde_json::to_string(&delete).unwrap();
+    assert_same(&serde_json::from_str(&json).unwrap(), &delete);
+}
+
+#[test]
+fn serde_document() {
+
+    let json = r#"{
+        "distinct": null,
+        "select": {"Only": [{"Function": ["COUNT", []]}]},
+        "from": {"Table": {"name": "jedi", "alias": null, "scopes": [], "soft_delete": null, "tenant": null}},
+        "joins": [],
+        "scope": null,
+        "soft_delete": null,
+        "deleted": "Exclude",
+        "where_": {"Inequality": {
+            "field": {"Field": {"name": "force_level", "table_name": "jedi", "qual": null}},
+            "value": {"Value": {"I64": 9000}},
+            "inequality": "GreaterThan"
+        }},
+        "group_by": null,
+        "having": null,
+        "limit": null,
+        "offset": null,
+        "order_by": [],
+        "for_": null
+    }"#;
+
+    let query: SelectQuery<(), LimitMany, ()> = serde_json::from_str(json).unwrap();
+    assert_eq!(render(&query, Box::new(sql::PostgreSqlAdapter)),
+        ("SELECT COUNT(*) FROM jedi WHERE force_level > $1;".to_string(), vec![Value::I64(9000)]));
+    assert_eq!(render(&query, Box::new(sql::MysqlAdapter)),
+        ("SELECT COUNT(*) FROM jedi WHERE force_level > ?;".to_string(), vec![Value::I64(9000)]));
+
+    // Held values keep their type
+    let reloaded = serde_json::to_value(&query).unwrap();
+    assert_eq!(reloaded["where_"]["Inequality"]["value"], serde_json::json!({"Value": {"I64": 9000}}));
+
+    let error = serde_json::from_str::<SelectQuery<(), LimitMany, ()>>(
+        &json.replace(r#"["COUNT", []]"#, r#"["MEDIAN", [{"Raw": {"content": "force_level"}}]]"#)
+    ).unwrap_err();
+    assert!(error.to_string().starts_with("unknown function MEDIAN"));
+}
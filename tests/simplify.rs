@@ -0,0 +1,79 @@
+use std::sync::Arc;
+use deuterium::*;
+use deuterium::simplify::simplify;
+
+fn raw(content: &str) -> SharedPredicate {
+    Arc::new(Box::new(RawPredicate::new(content)) as Box<dyn Predicate>)
+}
+
+#[test]
+fn simplify_junctions() {
+
+    let jedi_table = TableDef::new("jedi");
+    let name = NamedField::<String>::field_of("name", &jedi_table);
+    let force_level = NamedField::<i32>::field_of("force_level", &jedi_table);
+    let side = NamedField::<bool>::field_of("side", &jedi_table);
+    let query = |predicate: SharedPredicate| jedi_table.select_all().where_(simplify(&predicate));
+
+    // Nested junctions are flattened, constants and duplicates dropped
+    assert_sql!(query(side.is(true).and(force_level.gt(10)).and(raw("TRUE")).and(side.is(true))),
+        "SELECT * FROM jedi WHERE side = $1 AND force_level > $2;");
+    assert_sql!(query(side.is(true).and(name.is("Luke".to_string()).or(name.is("Leia".to_string())).or(force_level.gt(10)))),
+        "SELECT * FROM jedi WHERE side = $1 AND (name = $2 OR name = $3 OR force_level > $4);");
+    assert_sql!(query(side.is(true).or(raw("true"))), "SELECT * FROM jedi WHERE true;");
+    assert_sql!(query(side.is(true).and(force_level.gt(10).and(side.is(true)))),
+        "SELECT * FROM jedi WHERE side = $1 AND force_level > $2;");
+    assert_sql!(query(side.is(true).or(force_level.gt(10).or(side.is(true).or(force_level.gt(10))))),
+        "SELECT * FROM jedi WHERE side = $1 OR force_level > $2;");
+    assert_sql!(query(raw("name = 'Luke' OR name = 'Leia'").and(side.is(true))),
+        "SELECT * FROM jedi WHERE (name = 'Luke' OR name = 'Leia') AND side = $1;");
+    assert_sql!(query(force_level.in_range(1, 10).or(side.is(true))),
+        "SELECT * FROM jedi WHERE (force_level >= $1 AND force_level <= $2) OR side = $3;");
+
+    // NOT is pushed to the leaves with De Morgan's laws
+    assert_sql!(query(side.is(true).and(force_level.gt(10).or(name.like("Sky%".to_string()))).exclude()),
+        "SELECT * FROM jedi WHERE side != $1 OR (force_level <= $2 AND name NOT LIKE $3);");
+    assert_sql!(query(side.is(true).exclude().exclude()), "SELECT * FROM jedi WHERE side = $1;");
+
+    // IN with one element
+    assert_sql!(query(name.in_(vec!["Luke".to_string()])), "SELECT * FROM jedi WHERE name = $1;");
+    assert_sql!(query(name.in_(vec!["Luke".to_string()]).exclude()), "SELECT * FROM jedi WHERE name != $1;");
+
+    // Same rendering in pretty mode
+    let mut ctx = SqlContext::new(Box::new(sql::PostgreSqlAdapter));
+    ctx.set_pretty(true);
+    let predicate = simplify(&side.is(true).and(name.is("Luke".to_string()).or(force_level.gt(10))).and(force_level.lt(100)));
+    assert_eq!(jedi_table.select_all().where_(predicate).to_final_sql(&mut ctx).unwrap(), "SELECT *
+FROM jedi
+WHERE side = $1
+    AND (
+        name = $2
+        OR force_level > $3
+    )
+    AND force_level < $4;");
+}
+
+#[test]
+fn simplify_contradictions() {
+
+    let jedi_table = TableDef::new("jedi");
+    let force_level = NamedField::<i32>::field_of("force_level", &jedi_table);
+    let master = NamedField::<Option<String>>::field_of("master", &jedi_table);
+    let other_force_level = NamedField::<i32>::field_of("force_level", &jedi_table).qual_with("j");
+    let query = |predicate: SharedPredicate| jedi_table.select_all().where_(simplify(&predicate));
+
+    assert_sql!(query(force_level.is(1).and(force_level.is(2))), "SELECT * FROM jedi WHERE false;");
+    assert_sql!(query(force_level.is(1).and(force_level.is(2)).or(force_level.lt(0))),
+        "SELECT * FROM jedi WHERE force_level < $1;");
+    assert_sql!(query(force_level.gt(1).and(force_level.gt(1).exclude())), "SELECT * FROM jedi WHERE false;");
+    assert_sql!(query(master.is(Some("Yoda".to_string())).and(master.is_null())), "SELECT * FROM jedi WHERE false;");
+    assert_sql!(query(master.is_null().and(master.not_null())), "SELECT * FROM jedi WHERE false;");
+    assert_sql!(query(raw("1 = 1").and(raw("FALSE"))), "SELECT * FROM jedi WHERE false;");
+
+    // Not contradictions: same value, other column, an OR
+    assert_sql!(query(force_level.is(1).and(force_level.is(1))), "SELECT * FROM jedi WHERE force_level = $1;");
+    assert_sql!(query(force_level.is(1).and(other_force_level.is(2))),
+        "SELECT * FROM jedi WHERE force_level = $1 AND j.force_level = $2;");
+    assert_sql!(query(force_level.is(1).or(force_level.is(2))),
+        "SELECT * FROM jedi WHERE force_level = $1 OR force_level = $2;");
+}
@@ -0,0 +1,85 @@
+use deuterium::*;
+
+#[derive(Debug)]
+struct SqliteAdapter;
+
+impl sql::SqlAdapter for SqliteAdapter {
+    fn placeholder(&self, _: usize) -> String {
+        "?".to_string()
+    }
+
+    fn current_timestamp(&self) -> &'static str { "datetime('now')" }
+}
+
+fn jedi_table() -> TableDef {
+    TableDef::new("jedi").soft_delete("deleted_at")
+}
+
+#[test]
+fn soft_delete_select() {
+
+    let jedi = jedi_table();
+    let name = NamedField::<String>::field_of("name", &jedi);
+
+    assert_sql!(jedi.select_all(), "SELECT * FROM jedi WHERE jedi.deleted_at IS NULL;");
+    assert_sql!(jedi.select_all().where_(name.is("Luke".to_string())),
+        "SELECT * FROM jedi WHERE (jedi.deleted_at IS NULL) AND (name = $1);");
+    assert_sql!(jedi.select_all().with_deleted(), "SELECT * FROM jedi;");
+    assert_sql!(jedi.select_all().only_deleted(), "SELECT * FROM jedi WHERE jedi.deleted_at IS NOT NULL;");
+
+    let j = jedi.alias("j");
+    assert_sql!(j.select_all(), "SELECT * FROM jedi AS j WHERE j.deleted_at IS NULL;");
+}
+
+#[test]
+fn soft_delete_delete() {
+
+    let jedi = jedi_table();
+    let name = NamedField::<String>::field_of("name", &jedi);
+
+    assert_sql!(jedi.delete().where_(name.is("Luke".to_string())),
+        "UPDATE jedi SET deleted_at = CURRENT_TIMESTAMP WHERE (jedi.deleted_at IS NULL) AND (name = $1);");
+    assert_sql!(jedi.delete().where_(name.is("Luke".to_string())).returning_all(),
+        "UPDATE jedi SET deleted_at = CURRENT_TIMESTAMP WHERE (jedi.deleted_at IS NULL) AND (name = $1) RETURNING *;");
+    assert_sql!(jedi.delete().all(), "UPDATE jedi SET deleted_at = CURRENT_TIMESTAMP WHERE jedi.deleted_at IS NULL;");
+    assert_sql!(jedi.delete(), "UPDATE jedi SET deleted_at = CURRENT_TIMESTAMP WHERE true = false;");
+
+    let sql = jedi.delete().where_(name.is("Luke".to_string())).to_final_sql(&mut SqlContext::new(Box::new(SqliteAdapter))).unwrap();
+    assert_eq!(sql, "UPDATE jedi SET deleted_at = datetime('now') WHERE (jedi.deleted_at IS NULL) AND (name = ?);");
+    assert_sql!(jedi.delete().where_(name.is("Luke".to_string())).force_delete(),
+        "DELETE FROM jedi WHERE name = $1;");
+}
+
+#[test]
+fn soft_delete_using() {
+
+    let jedi = jedi_table();
+    let padawan = TableDef::new("padawan").soft_delete("deleted_at");
+    let id = NamedField::<i32>::field_of("id", &jedi);
+    let master_id = NamedField::<i32>::field_of("master_id", &padawan);
+
+    assert_sql!(jedi.delete().using(&padawan).where_(id.qual().is(master_id.qual())),
+        "UPDATE jedi SET deleted_at = CURRENT_TIMESTAMP FROM padawan WHERE ((jedi.deleted_at IS NULL) AND (padawan.deleted_at IS NULL)) AND (jedi.id = padawan.master_id);");
+    assert_sql!(jedi.delete().using(&padawan).where_(id.qual().is(master_id.qual())).force_delete(),
+        "DELETE FROM jedi USING padawan WHERE (padawan.deleted_at IS NULL) AND (jedi.id = padawan.master_id);");
+    assert_sql!(TableDef::new("sith").update().field(id.set(&1)).from(&padawan).all(),
+        "UPDATE sith SET id = $1 FROM padawan WHERE padawan.deleted_at IS NULL;");
+}
+
+#[test]
+fn soft_delete_join() {
+
+    let jedi = jedi_table();
+    let padawan = TableDef::new("padawan").soft_delete("deleted_at");
+    let id = NamedField::<i32>::field_of("id", &jedi);
+    let master_id = NamedField::<i32>::field_of("master_id", &padawan);
+
+    assert_sql!(jedi.select_all().left_join(&padawan, master_id.qual().is(id.qual())),
+        "SELECT * FROM jedi LEFT JOIN padawan ON (padawan.master_id = jedi.id) AND (padawan.deleted_at IS NULL) WHERE jedi.deleted_at IS NULL;");
+    assert_sql!(jedi.select_all().cross_join(&padawan),
+        "SELECT * FROM jedi CROSS JOIN (SELECT * FROM padawan WHERE padawan.deleted_at IS NULL) as padawan WHERE jedi.deleted_at IS NULL;");
+    assert_sql!(jedi.select_all().left_join(&padawan, master_id.qual().is(id.qual())).with_deleted(),
+        "SELECT * FROM jedi LEFT JOIN padawan ON padawan.master_id = jedi.id;");
+    assert_sql!(jedi.select_all().right_join(&padawan, master_id.qual().is(id.qual())).only_deleted(),
+        "SELECT * FROM (SELECT * FROM jedi WHERE jedi.deleted_at IS NOT NULL) as jedi RIGHT JOIN (SELECT * FROM padawan WHERE padawan.deleted_at IS NULL) as padawan ON padawan.master_id = jedi.id;");
+}
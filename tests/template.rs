@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+use deuterium::*;
+
+#[test]
+fn template() {
+
+    let jedi_table = TableDef::new("jedi");
+    let name = NamedField::<String>::field_of("name", &jedi_table);
+    let force_level = NamedField::<i8>::field_of("force_level", &jedi_table);
+
+    let query = jedi_table.select_1(&name)
+        .where_(name.is(Placeholder::named("name")).and(force_level.gt(100i8)))
+        .or(name.like(Placeholder::named("name")));
+
+    let template = query.prepare(&mut SqlContext::new(Box::new(sql::PostgreSqlAdapter))).unwrap();
+    assert_eq!(template.get_sql(), "SELECT name FROM jedi WHERE ((name = $1) AND (force_level > $2)) OR (name LIKE $1);");
+
+    for jedi in ["Luke", "Yoda"].iter() {
+        let mut values = HashMap::new();
+        values.insert("name", Value::String(jedi.to_string()));
+        assert_eq!(template.bind(&values).unwrap(), vec![Value::String(jedi.to_string()), Value::I8(100)]);
+    }
+}
+
+#[test]
+fn template_mysql() {
+
+    let jedi_table = TableDef::new("jedi");
+    let name = NamedField::<String>::field_of("name", &jedi_table);
+
+    let query = jedi_table.select_1(&name)
+        .where_(name.is(Placeholder::named("name")))
+        .or(name.like(Placeholder::named("name")));
+
+    let template = query.prepare(&mut SqlContext::new(Box::new(sql::MysqlAdapter))).unwrap();
+    assert_eq!(template.get_sql(), "SELECT name FROM jedi WHERE (name = ?) OR (name LIKE ?);");
+
+    let mut values = HashMap::new();
+    values.insert("name", Value::String("Luke".to_string()));
+    assert_eq!(template.bind(&values).unwrap(), vec![Value::String("Luke".to_string()), Value::String("Luke".to_string())]);
+    assert!(template.bind(&HashMap::<String, Value>::new()).is_err());
+}
+
+#[test]
+fn template_unbound_order() {
+
+    let jedi_table = TableDef::new("jedi");
+    let name = NamedField::<String>::field_of("name", &jedi_table);
+    let master = NamedField::<String>::field_of("master", &jedi_table);
+
+    let query = jedi_table.select_1(&name)
+        .where_(name.is(Placeholder::named("name")))
+        .or(master.is(Placeholder::named("master")))
+        .or(master.like(Placeholder::named("pattern")));
+
+    let template = query.prepare(&mut SqlContext::new(Box::new(sql::PostgreSqlAdapter))).unwrap();
+    assert_eq!(template.get_named(), &[("name".to_string(), 0), ("master".to_string(), 1), ("pattern".to_string(), 2)][..]);
+
+    // The first unbound placeholder in the query is reported, every time
+    let mut values = HashMap::new();
+    values.insert("name", Value::String("Luke".to_string()));
+    for _ in 0..10 {
+        match template.bind(&values) {
+            Err(Error::UnboundPlaceholder { ref name }) => assert_eq!(name, "master"),
+            other => panic!("unexpected result: {:?}", other)
+        }
+    }
+}
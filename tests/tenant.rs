@@ -0,0 +1,127 @@
+use deuterium::*;
+
+fn tenant_sql<Q: QueryToSql>(query: &Q) -> String {
+    let mut ctx = SqlContext::new(Box::new(sql::PostgreSqlAdapter));
+    ctx.set_tenant(42i32);
+    query.to_final_sql(&mut ctx).unwrap()
+}
+
+fn tables() -> (TableDef, TableDef) {
+    (TableDef::new("jedi").tenant("tenant_id"), TableDef::new("padawan").tenant("tenant_id"))
+}
+
+#[test]
+fn tenant_select() {
+
+    let (jedi, padawan) = tables();
+    let id = NamedField::<i32>::field_of("id", &jedi);
+    let master_id = NamedField::<i32>::field_of("master_id", &padawan);
+    let name = NamedField::<String>::field_of("name", &jedi);
+
+    assert_eq!(tenant_sql(&jedi.select_all()), "SELECT * FROM jedi WHERE jedi.tenant_id = $1;");
+    assert_eq!(tenant_sql(&jedi.select_all().where_(name.is("Luke".to_string()))),
+        "SELECT * FROM jedi WHERE (jedi.tenant_id = $1) AND (name = $2);");
+
+    assert_eq!(tenant_sql(&jedi.select_all().left_join(&padawan, master_id.qual().is(id.qual()))),
+        "SELECT * FROM jedi LEFT JOIN padawan ON (padawan.master_id = jedi.id) AND (padawan.tenant_id = $1) WHERE jedi.tenant_id = $2;");
+    assert_eq!(tenant_sql(&jedi.select_all().cross_join(&padawan)),
+        "SELECT * FROM jedi CROSS JOIN (SELECT * FROM padawan WHERE padawan.tenant_id = $1) as padawan WHERE jedi.tenant_id = $2;");
+
+    let sub = jedi.alias("j").select_all().alias("sub");
+    assert_eq!(tenant_sql(&sub.select_all()),
+        "SELECT * FROM (SELECT * FROM jedi AS j WHERE j.tenant_id = $1) as sub;");
+}
+
+#[test]
+fn tenant_joins() {
+
+    let (jedi, padawan) = tables();
+    let id = NamedField::<i32>::field_of("id", &jedi);
+    let master_id = NamedField::<i32>::field_of("master_id", &padawan);
+    let on = || master_id.qual().is(id.qual());
+    let query = jedi.select_all();
+
+    let filtered_jedi = "(SELECT * FROM jedi WHERE jedi.tenant_id = $1) as jedi";
+    let filtered_padawan = "(SELECT * FROM padawan WHERE padawan.tenant_id = $2) as padawan";
+
+    // The joined table is filtered in ON unless the join preserves its rows
+    assert_eq!(tenant_sql(&query.inner_join(&padawan, on())),
+        "SELECT * FROM jedi INNER JOIN padawan ON (padawan.master_id = jedi.id) AND (padawan.tenant_id = $1) WHERE jedi.tenant_id = $2;");
+    assert_eq!(tenant_sql(&query.left_join(&padawan, on())),
+        "SELECT * FROM jedi LEFT JOIN padawan ON (padawan.master_id = jedi.id) AND (padawan.tenant_id = $1) WHERE jedi.tenant_id = $2;");
+    assert_eq!(tenant_sql(&query.left_outer_join(&padawan, on())),
+        "SELECT * FROM jedi LEFT OUTER JOIN padawan ON (padawan.master_id = jedi.id) AND (padawan.tenant_id = $1) WHERE jedi.tenant_id = $2;");
+
+    // Right and full joins extend rows of jedi with NULLs, so both sides are filtered before
+    assert_eq!(tenant_sql(&query.right_join(&padawan, on())),
+        format!("SELECT * FROM {} RIGHT JOIN {} ON padawan.master_id = jedi.id;", filtered_jedi, filtered_padawan));
+    assert_eq!(tenant_sql(&query.right_outer_join(&padawan, on())),
+        format!("SELECT * FROM {} RIGHT OUTER JOIN {} ON padawan.master_id = jedi.id;", filtered_jedi, filtered_padawan));
+    assert_eq!(tenant_sql(&query.full_join(&padawan, on())),
+        format!("SELECT * FROM {} FULL JOIN {} ON padawan.master_id = jedi.id;", filtered_jedi, filtered_padawan));
+    assert_eq!(tenant_sql(&query.full_outer_join(&padawan, on())),
+        format!("SELECT * FROM {} FULL OUTER JOIN {} ON padawan.master_id = jedi.id;", filtered_jedi, filtered_padawan));
+
+    // Natural joins have no ON clause, and WHERE would drop rows extended with NULLs
+    assert_eq!(tenant_sql(&query.natural_join(&padawan)),
+        "SELECT * FROM jedi NATURAL JOIN (SELECT * FROM padawan WHERE padawan.tenant_id = $1) as padawan WHERE jedi.tenant_id = $2;");
+    assert_eq!(tenant_sql(&query.natural_left_join(&padawan)),
+        "SELECT * FROM jedi NATURAL LEFT JOIN (SELECT * FROM padawan WHERE padawan.tenant_id = $1) as padawan WHERE jedi.tenant_id = $2;");
+    assert_eq!(tenant_sql(&query.natural_right_join(&padawan)),
+        format!("SELECT * FROM {} NATURAL RIGHT JOIN {};", filtered_jedi, filtered_padawan));
+    assert_eq!(tenant_sql(&query.natural_full_join(&padawan)),
+        format!("SELECT * FROM {} NATURAL FULL JOIN {};", filtered_jedi, filtered_padawan));
+
+    let j = jedi.alias("j");
+    let j_id = NamedField::<i32>::field_of("id", &j);
+    assert_eq!(tenant_sql(&j.select_all().right_join(&padawan, master_id.qual().is(j_id))),
+        "SELECT * FROM (SELECT * FROM jedi AS j WHERE j.tenant_id = $1) as j RIGHT JOIN (SELECT * FROM padawan WHERE padawan.tenant_id = $2) as padawan ON padawan.master_id = j.id;");
+}
+
+#[test]
+fn tenant_update_delete() {
+
+    let (jedi, _) = tables();
+    let name = NamedField::<String>::field_of("name", &jedi);
+
+    assert_eq!(tenant_sql(&jedi.update().field(name.set(&"Luke".to_string())).where_(name.is("Anakin".to_string()))),
+        "UPDATE jedi SET name = $1 WHERE (jedi.tenant_id = $2) AND (name = $3);");
+    assert_eq!(tenant_sql(&jedi.update().field(name.set(&"Luke".to_string())).all()),
+        "UPDATE jedi SET name = $1 WHERE jedi.tenant_id = $2;");
+    assert_eq!(tenant_sql(&jedi.delete()), "DELETE FROM jedi WHERE true = false;");
+    assert_eq!(tenant_sql(&jedi.delete().where_(name.is("Luke".to_string()))),
+        "DELETE FROM jedi WHERE (jedi.tenant_id = $1) AND (name = $2);");
+}
+
+#[test]
+fn tenant_insert() {
+
+    let (jedi, _) = tables();
+    let name = NamedField::<String>::field_of("name", &jedi);
+
+    let mut query = jedi.insert_fields(&[&name]);
+    query.push_untyped(&["Luke".to_string().as_expr()]);
+    query.push_untyped(&["Leia".to_string().as_expr()]);
+    assert_eq!(tenant_sql(&query), "INSERT INTO jedi (name, tenant_id) VALUES\n    ($1, $2),\n    ($3, $4);");
+
+    assert_eq!(tenant_sql(&jedi.insert_all()), "INSERT INTO jedi (tenant_id) VALUES ($1);");
+
+    // Values are held in text order, which MySQL placeholders rely on
+    let sith = TableDef::new("sith");
+    let sith_name = NamedField::<String>::field_of("name", &sith);
+    let query = jedi.insert_fields(&[&name]).from_select(sith.select(&[&sith_name]).where_(sith_name.is("Vader".to_string())));
+    let mut ctx = SqlContext::new(Box::new(sql::MysqlAdapter));
+    ctx.set_tenant(42i32);
+    let compiled = query.build(&mut ctx).unwrap();
+    assert_eq!(compiled.sql, "INSERT INTO jedi (name, tenant_id) SELECT source.*, ? FROM (SELECT name FROM sith WHERE name = ?) AS source;");
+    assert_eq!(compiled.params, vec![Value::I32(42), Value::String("Vader".to_string())]);
+}
+
+#[test]
+fn tenant_missing() {
+    let (jedi, _) = tables();
+    match jedi.select_all().to_final_sql(&mut SqlContext::new(Box::new(sql::PostgreSqlAdapter))) {
+        Err(Error::MissingTenant { ref table }) => assert_eq!(table, "jedi"),
+        other => panic!("unexpected result: {:?}", other)
+    }
+}
@@ -1,12 +1,14 @@
 extern crate deuterium;
 extern crate chrono;
+extern crate serde_json;
+extern crate uuid;
 
 use deuterium::*;
 
 #[macro_export]
 macro_rules! assert_sql {
     ($query:expr, $s:expr) => (
-        assert_eq!(&$query.to_final_sql(&mut SqlContext::new(Box::new(sql::PostgreSqlAdapter))), $s)
+        assert_eq!(&$query.to_final_sql(&mut SqlContext::new(Box::new(sql::PostgreSqlAdapter))).unwrap(), $s)
     )
 }
 
@@ -19,6 +21,29 @@ mod insert;
 mod update;
 mod delete;
 mod placeholder;
+mod codegen;
+mod codegen_schema;
+mod model;
+mod tracked;
+mod relation;
+mod scope;
+mod soft_delete;
+mod tenant;
+mod send_sync;
+mod error;
+mod build;
+mod template;
+mod visit;
+mod fingerprint;
+mod pretty;
+mod debug_sql;
+mod parse;
+mod filter;
+mod eval;
+mod simplify;
+mod negation;
+#[cfg(feature = "serde")]
+mod serde;
 
 #[test]
 fn select_order() {
@@ -0,0 +1,100 @@
+use deuterium::*;
+
+#[derive(Clone, Debug, Model)]
+#[model(table = "jedi")]
+pub struct Jedi {
+    #[primary_key]
+    pub id: i32,
+    pub name: String,
+    pub force_level: Option<i8>,
+    pub version: i32,
+}
+
+fn luke() -> Tracked<Jedi> {
+    Tracked::new(Jedi { id: 1, name: "Luke".to_string(), force_level: Some(100), version: 3 })
+}
+
+#[test]
+fn tracked_update() {
+
+    let jedi = JediTable::new();
+
+    let mut luke = luke();
+    assert!(luke.update_query().is_none());
+
+    luke.set(&jedi.force_level, Some(120)).unwrap();
+    assert_eq!(luke.force_level, Some(120));
+    assert_sql!(luke.update_query().unwrap(), "UPDATE jedi SET force_level = $1 WHERE id = $2;");
+
+    luke.set(&jedi.name, "Luke Skywalker".to_string()).unwrap().set(&jedi.force_level, Some(110)).unwrap();
+    assert_eq!(luke.get_changed(), &vec!["force_level".to_string(), "name".to_string()]);
+    assert_sql!(luke.update_query().unwrap(), "UPDATE jedi SET force_level = $1, name = $2 WHERE id = $3;");
+
+    luke.reset();
+    assert!(!luke.is_changed());
+}
+
+#[test]
+fn tracked_version() {
+
+    let jedi = JediTable::new();
+
+    let mut luke = luke().with_version(&jedi.version).unwrap();
+    assert!(luke.update_query().is_none());
+
+    luke.set(&jedi.name, "Luke Skywalker".to_string()).unwrap();
+    assert_sql!(luke.update_query().unwrap(),
+        "UPDATE jedi SET name = $1, version = version + 1 WHERE (id = $2) AND (version = $3);");
+
+    luke.saved();
+    assert_eq!(luke.version, 4);
+    assert!(!luke.is_changed());
+
+    luke.saved();
+    assert_eq!(luke.version, 4);
+
+    assert_eq!(luke.set(&jedi.version, 10).unwrap_err(), TrackedError::VersionColumn("version".to_string()));
+    assert_eq!(luke.version, 4);
+
+    let mut anakin = self::luke();
+    anakin.set(&jedi.version, 10).unwrap();
+    let anakin = anakin.with_version(&jedi.version).unwrap();
+    assert!(!anakin.is_changed());
+
+    assert_eq!(self::luke().with_version(&jedi.name).unwrap_err(), TrackedError::UnsupportedVersion("name".to_string()));
+}
+
+#[test]
+fn tracked_foreign_field() {
+
+    let sith = TableDef::new("sith");
+    let rage = NamedField::<i32>::field_of("rage", &sith);
+    assert_eq!(luke().set(&rage, 100).unwrap_err(),
+        TrackedError::ForeignColumn { column: "rage".to_string(), table: "sith".to_string() });
+
+    let jedi = TableDef::new("jedi");
+    let rage = NamedField::<i32>::field_of("rage", &jedi);
+    assert_eq!(luke().set(&rage, 100).unwrap_err(), TrackedError::UnknownColumn("rage".to_string()));
+
+    let id = NamedField::<i64>::field_of("id", &jedi);
+    assert_eq!(luke().set(&id, 100).unwrap_err(), TrackedError::UnknownColumn("id".to_string()));
+}
+
+#[test]
+fn tracked_primary_key() {
+
+    let jedi = JediTable::new();
+
+    let mut luke = luke();
+    assert_eq!(luke.set(&jedi.id, 2).unwrap_err(), TrackedError::PrimaryKey("id".to_string()));
+    assert_eq!(luke.id, 1);
+    assert!(!luke.is_changed());
+
+    luke.set(&jedi.name, "Luke Skywalker".to_string()).unwrap();
+    assert_eq!(luke.set(&jedi.id, 2).unwrap_err(), TrackedError::PrimaryKey("id".to_string()));
+    let compiled = luke.update_query().unwrap().build(&mut SqlContext::new(Box::new(sql::PostgreSqlAdapter))).unwrap();
+    assert_eq!(compiled.sql, "UPDATE jedi SET name = $1 WHERE id = $2;");
+    assert_eq!(compiled.params, vec![Value::String("Luke Skywalker".to_string()), Value::I32(1)]);
+
+    assert_eq!(self::luke().with_version(&jedi.id).unwrap_err(), TrackedError::PrimaryKey("id".to_string()));
+}
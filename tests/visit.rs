@@ -0,0 +1,202 @@
+use deuterium::*;
+
+struct Tables(Vec<String>);
+
+impl Visitor for Tables {
+    fn visit_table(&mut self, table: &dyn Table) {
+        self.0.push(table.get_table_name().to_string())
+    }
+}
+
+struct Values(Vec<Value>);
+
+impl Visitor for Values {
+    fn visit_value(&mut self, value: &Value) {
+        self.0.push(value.clone())
+    }
+}
+
+struct Columns(Vec<String>);
+
+impl Visitor for Columns {
+    fn visit_field(&mut self, field: &dyn Field) {
+        self.0.push(field.name().to_string())
+    }
+}
+
+#[test]
+fn visit_tables() {
+
+    let jedi_table = TableDef::new("jedi");
+    let planet_table = TableDef::new("planet");
+    let ship_table = TableDef::new("ship");
+    let planet_id = NamedField::<i32>::field_of("planet_id", &jedi_table);
+    let id = NamedField::<i32>::field_of("id", &planet_table);
+    let pilot_id = NamedField::<i32>::field_of("pilot_id", &ship_table);
+
+    let query = jedi_table.select_all()
+        .inner_join(&planet_table, planet_id.is(id.clone()))
+        .where_(id.in_(ship_table.select_1(&pilot_id)));
+
+    let mut tables = Tables(vec![]);
+    query.accept(&mut tables);
+    assert_eq!(tables.0, vec!["jedi", "planet", "ship"]);
+}
+
+#[test]
+fn visit_values() {
+
+    let jedi_table = TableDef::new("jedi");
+    let name = NamedField::<String>::field_of("name", &jedi_table);
+    let force_level = NamedField::<i8>::field_of("force_level", &jedi_table);
+
+    let query = jedi_table.select_1(&name)
+        .where_(name.is("Luke".to_string()).and(force_level.in_(vec![100i8, 120i8])));
+
+    let mut values = Values(vec![]);
+    query.accept(&mut values);
+    assert_eq!(values.0, vec![Value::String("Luke".to_string()), Value::I8(100), Value::I8(120)]);
+}
+
+#[test]
+fn visit_update() {
+
+    let jedi_table = TableDef::new("jedi");
+    let name = NamedField::<String>::field_of("name", &jedi_table);
+    let side = NamedField::<bool>::field_of("side", &jedi_table);
+
+    let query = jedi_table.update().field(side.set(&true)).where_(name.is("Luke".to_string()));
+
+    let mut columns = Columns(vec![]);
+    query.accept(&mut columns);
+    assert_eq!(columns.0, vec!["side", "name"]);
+}
+
+struct Archive {
+    name: StringField
+}
+
+impl Rewriter for Archive {
+    fn rewrite_predicate(&mut self, predicate: SharedPredicate) -> SharedPredicate {
+        let by_name = match predicate.predicate_node() {
+            PredicateNode::Is { field: Node::Field(field), .. } => field.name() == "name",
+            _ => false
+        };
+
+        if by_name { self.name.is("Yoda".to_string()) } else { predicate }
+    }
+
+    fn rewrite_from(&mut self, from: SharedFrom) -> SharedFrom {
+        match from.as_table().map(|table| table.get_table_name() == "jedi") {
+            Some(true) => TableDef::new("jedi_archive").upcast_from(),
+            _ => from
+        }
+    }
+}
+
+#[test]
+fn rewrite() {
+
+    let jedi_table = TableDef::new("jedi");
+    let name = NamedField::<String>::field_of("name", &jedi_table);
+    let force_level = NamedField::<i8>::field_of("force_level", &jedi_table);
+
+    let query = jedi_table.select_all()
+        .where_(name.is("Luke".to_string()).or(force_level.gt(100i8)));
+
+    let rewritten = query.rewrite(&mut Archive { name: name.clone() });
+    assert_sql!(rewritten, "SELECT * FROM jedi_archive WHERE (name = $1) OR (force_level > $2);");
+
+    let mut values = Values(vec![]);
+    rewritten.accept(&mut values);
+    assert_eq!(values.0, vec![Value::String("Yoda".to_string()), Value::I8(100)]);
+    assert_sql!(query, "SELECT * FROM jedi WHERE (name = $1) OR (force_level > $2);");
+}
+
+#[test]
+fn rewrite_subqueries() {
+
+    let jedi_table = TableDef::new("jedi");
+    let planet_table = TableDef::new("planet");
+    let name = NamedField::<String>::field_of("name", &jedi_table);
+    let planet_id = NamedField::<i32>::field_of("planet_id", &jedi_table);
+    let id = NamedField::<i32>::field_of("id", &planet_table);
+
+    let query = planet_table.select_all()
+        .where_(id.in_(jedi_table.select_1(&planet_id).where_(name.is("Luke".to_string()))));
+
+    let rewritten = query.rewrite(&mut Archive { name: name.clone() });
+    assert_sql!(rewritten, "SELECT * FROM planet WHERE id IN (SELECT planet_id FROM jedi_archive WHERE name = $1);");
+
+    let mut values = Values(vec![]);
+    rewritten.accept(&mut values);
+    assert_eq!(values.0, vec![Value::String("Yoda".to_string())]);
+}
+
+struct Rename {
+    full_name: StringField
+}
+
+impl Rewriter for Rename {
+    fn rewrite_expression(&mut self, expression: SharedExpression) -> SharedExpression {
+        let by_name = match expression.expression_node() {
+            Node::Field(field) => field.name() == "name",
+            _ => false
+        };
+
+        if by_name { self.full_name.upcast_expression() } else { expression }
+    }
+}
+
+#[test]
+fn rewrite_clauses() {
+
+    let jedi_table = TableDef::new("jedi");
+    let name = NamedField::<String>::field_of("name", &jedi_table);
+    let full_name = NamedField::<String>::field_of("full_name", &jedi_table);
+    let nick = NamedField::<String>::field_of("nick", &jedi_table);
+    let mut rename = Rename { full_name: full_name.clone() };
+
+    let query = jedi_table.select_1(&name).distinct_on(&[&name]).group_by(&[&name]).order_by(&name);
+    assert_sql!(query.rewrite(&mut rename), "SELECT DISTINCT ON (full_name) full_name FROM jedi GROUP BY full_name ORDER BY full_name ASC;");
+
+    let query = jedi_table.update().all().field(nick.set(&name)).returning(&[&name]);
+    assert_sql!(query.rewrite(&mut rename), "UPDATE jedi SET nick = full_name RETURNING full_name;");
+
+    let query = jedi_table.delete().all().returning(&[&name]);
+    assert_sql!(query.rewrite(&mut rename), "DELETE FROM jedi RETURNING full_name;");
+
+    let mut query = jedi_table.insert_1_for_test(&nick).returning(&[&name]);
+    query.push((InsertValue::new(&name), ));
+    assert_sql!(query.rewrite(&mut rename), "INSERT INTO jedi (nick) VALUES\n    (full_name) RETURNING full_name;");
+
+    let query = jedi_table.insert_fields(&[&nick]).from_select(jedi_table.select(&[&name]));
+    assert_sql!(query.rewrite(&mut rename), "INSERT INTO jedi (nick) SELECT full_name FROM jedi;");
+}
+
+struct Placeholders(Vec<String>);
+
+impl Visitor for Placeholders {
+    fn visit_placeholder(&mut self, placeholder: &Placeholder) {
+        self.0.push(placeholder.get_idx().to_string())
+    }
+
+    fn visit_named_placeholder(&mut self, placeholder: &NamedPlaceholder) {
+        self.0.push(placeholder.get_name().to_string())
+    }
+}
+
+#[test]
+fn visit_placeholders() {
+
+    let jedi_table = TableDef::new("jedi");
+    let name = NamedField::<String>::field_of("name", &jedi_table);
+    let side = NamedField::<bool>::field_of("side", &jedi_table);
+
+    let query = jedi_table.select_all()
+        .where_(name.is(Placeholder::named("name")).and(side.is(Placeholder::new(2))));
+
+    let mut placeholders = Placeholders(vec![]);
+    query.accept(&mut placeholders);
+    assert_eq!(placeholders.0, vec!["name", "2"]);
+}
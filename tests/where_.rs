@@ -74,7 +74,7 @@ fn exclude_and() {
     let side = NamedField::<bool>::field_of("side", &jedi_table);
     
     let query = jedi_table.select_all().exclude(name.is("Luke".to_string()).and(side.is(true)));
-    assert_sql!(query, "SELECT * FROM jedi WHERE (name != $1) AND (side != $2);"); 
+    assert_sql!(query, "SELECT * FROM jedi WHERE NOT ((name = $1) AND (side = $2));"); 
 }
 
 #[test]
@@ -85,7 +85,7 @@ fn exclude_or() {
     let side = NamedField::<bool>::field_of("side", &jedi_table);
     
     let query = jedi_table.select_all().exclude(name.is("Luke".to_string()).or(side.is(true)));
-    assert_sql!(query, "SELECT * FROM jedi WHERE (name != $1) AND (side != $2);"); 
+    assert_sql!(query, "SELECT * FROM jedi WHERE NOT ((name = $1) OR (side = $2));"); 
 }
 
 #[test]
@@ -138,6 +138,9 @@ fn predicate_is_null() {
     let query = jedi_table.select_all().exclude(force_level.is_null());
     assert_sql!(query, "SELECT * FROM jedi WHERE force_level IS NOT NULL;");     
 
+    let query = jedi_table.select_all().exclude(force_level.not_null());
+    assert_sql!(query, "SELECT * FROM jedi WHERE force_level IS NULL;");
+
 }
 
 #[test]